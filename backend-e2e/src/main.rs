@@ -19,13 +19,15 @@ fn server_socket() -> String {
 }
 
 async fn get_event(public: String, secret: Option<String>) -> Option<GetEventResponse> {
-    let url = if let Some(secret) = secret {
-        format!("{}/api/mod/event/{}/{}", server_rest(), public, secret)
+    let request = if let Some(secret) = secret {
+        reqwest::Client::new()
+            .get(format!("{}/api/mod/event/{}", server_rest(), public))
+            .bearer_auth(secret)
     } else {
-        format!("{}/api/event/{}", server_rest(), public)
+        reqwest::Client::new().get(format!("{}/api/event/{}", server_rest(), public))
     };
 
-    let res = reqwest::Client::new().get(url).send().await.unwrap();
+    let res = request.send().await.unwrap();
 
     if res.status() == StatusCode::OK {
         assert!(res
@@ -81,12 +83,8 @@ async fn add_event(name: String) -> EventInfo {
 
 async fn delete_event(id: String, secret: String) {
     let res = reqwest::Client::new()
-        .get(format!(
-            "{}/api/mod/event/delete/{}/{}",
-            server_rest(),
-            id,
-            secret
-        ))
+        .get(format!("{}/api/mod/event/{}/delete", server_rest(), id))
+        .bearer_auth(secret)
         .send()
         .await
         .unwrap();
@@ -96,7 +94,8 @@ async fn delete_event(id: String, secret: String) {
 
 async fn change_event_state(id: String, secret: String, state: u8) {
     let res = reqwest::Client::new()
-        .post(format!("{}/api/mod/event/{}/{}", server_rest(), id, secret))
+        .post(format!("{}/api/mod/event/{}", server_rest(), id))
+        .bearer_auth(secret)
         .json(&json!({
             "state": {
                 "state": state
@@ -165,12 +164,12 @@ async fn hide_question(event: String, secret: String, question_id: i64) {
 
     let res = reqwest::Client::new()
         .post(format!(
-            "{}/api/mod/event/questionmod/{}/{}/{}",
+            "{}/api/mod/event/{}/question/{}",
             server_rest(),
             event,
-            secret,
             question_id
         ))
+        .bearer_auth(secret)
         .json(&body)
         .send()
         .await
@@ -202,15 +201,17 @@ async fn get_public_question(event: &str, question_id: i64) -> shared::QuestionI
 }
 
 async fn get_mod_question(event: &str, secret: &str, question_id: i64) -> shared::QuestionItem {
-    let res = reqwest::get(format!(
-        "{}/api/mod/event/question/{}/{}/{}",
-        server_rest(),
-        event,
-        secret,
-        question_id
-    ))
-    .await
-    .unwrap();
+    let res = reqwest::Client::new()
+        .get(format!(
+            "{}/api/mod/event/{}/question/{}",
+            server_rest(),
+            event,
+            question_id
+        ))
+        .bearer_auth(secret)
+        .send()
+        .await
+        .unwrap();
     assert_eq!(res.status(), StatusCode::OK);
     res.json().await.unwrap()
 }
@@ -228,7 +229,8 @@ async fn add_question_status(event: &str, text: &str) -> StatusCode {
 
 async fn edit_event(id: &str, secret: &str, changes: &shared::ModEvent) -> StatusCode {
     reqwest::Client::new()
-        .post(format!("{}/api/mod/event/{}/{}", server_rest(), id, secret))
+        .post(format!("{}/api/mod/event/{}", server_rest(), id))
+        .bearer_auth(secret)
         .json(changes)
         .send()
         .await
@@ -244,12 +246,12 @@ async fn mod_question(
 ) -> StatusCode {
     reqwest::Client::new()
         .post(format!(
-            "{}/api/mod/event/questionmod/{}/{}/{}",
+            "{}/api/mod/event/{}/question/{}",
             server_rest(),
             event,
-            secret,
             question_id
         ))
+        .bearer_auth(secret)
         .json(&change)
         .send()
         .await
@@ -259,12 +261,8 @@ async fn mod_question(
 
 async fn delete_event_status(id: &str, secret: &str) -> StatusCode {
     reqwest::Client::new()
-        .get(format!(
-            "{}/api/mod/event/delete/{}/{}",
-            server_rest(),
-            id,
-            secret
-        ))
+        .get(format!("{}/api/mod/event/{}/delete", server_rest(), id))
+        .bearer_auth(secret)
         .send()
         .await
         .unwrap()
@@ -308,6 +306,11 @@ fn admin_pwd_hash() -> Option<String> {
         .filter(|h| !h.trim().is_empty())
 }
 
+/// Whether the server under test runs the fake payment provider (`LA_PAYMENT_FAKE=1`).
+fn fake_payment() -> bool {
+    std::env::var("LA_PAYMENT_FAKE").is_ok_and(|var| var == "1")
+}
+
 async fn admin_login(client: &reqwest::Client, name: &str, pwd_hash: &str) -> StatusCode {
     client
         .post(format!("{}/api/admin/login", server_rest()))
@@ -348,14 +351,11 @@ async fn premium_upgrade(
     secret: &str,
 ) -> shared::EventUpgradeResponse {
     let res = client
-        .post(format!(
-            "{}/api/mod/event/upgrade/{}/{}",
-            server_rest(),
-            event,
-            secret
-        ))
+        .post(format!("{}/api/mod/event/{}/upgrade", server_rest(), event))
+        .bearer_auth(secret)
         .json(&shared::ModRequestPremium {
             context: shared::ModRequestPremiumContext::Regular,
+            tier: shared::PremiumTier::SingleEvent,
         })
         .send()
         .await
@@ -379,16 +379,18 @@ async fn public_get_question_status(event: &str, question_id: i64) -> StatusCode
 
 /// GET a single question via the moderator route, returning only the status code.
 async fn mod_get_question_status(event: &str, secret: &str, question_id: i64) -> StatusCode {
-    reqwest::get(format!(
-        "{}/api/mod/event/question/{}/{}/{}",
-        server_rest(),
-        event,
-        secret,
-        question_id
-    ))
-    .await
-    .unwrap()
-    .status()
+    reqwest::Client::new()
+        .get(format!(
+            "{}/api/mod/event/{}/question/{}",
+            server_rest(),
+            event,
+            question_id
+        ))
+        .bearer_auth(secret)
+        .send()
+        .await
+        .unwrap()
+        .status()
 }
 
 /// POST an editlike returning only the status code — for state-gate error cases.
@@ -553,7 +555,14 @@ mod test {
         let version: shared::VersionInfo = serde_json::from_str(&body).unwrap();
         assert!(!version.git_hash.trim().is_empty());
         // backend reports a real (non-zero) semver triplet
-        assert!(version.version > shared::Semver { major: 0, minor: 0, patch: 0 });
+        assert!(
+            version.version
+                > shared::Semver {
+                    major: 0,
+                    minor: 0,
+                    patch: 0
+                }
+        );
     }
 
     #[tokio::test]
@@ -675,6 +684,63 @@ mod test {
         assert!(!after.info.is_deleted());
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_mod_session_and_legacy_routes() {
+        let e = add_event(TEST_EVENT_NAME.to_string()).await;
+        let secret = e.tokens.moderator_token.clone().unwrap();
+        let public = e.tokens.public_token.clone();
+
+        let client = cookie_client();
+
+        // no secret: not a moderator
+        let res = client
+            .get(format!("{}/api/mod/event/{}", server_rest(), public))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // exchange the mod link secret once for a grant cookie ...
+        let res = client
+            .post(format!(
+                "{}/api/mod/event/{}/session",
+                server_rest(),
+                public
+            ))
+            .bearer_auth(&secret)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // ... after which no request carries the secret anymore
+        let res = client
+            .get(format!("{}/api/mod/event/{}", server_rest(), public))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res
+            .json::<GetEventResponse>()
+            .await
+            .unwrap()
+            .mod_info
+            .is_some());
+
+        // secret in the path keeps working, flagged as deprecated
+        let res = reqwest::get(format!(
+            "{}/api/mod/event/{}/{}",
+            server_rest(),
+            public,
+            secret
+        ))
+        .await
+        .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("deprecation").unwrap(), "true");
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_password_protection() {
@@ -1206,6 +1272,50 @@ mod test {
         assert_eq!(socket.read().unwrap().into_text().unwrap(), "e");
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    #[ignore = "needs a server we control (fake payment); run via `just e2e-test-local`"]
+    async fn test_fake_checkout_upgrade() {
+        if !fake_payment() {
+            eprintln!("skipping test_fake_checkout_upgrade: LA_PAYMENT_FAKE not set");
+            return;
+        }
+        let e = add_event(TEST_EVENT_NAME.to_string()).await;
+        let secret = e.tokens.moderator_token.clone().unwrap();
+        let public = e.tokens.public_token.clone();
+
+        let (mut socket, _) =
+            connect(&format!("{}/push/{}", server_socket(), public)).expect("connect");
+        assert_eq!(socket.read().unwrap().into_text().unwrap(), "v:1");
+
+        // without an admin session this goes through the (fake) checkout
+        let shared::EventUpgradeResponse::Redirect { url } =
+            premium_upgrade(&reqwest::Client::new(), &public, &secret).await
+        else {
+            unreachable!("expected a checkout redirect");
+        };
+
+        // the fake pays right away and delivers a signed `checkout.session.completed` webhook
+        assert_eq!(socket.read().unwrap().into_text().unwrap(), "e");
+        let ev = get_event(public.clone(), Some(secret)).await.unwrap();
+        assert!(ev.info.is_premium());
+
+        // the moderator returning from the checkout captures the same order
+        let (_, session) = url.split_once("token=").unwrap();
+        let capture: shared::PaymentCapture = reqwest::get(format!(
+            "{}/api/mod/event/capture/{}/{}",
+            server_rest(),
+            public,
+            session
+        ))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+        assert!(capture.order_captured);
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     #[ignore = "needs a server we control (admin creds); run via `just e2e-test-local`"]
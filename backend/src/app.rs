@@ -1,10 +1,15 @@
 use async_trait::async_trait;
 use axum::extract::ws::{CloseFrame, Message, WebSocket, close_code::RESTART};
 use shared::{
-    AddEvent, Color, ContextValidation, EventInfo, EventResponseFlags, EventState, EventTags,
-    EventTokens, EventUpgradeResponse, GetEventResponse, ModEvent, ModInfo, ModQuestion,
-    PasswordValidation, PaymentCapture, QuestionItem, States, SubscriptionResponse,
-    SubscriptionUrlResponse, TagValidation,
+    AddEvent, CloneEvent, Color, ContentFilter, ContentFilterValidation, ContextItem,
+    ContextValidation, CreateEventValidation, EventData, EventInfo, EventResponseFlags,
+    EventSessions, EventState, EventTags, EventTemplate, EventTokens, EventUpgradeResponse,
+    GetEventResponse, MAX_CONTEXT_LINKS, MAX_POLLS, ModEvent, ModInfo, ModQuestion,
+    OrganizerDashboard, OrganizerEvent, ParticipantNameValidation, ParticipantNames,
+    PasswordValidation, PaymentCapture, Poll, PollOption, PollValidation, PremiumTier,
+    QuestionItem, QuestionLimits, QuestionLimitsValidation, SaveTemplate, SessionValidation,
+    States, SubscriptionResponse, SubscriptionUrlResponse, TagValidation, WebhookDeliveries,
+    WebhookDelivery, WebhookOutcome,
 };
 use std::{
     collections::HashMap,
@@ -12,7 +17,7 @@ use std::{
         Arc,
         atomic::{AtomicBool, AtomicUsize, Ordering},
     },
-    time::{Duration, Instant},
+    time::Duration,
 };
 use tokio::{
     sync::{RwLock, mpsc},
@@ -23,12 +28,21 @@ use tracing::instrument;
 use ulid::Ulid;
 
 use crate::{
-    bail, env,
+    bail,
+    content_filter::{self, Verdict},
     error::{InternalError, Result},
-    eventsdb::{ApiEventInfo, EventEntry, EventsDB, PremiumOrder},
+    eventsdb::{
+        self, ApiEventInfo, EventEntry, EventsDB, PremiumFeature, PremiumIssue, PremiumIssueReason,
+        PremiumOrder, StoredOrganizer, StoredPassword, StoredPoll, StoredSubscription,
+        StoredTemplate, StoredVoucher, StoredWebhookLog,
+    },
     mail::MailConfig,
-    payment::Payment,
+    payment::{ORDER_ID_PLACEHOLDER, PaymentProvider},
+    privacy,
     pubsub::{PubSubPublish, PubSubReceiver},
+    shortener::{Ezlime, UrlShortener},
+    similarity,
+    throttle::{Policy, Throttle},
     tracking::{EditEvent, Tracking},
     utils::timestamp_now,
     viewers::Viewers,
@@ -36,9 +50,63 @@ use crate::{
 
 pub type SharedApp = Arc<App>;
 
+const MAX_TEMPLATE_NAME_LEN: usize = 30;
+const MAX_ORGANIZER_EVENTS: usize = 500;
+const MAX_ORGANIZER_WRITE_ATTEMPTS: usize = 5;
+const MAX_INVOICE_LEN: usize = 50;
+const MAX_VOUCHER_CODE_LEN: usize = 32;
+const MIN_VOUCHER_CODE_LEN: usize = 4;
+const MAX_VOUCHER_REDEMPTIONS: usize = 1000;
+const MAX_POLL_VOTE_ATTEMPTS: usize = 5;
+/// how many of the latest questions a new one is checked against for duplicates
+const MAX_SIMILAR_CANDIDATES: usize = 100;
+const MAX_VOUCHER_RELEASE_ATTEMPTS: usize = 5;
+const MAX_WEBHOOK_LOG_ATTEMPTS: usize = 5;
+/// a delivery processing for longer than this was dropped (e.g. by a restart) and is taken over
+const WEBHOOK_CLAIM_TIMEOUT_SECS: i64 = 5 * 60;
+
+/// Per email and per client: a recovery guesses emails, so even fewer pass than logins. Not per
+/// event, guesses of other addresses would lock out the moderator.
+const RECOVER_MAIL_POLICY: Policy = Policy {
+    free_attempts: 3,
+    base: Duration::from_secs(60),
+    max: Duration::from_secs(60 * 60),
+};
+
+/// Per email and per client: every login request sends a mail, so only a few pass per window.
+const LOGIN_MAIL_POLICY: Policy = Policy {
+    free_attempts: 5,
+    base: Duration::from_secs(60),
+    max: Duration::from_secs(60 * 60),
+};
+
+/// Per client: a few typos are fine, guessing is not.
+const PWD_CLIENT_POLICY: Policy = Policy {
+    free_attempts: 5,
+    base: Duration::from_secs(2),
+    max: Duration::from_secs(15 * 60),
+};
+
+/// Per event: generous since a busy event sees plenty of honest typos, and short so a flood of
+/// guesses only delays everyone else, but it still stops a distributed enumeration of short
+/// (e.g. numeric) passwords.
+const PWD_EVENT_POLICY: Policy = Policy {
+    free_attempts: 50,
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+};
+
+/// Per event and per client: codes handed out at meetups are short enough to be guessed.
+const VOUCHER_POLICY: Policy = Policy {
+    free_attempts: 5,
+    base: Duration::from_secs(60),
+    max: Duration::from_secs(60 * 60),
+};
+
 enum Notification {
     Event,
     Question(i64),
+    Poll(i64),
     Viewers(i64),
 }
 
@@ -50,11 +118,14 @@ pub struct App {
     shutdown: Arc<AtomicBool>,
     pubsub_publish: Arc<dyn PubSubPublish>,
     viewers: Arc<dyn Viewers>,
-    payment: Arc<Payment>,
+    throttle: Arc<dyn Throttle>,
+    payment: Arc<dyn PaymentProvider>,
     tracking: Tracking,
     base_url: String,
-    ezlime_key: Option<String>,
+    shortener: Arc<dyn UrlShortener>,
     mail_config: MailConfig,
+    /// see [`crate::env::privacy_mode`]
+    privacy_mode: bool,
 }
 
 static NEXT_USER_ID: AtomicUsize = AtomicUsize::new(1);
@@ -63,13 +134,16 @@ type OutBoundChannel =
     mpsc::UnboundedSender<std::result::Result<axum::extract::ws::Message, axum::Error>>;
 
 impl App {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         eventsdb: Arc<dyn EventsDB>,
         pubsub_publish: Arc<dyn PubSubPublish>,
         viewers: Arc<dyn Viewers>,
-        payment: Arc<Payment>,
+        throttle: Arc<dyn Throttle>,
+        payment: Arc<dyn PaymentProvider>,
         tracking: Tracking,
         base_url: String,
+        privacy_mode: bool,
     ) -> Self {
         let mail_config = MailConfig::new();
 
@@ -78,30 +152,17 @@ impl App {
             pubsub_publish,
             channels: Arc::default(),
             base_url,
-            ezlime_key: Self::ezlime_key(),
+            shortener: Arc::new(Ezlime::from_env()),
             mail_config,
             payment,
             viewers,
+            throttle,
             tracking,
             shutdown: Arc::new(AtomicBool::new(false)),
+            privacy_mode,
         }
     }
 
-    fn ezlime_key() -> Option<String> {
-        let key = std::env::var(env::ENV_WEEME_KEY).ok();
-
-        if key.clone().unwrap_or_default().trim().is_empty() {
-            tracing::warn!("no url shorten token set");
-        } else {
-            tracing::info!(
-                "link shortener token set (len: {})",
-                key.clone().unwrap_or_default().trim().len()
-            );
-        }
-
-        key
-    }
-
     #[instrument(skip(self))]
     pub async fn shutdown(&self) -> Result<()> {
         tracing::info!("shutting down..");
@@ -133,6 +194,9 @@ impl App {
             tracing::warn!("shutdown drain timed out; {remaining} ws connections still open");
         }
 
+        // analytics queued until now would be lost with the process
+        self.tracking.flush().await;
+
         tracing::info!("shutting down.. done");
 
         Ok(())
@@ -153,33 +217,63 @@ impl App {
         }
     }
 
-    #[instrument(skip(self))]
-    async fn shorten_url(&self, url: &str) -> String {
-        if let Some(ezlime_key) = &self.ezlime_key
-            && !ezlime_key.trim().is_empty()
-        {
-            let now = Instant::now();
-
-            let ezlime = ezlime_rs::EzlimeApi::new(ezlime_key.clone());
-
-            match ezlime.create_short_url(url).await {
-                Ok(shortened_url) => {
-                    tracing::info!(
-                        "short url: '{}' (in {}ms)",
-                        shortened_url,
-                        now.elapsed().as_millis()
-                    );
-                    return shortened_url;
-                }
-                Err(e) => {
-                    tracing::error!("failed to create short url: {}", e);
-                }
+    /// Whether data of `e` may leave for third parties (link shortener, event content in
+    /// tracking and sentry).
+    const fn third_parties(&self, e: &ApiEventInfo) -> bool {
+        !self.privacy_mode && !e.no_third_parties
+    }
+
+    /// Load event `id`, scrubbing what the request reports to sentry if the event opted out of
+    /// third parties.
+    async fn load_entry(&self, id: &str) -> eventsdb::Result<EventEntry> {
+        let mut entry = self.eventsdb.get(id).await?;
+
+        if entry.event.no_third_parties {
+            privacy::scrub_request_scope();
+        }
+
+        if matches!(entry.event.password, StoredPassword::Legacy(_)) {
+            entry = self.migrate_password(id, entry).await;
+        }
+
+        Ok(entry)
+    }
+
+    /// Store the hash of a legacy plaintext password, once per event. Any failure leaves the
+    /// plaintext in place to be migrated on a later load.
+    async fn migrate_password(&self, id: &str, entry: EventEntry) -> EventEntry {
+        let password = entry.event.password.clone();
+
+        // argon2 is deliberately slow, keep it off the async executor
+        let migrated = match task::spawn_blocking(move || password.migrate()).await {
+            Ok(migrated @ StoredPassword::Hashed(_)) => migrated,
+            Ok(_) => return entry,
+            Err(e) => {
+                tracing::error!("password migration task of {id}: {e}");
+                return entry;
+            }
+        };
+
+        let mut updated = entry.clone();
+        updated.event.password = migrated;
+        // not an edit, `bump` would move `last_edit_unix`
+        updated.version += 1;
+
+        match self.eventsdb.put(updated.clone()).await {
+            Ok(()) => updated,
+            Err(e) => {
+                tracing::warn!("password migration of {id} not stored: {e}");
+                entry
             }
-        } else {
-            tracing::info!("no weeme key");
         }
+    }
 
-        url.to_owned()
+    #[instrument(skip(self))]
+    async fn shorten_url(&self, url: &str) -> String {
+        self.shortener
+            .shorten(url)
+            .await
+            .unwrap_or_else(|| url.to_owned())
     }
 
     #[instrument(skip(self, request))]
@@ -197,80 +291,454 @@ impl App {
             }));
         }
 
-        let premium_id = if let Some(customer) = request.customer {
-            tracing::info!("customer supplied: {}", customer);
-            Some(PremiumOrder::StripeSubscriptionId(
-                self.payment.verify_customer(customer.as_str()).await?,
-            ))
+        let subscription = self.customer_subscription(request.customer).await?;
+
+        let template = if let Some(template) = &request.template {
+            Some(self.eventsdb.get_template(template).await?)
         } else {
             None
         };
 
+        let mut e = Self::new_event(
+            request.data,
+            Self::subscription_premium(subscription.as_ref()),
+        );
+        e.no_third_parties = request.no_third_parties;
+
+        if let Some(template) = &template {
+            template.apply(&mut e);
+        }
+
+        let result = self
+            .store_new_event(e, request.test, request.moderator_email)
+            .await?;
+
+        self.subscription_add_event(subscription, &result.tokens.public_token)
+            .await;
+
+        Ok(result)
+    }
+
+    /// Create a new event with the settings of event `id`, see [`StoredTemplate::apply`].
+    pub async fn mod_clone_event(
+        &self,
+        id: String,
+        secret: String,
+        request: CloneEvent,
+    ) -> Result<EventInfo> {
+        let entry = self.load_entry(&id).await?;
+
+        let source = &entry.event;
+
+        if source.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
+
+        // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+        if source.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
+        }
+
+        let subscription = self.customer_subscription(request.customer).await?;
+
+        let mut e = Self::new_event(
+            EventData {
+                name: source.data.name.clone(),
+                description: source.data.description.clone(),
+                ..EventData::default()
+            },
+            Self::subscription_premium(subscription.as_ref()),
+        );
+
+        StoredTemplate::from_event(String::new(), String::new(), 0, source).apply(&mut e);
+        e.no_third_parties = source.no_third_parties;
+        e.password = source.password.clone();
+
+        // only the hash of the address is known, it keeps the salt it was made with so the mod
+        // link of the clone can be recovered the same way
+        if source.mod_email_hash.is_some() {
+            e.mod_email_hash.clone_from(&source.mod_email_hash);
+            e.mod_email_salt = Some(
+                source
+                    .mod_email_salt
+                    .clone()
+                    .unwrap_or_else(|| source.tokens.public_token.clone()),
+            );
+        }
+
+        if request.questions {
+            let keep_tags = e.entitled(PremiumFeature::Tags);
+
+            e.questions = source
+                .questions
+                .iter()
+                .filter(|q| !q.answered && !q.hidden && !q.screening)
+                .cloned()
+                .zip(0..)
+                .map(|(q, id)| QuestionItem {
+                    id,
+                    tag: q.tag.filter(|_| keep_tags),
+                    session: None,
+                    ..q
+                })
+                .collect();
+        }
+
+        self.tracking
+            .track_event_clone(id, e.tokens.public_token.clone(), e.questions.len());
+
+        let result = self.store_new_event(e, false, None).await?;
+
+        self.subscription_add_event(subscription, &result.tokens.public_token)
+            .await;
+
+        Ok(result)
+    }
+
+    /// Save the settings of event `id` as a template that [`AddEvent::template`] can refer to.
+    pub async fn mod_save_template(
+        &self,
+        id: String,
+        secret: String,
+        request: SaveTemplate,
+    ) -> Result<EventTemplate> {
+        let entry = self.load_entry(&id).await?;
+
+        let e = &entry.event;
+
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
+
+        // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+        if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
+        }
+
+        let name = request.name.trim();
+        let name_len = name.chars().count();
+        if name_len == 0 || name_len > MAX_TEMPLATE_NAME_LEN {
+            bail!("invalid template name length: {name_len}");
+        }
+
+        let template = StoredTemplate::from_event(
+            Ulid::new().to_string(),
+            name.to_string(),
+            timestamp_now(),
+            e,
+        );
+
+        let result = EventTemplate {
+            id: template.id.clone(),
+            name: template.name.clone(),
+        };
+
+        self.eventsdb.put_template(template).await?;
+
+        self.tracking
+            .track_event_template_saved(id, result.id.clone());
+
+        Ok(result)
+    }
+
+    /// Subscription a new event of `customer` is created under. Refuses once a webhook reported
+    /// it inactive, even if stripe still lists it as active for the rest of the period.
+    async fn customer_subscription(
+        &self,
+        customer: Option<String>,
+    ) -> Result<Option<StoredSubscription>> {
+        let Some(customer) = customer else {
+            return Ok(None);
+        };
+
+        tracing::info!("customer supplied: {}", customer);
+
+        let id = self.payment.verify_customer(customer.as_str()).await?;
+
+        Ok(Some(self.active_subscription(id, customer).await?))
+    }
+
+    async fn active_subscription(
+        &self,
+        id: String,
+        customer: String,
+    ) -> Result<StoredSubscription> {
+        let subscription = match self.eventsdb.get_subscription(&id).await {
+            Ok(subscription) => subscription,
+            Err(eventsdb::Error::ItemNotFound) => StoredSubscription {
+                id,
+                customer,
+                status: String::from("active"),
+                update_time_unix: 0,
+                events: Vec::new(),
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        if !subscription.is_active() {
+            return Err(InternalError::SubscriptionInactive(subscription.id));
+        }
+
+        Ok(subscription)
+    }
+
+    fn subscription_premium(subscription: Option<&StoredSubscription>) -> Option<PremiumOrder> {
+        subscription.map(|subscription| PremiumOrder::StripeSubscriptionId(subscription.id.clone()))
+    }
+
+    /// Remember that `event` was created under `subscription`. The event exists at this point,
+    /// failing here would only hide its mod link from the creator.
+    async fn subscription_add_event(&self, subscription: Option<StoredSubscription>, event: &str) {
+        let Some(subscription) = subscription else {
+            return;
+        };
+
+        // only appends, a webhook may have changed the status since it was loaded
+        if let Err(e) = self
+            .eventsdb
+            .add_subscription_event(&subscription, event)
+            .await
+        {
+            tracing::error!("failed to record subscription event {event}: {e}");
+        }
+    }
+
+    /// Claim webhook delivery `id` for processing. Retries of a delivery that was processed or
+    /// ignored are skipped, as are deliveries another request is processing right now. Failed
+    /// ones are processed again.
+    pub async fn webhook_claim(&self, id: &str, kind: &str) -> Result<bool> {
         let now = timestamp_now();
 
-        let request_mod_mail = request.moderator_email.clone();
+        let delivery = WebhookDelivery {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            time_unix: now,
+            outcome: WebhookOutcome::Processing,
+        };
+
+        Ok(self
+            .eventsdb
+            .claim_webhook(delivery, now - WEBHOOK_CLAIM_TIMEOUT_SECS)
+            .await?)
+    }
+
+    /// Record the `outcome` of webhook delivery `id` in the ledger and the delivery log.
+    #[instrument(skip(self))]
+    pub async fn webhook_processed(
+        &self,
+        id: String,
+        kind: String,
+        outcome: WebhookOutcome,
+    ) -> Result<()> {
+        let delivery = WebhookDelivery {
+            id,
+            kind,
+            time_unix: timestamp_now(),
+            outcome,
+        };
+
+        self.eventsdb.put_webhook(delivery.clone()).await?;
+
+        let mut attempt = 1;
+
+        loop {
+            let mut log = match self.eventsdb.get_webhook_log().await {
+                Ok(log) => log,
+                Err(eventsdb::Error::ItemNotFound) => StoredWebhookLog::default(),
+                Err(e) => return Err(e.into()),
+            };
+
+            log.record(delivery.clone());
+
+            match self.eventsdb.put_webhook_log(log).await {
+                Ok(()) => return Ok(()),
+                Err(eventsdb::Error::Concurrency) if attempt < MAX_WEBHOOK_LOG_ATTEMPTS => {
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub async fn webhook_deliveries(&self) -> Result<WebhookDeliveries> {
+        let deliveries = match self.eventsdb.get_webhook_log().await {
+            Ok(log) => log.deliveries,
+            Err(eventsdb::Error::ItemNotFound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(WebhookDeliveries { deliveries })
+    }
+
+    /// Stripe reported a new `status` for `subscription`. Events created under it keep premium,
+    /// they were paid for while it was active. It only decides whether new ones can be created,
+    /// see [`Self::customer_subscription`].
+    #[instrument(skip(self))]
+    pub async fn subscription_changed(
+        &self,
+        subscription: String,
+        customer: String,
+        status: String,
+        time_unix: i64,
+    ) -> Result<()> {
+        let update = StoredSubscription {
+            id: subscription.clone(),
+            customer,
+            status: status.clone(),
+            update_time_unix: time_unix,
+            events: Vec::new(),
+        };
+
+        // webhooks arrive out of order, a late `updated` must not revive a `deleted`
+        let Some(stored) = self.eventsdb.update_subscription(&update).await? else {
+            tracing::info!("subscription change outdated");
+            return Ok(());
+        };
+
+        self.tracking
+            .track_subscription_changed(subscription, status, stored.events.len());
+
+        Ok(())
+    }
 
-        let public_token = Ulid::new().to_string();
-        let mod_token = Ulid::new().to_string();
+    fn new_event(data: EventData, premium_id: Option<PremiumOrder>) -> ApiEventInfo {
+        let now = timestamp_now();
 
-        let mut e = ApiEventInfo {
+        ApiEventInfo {
             create_time_unix: now,
             delete_time_unix: 0,
             last_edit_unix: now,
             deleted: false,
+            premium_tier: premium_id
+                .as_ref()
+                .map(PremiumOrder::default_tier)
+                .unwrap_or_default(),
+            premium_until_unix: None,
             premium_id,
-            password: shared::EventPassword::Disabled,
+            password: StoredPassword::Disabled,
             questions: Vec::new(),
             do_screening: false,
             state: EventState {
                 state: States::Open,
             },
-            data: request.data,
+            data,
             tokens: EventTokens {
-                public_token: public_token.clone(),
-                moderator_token: Some(mod_token.clone()),
+                public_token: Ulid::new().to_string(),
+                moderator_token: Some(Ulid::new().to_string()),
             },
             context: Vec::new(),
             tags: EventTags::default(),
-        };
+            content_filter: ContentFilter::default(),
+            question_limits: QuestionLimits::default(),
+            participant_names: ParticipantNames::default(),
+            polls: Vec::new(),
+            sessions: EventSessions::default(),
+            mod_email_hash: None,
+            mod_email_salt: None,
+            premium_issue: None,
+            no_third_parties: false,
+        }
+    }
+
+    async fn store_new_event(
+        &self,
+        mut e: ApiEventInfo,
+        test: bool,
+        mod_mail: Option<String>,
+    ) -> Result<EventInfo> {
+        let now = e.create_time_unix;
 
         let url = format!("{}/event/{}", self.base_url, e.tokens.public_token);
 
         //Note: only use shortener outside of e2e tests
-        if !request.test {
-            e.data.short_url = self.shorten_url(&url).await;
+        if !test {
+            e.data.short_url = if self.third_parties(&e) {
+                self.shorten_url(&url).await
+            } else {
+                url.clone()
+            };
         }
         e.data.long_url = Some(url.clone());
 
+        if let Some(mail) = mod_mail.as_deref().filter(|mail| !mail.trim().is_empty()) {
+            e.mod_email_hash = Some(ApiEventInfo::mod_email_hash(&e.tokens.public_token, mail));
+            e.mod_email_salt = None;
+        }
+
         let result = e.clone();
+        let no_third_parties = e.no_third_parties;
 
         self.eventsdb
-            .put(EventEntry::new(e, request.test.then_some(now + 60)))
+            .put(EventEntry::new(e, test.then_some(now + 60)))
             .await?;
 
-        if let Some(mail) = request_mod_mail.as_ref() {
+        if let Some(mail) = mod_mail {
             self.send_mail(
-                public_token,
-                mail.clone(),
+                result.tokens.public_token.clone(),
+                mail,
                 result.data.name.clone(),
                 result.data.short_url.clone(),
                 self.mod_link(&result.tokens),
+                "New Event Created",
             );
         }
 
-        if !request.test {
-            self.tracking
-                .track_event_create(
-                    result.tokens.public_token.clone(),
-                    url,
-                    result.data.name.clone(),
-                )
-                .await?;
+        if !test {
+            self.tracking.redacted(no_third_parties).track_event_create(
+                result.tokens.public_token.clone(),
+                url,
+                result.data.name.clone(),
+            );
         }
 
         Ok(result.into())
     }
 
+    /// Re-send the mod link of event `id` to `email` if it is the one given at creation. The
+    /// outcome is the same whether the email matched or the event exists, only the mail tells.
+    #[instrument(skip(self, email))]
+    pub async fn recover_mod_link(&self, id: String, email: &str, client: &str) -> Result<()> {
+        let email = email.trim();
+
+        if email.is_empty() || CreateEventValidation::check_email(email).is_some() {
+            return Err(InternalError::InvalidEmail);
+        }
+
+        // the organizer id is the unsalted hash of the address
+        let email_hash = StoredOrganizer::id_from_email(email);
+
+        self.rate_limit(
+            &[
+                format!("recover/email/{email_hash}"),
+                format!("recover/client/{client}"),
+            ],
+            RECOVER_MAIL_POLICY,
+        )
+        .await?;
+
+        let e = match self.load_entry(&id).await {
+            Ok(entry) => entry.event,
+            Err(eventsdb::Error::ItemNotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        if e.deleted || !e.is_mod_email(email) {
+            tracing::info!("mod link recovery without match");
+            return Ok(());
+        }
+
+        self.send_mail(
+            id,
+            email.to_string(),
+            e.data.name.clone(),
+            e.data.short_url.clone(),
+            self.mod_link(&e.tokens),
+            "Your Live-Ask Moderator Link",
+        );
+
+        Ok(())
+    }
+
     fn mod_link(&self, tokens: &EventTokens) -> String {
         let mod_token = tokens
             .moderator_token
@@ -283,8 +751,16 @@ impl App {
         )
     }
 
-    #[instrument(skip(self))]
-    pub async fn check_event_password(&self, id: String, password: &str) -> Result<bool> {
+    /// On a match returns the password fingerprint to bind into the pwd grant. Failures are
+    /// counted per event and per `client` on that event; either one running over its [`Policy`]
+    /// locks further attempts out with [`InternalError::PasswordLockout`].
+    #[instrument(skip(self, password))]
+    pub async fn check_event_password(
+        &self,
+        id: String,
+        password: &str,
+        client: &str,
+    ) -> Result<Option<String>> {
         tracing::info!("check_event_password");
 
         let mut validation = PasswordValidation::default();
@@ -293,16 +769,75 @@ impl App {
             return Err(InternalError::PasswordValidation(validation));
         }
 
-        let e = self.eventsdb.get(&id).await?.event;
+        let event_key = format!("pwd/event/{id}");
+        let client_key = format!("pwd/{id}/client/{client}");
+
+        for key in [&event_key, &client_key] {
+            if let Some(remaining) = self.throttle.locked_for(key).await {
+                return Err(InternalError::PasswordLockout(remaining));
+            }
+        }
+
+        let e = self.load_entry(&id).await?.event;
+
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
+
+        let stored = e.password;
+        let password = password.to_string();
+
+        // argon2 is deliberately slow, keep it off the async executor
+        let matches = task::spawn_blocking({
+            let stored = stored.clone();
+            move || stored.verify(&password)
+        })
+        .await
+        .map_err(|e| InternalError::General(format!("password verify task: {e}")))?;
+
+        if matches {
+            self.throttle.reset(&client_key).await;
+
+            return Ok(stored.fingerprint());
+        }
+
+        for (key, policy, scope) in [
+            (&event_key, PWD_EVENT_POLICY, "event"),
+            (&client_key, PWD_CLIENT_POLICY, "client"),
+        ] {
+            let failures = self.throttle.fail(key).await;
+
+            if let Some(lockout) = policy.lockout(failures) {
+                tracing::warn!(scope, failures, ?lockout, "event password lockout");
+
+                self.throttle.lock(key, lockout).await;
+
+                self.tracking
+                    .track_event_password_lockout(id.clone(), scope, failures, lockout);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Verify a moderator secret without touching the event, e.g. before handing out a
+    /// moderator grant cookie.
+    #[instrument(skip(self, secret))]
+    pub async fn check_mod_token(&self, id: String, secret: &str) -> Result<()> {
+        let e = self.load_entry(&id).await?.event;
 
         if e.deleted {
             return Err(InternalError::AccessingDeletedEvent(id));
         }
 
-        Ok(e.password.matches(&Some(password.to_string())))
+        if e.tokens.moderator_token.as_deref() != Some(secret) {
+            return Err(InternalError::WrongModeratorToken(id));
+        }
+
+        Ok(())
     }
 
-    #[instrument(skip(self))]
+    #[instrument(skip(self, secret))]
     pub async fn get_event(
         &self,
         id: String,
@@ -312,7 +847,7 @@ impl App {
     ) -> Result<GetEventResponse> {
         tracing::info!("get_event");
 
-        let mut e = self.eventsdb.get(&id).await?.event;
+        let mut e = self.load_entry(&id).await?.event;
 
         if let Some(secret) = &secret {
             // fail closed: a provided secret must match a present token (a `None` token rejects).
@@ -328,8 +863,9 @@ impl App {
         }
 
         let mod_info = is_mod.then(|| ModInfo {
-            pwd: e.password.clone(),
+            pwd: e.password.redacted(),
             private_token: e.tokens.moderator_token.clone().unwrap_or_default(),
+            content_filter: e.content_filter.clone(),
         });
 
         if !is_mod && !admin {
@@ -341,17 +877,15 @@ impl App {
                 .into_iter()
                 .filter(|q| !q.hidden && !q.screening)
                 .collect::<Vec<_>>();
+
+            e.polls.retain(|p| !p.poll.hidden);
         }
 
         let time_out_masked = if admin { false } else { e.adapt_if_timedout() };
 
         // a pwd grant unlocks only while its fingerprint still matches the current password,
         // so rotating the password re-locks outstanding grants.
-        let unlocked = matches!(
-            &e.password,
-            shared::EventPassword::Enabled(pwd)
-                if pwd_grant.as_deref() == Some(crate::utils::pwd_fingerprint(pwd).as_str())
-        );
+        let unlocked = pwd_grant.is_some() && pwd_grant == e.password.fingerprint();
 
         let pwd_masked = if (e.password.is_enabled() && !unlocked) && !admin && !is_mod {
             e.mask_data();
@@ -361,7 +895,7 @@ impl App {
         };
 
         let timed_out = e.is_timed_out_and_free();
-        let viewers = if admin || e.premium() {
+        let viewers = if admin || e.entitled(PremiumFeature::ViewerCount) {
             self.viewers.count(&id).await
         } else {
             0
@@ -390,7 +924,7 @@ impl App {
         secret: Option<String>,
         question_id: i64,
     ) -> Result<QuestionItem> {
-        let e = self.eventsdb.get(&id).await?.event;
+        let e = self.load_entry(&id).await?.event;
 
         if e.deleted {
             return Err(InternalError::AccessingDeletedEvent(id));
@@ -430,7 +964,7 @@ impl App {
     ) -> Result<EventInfo> {
         tracing::info!("mod_edit_question: {:?}", state);
 
-        let mut entry = self.eventsdb.get(&id).await?;
+        let mut entry = self.load_entry(&id).await?;
         {
             let e = &mut entry.event;
 
@@ -477,13 +1011,17 @@ impl App {
         Ok(e.into())
     }
 
-    pub async fn mod_edit_event(
+    /// Fold question `question_id` into `into`: `into` keeps its text and gains the likes, the
+    /// merged question is hidden. `into` has to be another question the audience sees, not
+    /// hidden, screening or answered.
+    pub async fn mod_merge_question(
         &self,
         id: String,
         secret: String,
-        changes: ModEvent,
+        question_id: i64,
+        into: i64,
     ) -> Result<EventInfo> {
-        let mut entry = self.eventsdb.get(&id).await?;
+        let mut entry = self.load_entry(&id).await?;
 
         let e = &mut entry.event;
 
@@ -500,28 +1038,37 @@ impl App {
             return Err(InternalError::WrongModeratorToken(id));
         }
 
-        if let Some(state) = changes.state {
-            e.state = state;
-        }
-        if let Some(screening) = changes.screening {
-            e.do_screening = screening;
+        if question_id == into {
+            bail!("cannot merge question into itself");
         }
-        if let Some(password) = changes.password {
-            self.mod_edit_password(e, password).await?;
-        }
-        if let Some(current_tag) = &changes.current_tag {
-            self.mod_edit_tag(e, current_tag).await?;
-        }
-        if let Some(context_link) = &changes.context {
-            self.mod_context(e, context_link).await?;
-        }
-        if let Some(meta) = &changes.meta {
-            self.mod_meta(e, meta).await?;
-        }
-        if let Some(color) = &changes.color {
-            self.mod_color(e, color).await?;
+
+        // the likes would vanish with a target the audience does not see
+        if !e
+            .questions
+            .iter()
+            .any(|q| q.id == into && !q.hidden && !q.screening && !q.answered)
+        {
+            bail!("merge target not visible: {into}");
         }
 
+        let Some(merged_likes) = e
+            .questions
+            .iter_mut()
+            .find(|q| q.id == question_id && !q.hidden)
+            .map(|q| {
+                q.hidden = true;
+                std::mem::take(&mut q.likes)
+            })
+        else {
+            bail!("question to merge not found: {question_id}");
+        };
+
+        let Some(target) = e.questions.iter_mut().find(|q| q.id == into) else {
+            bail!("merge target not found: {into}");
+        };
+
+        target.likes = target.likes.saturating_add(merged_likes);
+
         let result = e.clone();
 
         entry.bump();
@@ -533,80 +1080,84 @@ impl App {
         Ok(result.into())
     }
 
-    pub fn subscription_url(&self) -> Result<SubscriptionUrlResponse> {
-        let url = self.payment.subscription_url()?.to_string();
-        let portal_url = self.payment.portal_login_url_cached().map(String::from);
-        Ok(SubscriptionUrlResponse { url, portal_url })
-    }
-
-    pub async fn subscription_checkout(
+    #[allow(clippy::cast_possible_wrap)]
+    pub async fn mod_add_poll(
         &self,
-        payload: shared::SubscriptionCheckout,
-    ) -> Result<SubscriptionResponse> {
-        let customer = match payload {
-            shared::SubscriptionCheckout::CheckoutId(checkout) => {
-                self.payment.subscription_checkout(checkout).await?
-            }
-            shared::SubscriptionCheckout::CustomerEmail(email) => {
-                self.payment.subscription_customer_by_email(&email).await?
-            }
-        };
-
-        tracing::info!(customer, "customer id retrieved");
-
-        let email = self.payment.customer_email(customer.as_str()).await?;
-
-        tracing::info!(email_found = %email.is_some(), "customer email retrieved");
-
-        let portal_url = match self
-            .payment
-            .customer_portal_url(&customer, &self.base_url)
-            .await
-        {
-            Ok(url) => Some(url),
-            Err(e) => {
-                tracing::warn!(error = %e, "failed to create customer portal session");
-                None
-            }
-        };
+        id: String,
+        secret: String,
+        poll: shared::AddPoll,
+    ) -> Result<EventInfo> {
+        let mut entry = self.load_entry(&id).await?;
 
-        Ok(SubscriptionResponse {
-            customer,
-            email,
-            portal_url,
-        })
-    }
+        let e = &mut entry.event;
 
-    pub async fn delete_event(&self, id: String, secret: String) -> Result<()> {
-        let mut entry = self.eventsdb.get(&id).await?;
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
 
-        let e = &mut entry.event;
+        if e.is_timed_out_and_free() {
+            return Err(InternalError::TimedOutFreeEvent(id));
+        }
 
         // fail closed: reject unless a present token equals the secret (a `None` token rejects).
         if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
             return Err(InternalError::WrongModeratorToken(id));
         }
 
-        e.deleted = true;
-        e.delete_time_unix = timestamp_now();
+        let mut validation = PollValidation::default();
+
+        validation.check(&poll.question, &poll.options);
+        if validation.has_any() {
+            return Err(InternalError::PollValidation(validation));
+        }
+
+        if e.polls.len() >= MAX_POLLS {
+            bail!("max number of polls reached");
+        }
+
+        let poll_id = e.polls.len() as i64;
+
+        e.polls.push(StoredPoll::new(Poll {
+            id: poll_id,
+            question: poll.question.trim().to_string(),
+            kind: poll.kind,
+            options: poll
+                .options
+                .iter()
+                .map(|text| PollOption {
+                    text: text.trim().to_string(),
+                    votes: 0,
+                })
+                .collect(),
+            voters: 0,
+            closed: false,
+            hidden: false,
+            create_time_unix: timestamp_now(),
+        }));
+
+        self.tracking
+            .track_event_poll_added(id.clone(), poll.kind, poll.options.len());
+
+        let result = e.clone();
 
         entry.bump();
 
         self.eventsdb.put(entry).await?;
 
-        self.notify_subscribers(&id, Notification::Event).await;
+        self.notify_subscribers(&id, Notification::Poll(poll_id))
+            .await;
 
-        Ok(())
+        Ok(result.into())
     }
 
-    pub async fn request_premium_upgrade(
+    pub async fn mod_edit_poll(
         &self,
         id: String,
         secret: String,
-        admin: bool,
-        payload: shared::ModRequestPremium,
-    ) -> Result<EventUpgradeResponse> {
-        let mut entry = self.eventsdb.get(&id).await?;
+        poll_id: i64,
+        state: shared::ModPoll,
+    ) -> Result<EventInfo> {
+        let mut entry = self.load_entry(&id).await?;
 
         let e = &mut entry.event;
 
@@ -614,640 +1165,3188 @@ impl App {
             return Err(InternalError::AccessingDeletedEvent(id));
         }
 
-        let response = if admin {
-            let upgraded = self
-                .upgrade_event(id, PremiumOrder::Admin("unknown".into()))
-                .await?;
-            if !upgraded {
-                tracing::error!("admin upgrade failed");
-            }
-            EventUpgradeResponse::AdminUpgrade
-        } else {
-            // fail closed: reject unless a present token equals the secret (a `None` token rejects).
-            if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
-                return Err(InternalError::WrongModeratorToken(id));
-            }
+        if e.is_timed_out_and_free() {
+            return Err(InternalError::TimedOutFreeEvent(id));
+        }
 
-            let mod_url = self.mod_link(&e.tokens);
-            let approve_url = self
-                .payment
-                .create_order(
-                    &e.tokens.public_token,
-                    &mod_url,
-                    &format!("{mod_url}?payment=true&token={{CHECKOUT_SESSION_ID}}"),
-                )
-                .await?;
+        // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+        if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
+        }
 
-            EventUpgradeResponse::Redirect { url: approve_url }
-        };
+        let p = e
+            .polls
+            .iter_mut()
+            .find(|p| p.poll.id == poll_id)
+            .ok_or_else(|| InternalError::General("poll not found".into()))?;
 
-        task::spawn({
-            let tracking = self.tracking.clone();
-            let event = e.tokens.public_token.clone();
-            let context = format!("{:?}", payload.context);
-            async move {
-                let _ = tracking.track_event_request_upgrade(event, context).await;
-            }
-        });
+        p.poll.closed = state.closed;
+        p.poll.hidden = state.hidden;
 
-        Ok(response)
+        let result = e.clone();
+
+        entry.bump();
+
+        self.eventsdb.put(entry).await?;
+
+        self.notify_subscribers(&id, Notification::Poll(poll_id))
+            .await;
+
+        Ok(result.into())
     }
 
-    #[instrument(skip(self))]
-    pub async fn premium_capture(
+    /// Count the ballot of `voter` (see `auth::voter_cookie`), each voter gets one per poll.
+    pub async fn vote_poll(
         &self,
         id: String,
-        stripe_order_id: String,
-    ) -> Result<PaymentCapture> {
-        tracing::info!("premium_capture");
+        poll_id: i64,
+        voter: &str,
+        vote: shared::PollVote,
+    ) -> Result<Poll> {
+        let entry = self.open_poll_entry(&id, poll_id, &vote.choices).await?;
+
+        if !self.eventsdb.claim_poll_vote(&id, poll_id, voter).await? {
+            return Err(InternalError::AlreadyVoted(poll_id));
+        }
 
-        let (event_id, complete) = self
-            .payment
-            .retrieve_event_state(stripe_order_id.clone())
-            .await?;
+        let result = self
+            .count_poll_vote(&id, poll_id, &vote.choices, entry)
+            .await;
 
-        if event_id != id {
-            return Err(InternalError::General("invalid parameter".into()));
+        if result.is_err()
+            && let Err(e) = self.eventsdb.release_poll_vote(&id, poll_id, voter).await
+        {
+            tracing::error!("failed to release poll vote {id}/{poll_id}: {e}");
         }
 
-        let order_captured = complete;
+        let poll = result?;
 
-        if order_captured {
-            self.upgrade_event(id, PremiumOrder::StripeSessionId(stripe_order_id))
-                .await?;
-        }
+        self.notify_subscribers(&id, Notification::Poll(poll_id))
+            .await;
 
-        Ok(PaymentCapture { order_captured })
+        Ok(poll)
     }
 
-    #[instrument(skip(self))]
-    pub async fn payment_webhook(&self, stripe_session_id: String, event_id: String) -> Result<()> {
-        tracing::info!("order processing");
+    /// Load the event and make sure `poll_id` is open to a ballot of `choices`.
+    async fn open_poll_entry(
+        &self,
+        id: &str,
+        poll_id: i64,
+        choices: &[usize],
+    ) -> Result<EventEntry> {
+        let entry = self.load_entry(id).await?;
 
-        if !self
-            .upgrade_event(event_id, PremiumOrder::StripeSessionId(stripe_session_id))
-            .await?
-        {
-            tracing::warn!("webhook failed");
+        let e = &entry.event;
+
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id.to_string()));
         }
 
-        Ok(())
-    }
+        if e.is_timed_out_and_free() {
+            return Err(InternalError::TimedOutFreeEvent(id.to_string()));
+        }
 
-    #[instrument(skip(self))]
-    async fn upgrade_event(&self, event: String, order_id: PremiumOrder) -> Result<bool> {
-        tracing::info!("upgrade_event");
+        if matches!(e.state.state, States::Closed) {
+            bail!("event closed");
+        }
 
-        let mut entry = self.eventsdb.get(&event).await?;
+        let valid = e
+            .polls
+            .iter()
+            .find(|p| p.poll.id == poll_id && p.poll.is_open())
+            .is_some_and(|p| p.poll.valid_choices(choices));
 
-        if entry.event.premium() {
-            tracing::info!("event already premium");
-            return Ok(true);
+        if !valid {
+            return Err(InternalError::InvalidPollVote(poll_id));
         }
 
-        entry.event.premium_id = Some(order_id.clone());
-
-        entry.bump();
+        Ok(entry)
+    }
 
-        let (name, long_url, age) = (
-            entry.event.data.name.clone(),
-            entry.event.data.long_url.clone().unwrap_or_default(),
-            entry.event.age_in_seconds(),
-        );
+    /// Add a claimed ballot to the poll counts, reloading the event when a concurrent edit
+    /// (usually another vote) wins the write.
+    async fn count_poll_vote(
+        &self,
+        id: &str,
+        poll_id: i64,
+        choices: &[usize],
+        mut entry: EventEntry,
+    ) -> Result<Poll> {
+        let mut attempt = 1;
+
+        loop {
+            let p = entry
+                .event
+                .polls
+                .iter_mut()
+                .find(|p| p.poll.id == poll_id)
+                .ok_or_else(|| InternalError::InvalidPollVote(poll_id))?;
 
-        self.eventsdb.put(entry).await?;
+            p.add_vote(choices);
 
-        self.notify_subscribers(&event, Notification::Event).await;
+            let result = p.poll.clone();
 
-        self.tracking
-            .track_event_upgrade(event.clone(), name, long_url, age, order_id.into())
-            .await?;
+            entry.bump();
 
-        Ok(true)
+            match self.eventsdb.put(entry).await {
+                Ok(()) => return Ok(result),
+                Err(eventsdb::Error::Concurrency) if attempt < MAX_POLL_VOTE_ATTEMPTS => {
+                    attempt += 1;
+                    entry = self.open_poll_entry(id, poll_id, choices).await?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
-    //TODO: fix clippy-allow
-    #[allow(clippy::cast_possible_wrap)]
-    pub async fn add_question(
+    pub async fn mod_edit_event(
         &self,
         id: String,
-        question: shared::AddQuestion,
-    ) -> Result<QuestionItem> {
-        let trimmed_question = question.text.trim().to_string();
-
-        let mut validation = shared::AddQuestionValidation::default();
+        secret: String,
+        changes: ModEvent,
+    ) -> Result<EventInfo> {
+        let mut entry = self.load_entry(&id).await?;
 
-        validation.check(&trimmed_question);
+        let e = &mut entry.event;
 
-        if validation.has_any() {
-            return Err(InternalError::AddQuestionValidation(validation));
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
         }
 
-        let mut entry = self.eventsdb.get(&id).await?;
-
-        let e = &mut entry.event;
-
         if e.is_timed_out_and_free() {
             return Err(InternalError::TimedOutFreeEvent(id));
         }
 
-        if e.questions.len() > 500 {
-            bail!("max number of questions reached");
+        // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+        if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
         }
 
-        if !matches!(e.state.state, States::Open) {
-            bail!("event not open");
+        if let Some(state) = changes.state {
+            e.state = state;
         }
-
-        if e.questions
-            .iter()
-            .any(|q| q.text.trim() == trimmed_question)
-        {
-            return Err(InternalError::DuplicateQuestion);
+        if let Some(screening) = changes.screening {
+            e.do_screening = screening;
+        }
+        if let Some(password) = changes.password {
+            self.mod_edit_password(e, password).await?;
+        }
+        if let Some(current_tag) = &changes.current_tag {
+            self.mod_edit_tag(e, current_tag)?;
+        }
+        if let Some(edit_tag) = &changes.edit_tag {
+            self.mod_manage_tags(e, edit_tag)?;
+        }
+        if let Some(context_link) = &changes.context {
+            self.mod_context(e, context_link)?;
+        }
+        if let Some(meta) = &changes.meta {
+            self.mod_meta(e, meta)?;
+        }
+        if let Some(color) = &changes.color {
+            self.mod_color(e, color);
+        }
+        if let Some(filter) = changes.content_filter {
+            self.mod_content_filter(e, filter)?;
+        }
+        if let Some(limits) = changes.question_limits {
+            self.mod_question_limits(e, limits)?;
+        }
+        if let Some(names) = changes.participant_names {
+            e.participant_names = names;
+        }
+        if let Some(session) = &changes.session {
+            self.mod_sessions(e, session)?;
         }
 
-        let question_id = e.questions.len() as i64;
-
-        let question = shared::QuestionItem {
-            text: trimmed_question,
-            answered: false,
-            create_time_unix: timestamp_now(),
-            hidden: false,
-            screening: e.do_screening,
-            id: question_id,
-            likes: 1,
-            tag: question.tag.or(e.tags.current_tag),
-        };
-
-        e.questions.push(question.clone());
+        let result = e.clone();
 
         entry.bump();
 
         self.eventsdb.put(entry).await?;
 
-        self.notify_subscribers(&id, Notification::Question(question_id))
-            .await;
-
-        self.tracking
-            .track_event_question_added(id.clone(), question_id.saturating_add(1))
-            .await?;
+        self.notify_subscribers(&id, Notification::Event).await;
 
-        Ok(question)
+        Ok(result.into())
     }
 
-    pub async fn edit_like(&self, id: String, edit: shared::EditLike) -> Result<QuestionItem> {
-        let mut entry = self.eventsdb.get(&id).await?;
-
-        let e = &mut entry.event;
-
-        if e.is_timed_out_and_free() {
-            return Err(InternalError::TimedOutFreeEvent(id));
-        }
+    /// Start a magic link login for `email`, creating the organizer on first use. Returns the
+    /// organizer id and the nonce the mailed link has to carry, see [`Self::send_login_mail`].
+    #[instrument(skip_all)]
+    pub async fn organizer_login(&self, email: &str, client: &str) -> Result<(String, String)> {
+        let email = email.trim();
 
-        if matches!(e.state.state, States::Closed) {
-            bail!("event closed");
+        if email.is_empty() || CreateEventValidation::check_email(email).is_some() {
+            return Err(InternalError::InvalidEmail);
         }
 
-        if let Some(f) = e.questions.iter_mut().find(|e| e.id == edit.question_id) {
-            f.likes = if edit.like {
-                f.likes.saturating_add(1)
-            } else {
-                f.likes.saturating_sub(1)
-            };
-
-            let res = f.clone();
+        let id = StoredOrganizer::id_from_email(email);
 
-            entry.bump();
+        self.rate_limit(
+            &[
+                format!("login/email/{id}"),
+                format!("login/client/{client}"),
+            ],
+            LOGIN_MAIL_POLICY,
+        )
+        .await?;
 
-            self.eventsdb.put(entry).await?;
+        let nonce = Ulid::new().to_string();
 
-            self.notify_subscribers(&id, Notification::Question(edit.question_id))
-                .await;
+        self.update_organizer(&id, true, |organizer| {
+            organizer.login_nonce = Some(nonce.clone());
+            Ok(())
+        })
+        .await?;
 
-            Ok(res)
-        } else {
-            bail!("question not found")
-        }
+        Ok((id, nonce))
     }
 
-    // TODO: cleanup
-    #[allow(clippy::cognitive_complexity)]
-    pub async fn push_subscriber(&self, ws: WebSocket, id: String) {
-        use futures_util::StreamExt;
-
-        let (ws_sender, mut ws_receiver) = ws.split();
-
-        let user_id = NEXT_USER_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        let send_channel = Self::create_send_channel(ws_sender);
+    pub fn send_login_mail(&self, receiver: String, token: &str) {
+        let link = format!("{}/organizer/login/{token}", self.base_url);
+        let mail = self.mail_config.clone();
 
-        self.channels
-            .write()
-            .await
-            .insert(user_id, (id.clone(), send_channel.clone()));
+        tokio::spawn(async move {
+            if let Err(e) = mail.send_login_mail(receiver, link).await {
+                tracing::error!("login mail send error: {e}");
+            }
+        });
+    }
 
-        self.viewers.add(&id).await;
+    /// Redeem the nonce of a mailed login link, every link works only once.
+    #[instrument(skip(self, nonce))]
+    pub async fn organizer_verify(&self, id: &str, nonce: &str) -> Result<()> {
+        let events = self
+            .update_organizer(id, false, |organizer| {
+                if organizer.login_nonce.as_deref() != Some(nonce) {
+                    return Err(InternalError::InvalidLogin);
+                }
 
-        self.notify_viewer_count_change(&id);
+                organizer.login_nonce = None;
 
-        tracing::info!(
-            "user connected: {} ({} total)",
-            user_id,
-            self.channels.read().await.len()
-        );
+                Ok(organizer.events.len())
+            })
+            .await?;
 
-        while let Some(result) = ws_receiver.next().await {
-            let msg = match result {
-                Ok(msg) => msg,
-                Err(e) => {
-                    tracing::info!("websocket receive err (id={}): '{}'", user_id, e);
-                    break;
-                }
-            };
+        self.tracking.track_organizer_login(events);
 
-            //allow receiving `p` for app based pings
-            if !matches!(&msg, Message::Text(text) if text=="p") {
-                match &msg {
-                    //TODO: do we need to respond manually?
-                    Message::Ping(_) => tracing::info!("received msg:ping"),
-                    Message::Pong(_) => tracing::warn!("received msg:pong"),
-                    Message::Text(txt) => tracing::warn!("received msg:text: '{txt}'"),
-                    Message::Binary(bin) => tracing::warn!("received msg:binary: {}b", bin.len()),
-                    Message::Close(frame) => tracing::info!("received msg:close: {frame:?}"),
-                }
+        Ok(())
+    }
 
-                let (disconnect, sent_data) = match msg {
-                    Message::Ping(_) | Message::Pong(_) => (false, false),
-                    Message::Close(_) => (true, false),
-                    _ => (true, true),
-                };
+    /// Add event `id` to the account of `organizer`, proven by the moderator secret.
+    #[instrument(skip(self, secret))]
+    pub async fn organizer_add_event(
+        &self,
+        organizer: &str,
+        id: String,
+        secret: &str,
+    ) -> Result<()> {
+        self.check_mod_token(id.clone(), secret).await?;
 
-                if disconnect {
-                    if sent_data {
-                        tracing::warn!("user:{} sent data, disconnecting", user_id);
-                    }
-                    break;
+        let added = self
+            .update_organizer(organizer, false, |organizer| {
+                if organizer.events.contains(&id) {
+                    return Ok(false);
                 }
-            }
 
-            if self.is_shutting_down() {
-                tracing::info!("shutdown: close client connection [{user_id}]");
-
-                if let Err(e) = send_channel.send(Ok(Message::Close(Some(CloseFrame {
-                    code: RESTART,
-                    reason: "server shutdown".into(),
-                })))) {
-                    tracing::error!("shutdown error: {e}");
+                if organizer.events.len() >= MAX_ORGANIZER_EVENTS {
+                    bail!("max organizer events reached");
                 }
 
-                break;
-            }
-        }
-
-        tracing::info!(
-            "user disconnected: {} ({} remain)",
-            user_id,
-            self.channels.read().await.len().saturating_sub(1)
-        );
+                organizer.events.push(id.clone());
 
-        self.viewers.remove(&id).await;
+                Ok(true)
+            })
+            .await?;
 
-        //Note: lets not spam everyone if its a shutdown
-        if !self.is_shutting_down() {
-            self.notify_viewer_count_change(&id);
+        if added {
+            self.tracking.track_organizer_event_added(id);
         }
 
-        self.channels.write().await.remove(&user_id);
-    }
-
-    fn is_shutting_down(&self) -> bool {
-        self.shutdown.load(Ordering::Relaxed)
+        Ok(())
     }
 
-    fn notify_viewer_count_change(&self, event: &str) {
-        let event = event.to_string();
-        let app = self.clone();
-
-        tokio::spawn(async move {
-            let count = app.viewers.count(&event).await;
-
-            tracing::info!("notify viewer count: {count}");
-
-            app.notify_subscribers(&event, Notification::Viewers(count))
-                .await;
-        });
+    #[instrument(skip(self))]
+    pub async fn organizer_remove_event(&self, organizer: &str, id: &str) -> Result<()> {
+        self.update_organizer(organizer, false, |organizer| {
+            organizer.events.retain(|event| event != id);
+            Ok(())
+        })
+        .await
     }
 
-    fn create_send_channel(
-        ws_sender: futures_util::stream::SplitSink<WebSocket, axum::extract::ws::Message>,
-    ) -> OutBoundChannel {
-        use futures_util::FutureExt;
-        use futures_util::StreamExt;
-        use tokio_stream::wrappers::UnboundedReceiverStream;
+    /// Events owned by `organizer` including their moderator tokens, this is how organizers
+    /// recover a lost mod link.
+    #[instrument(skip(self))]
+    pub async fn organizer_dashboard(&self, organizer: &str) -> Result<OrganizerDashboard> {
+        let organizer = self.organizer(organizer).await?;
 
-        let (sender, receiver) = mpsc::unbounded_channel();
-        let rx = UnboundedReceiverStream::new(receiver);
+        let mut events = Vec::with_capacity(organizer.events.len());
 
-        tokio::task::spawn(rx.forward(ws_sender).map(|result| {
-            if let Err(e) = result {
-                let error_string_lowcase = e.to_string().to_lowercase();
-                let well_known = error_string_lowcase.starts_with("connection closed normally")
-                    || error_string_lowcase.starts_with("trying to work with closed connection");
-                if !well_known {
-                    tracing::warn!("websocket send error: {}", e);
-                }
+        for id in organizer.events.iter().rev() {
+            match self.load_entry(id).await {
+                Ok(entry) => events.push(Self::organizer_event(entry.event)),
+                Err(e) => tracing::warn!("organizer event {id} not loaded: {e}"),
             }
-        }));
+        }
 
-        sender
+        Ok(OrganizerDashboard { events })
     }
 
-    async fn notify_subscribers(&self, event_id: &str, n: Notification) {
-        let msg = match n {
-            Notification::Event => "e".to_string(),
-            Notification::Question(id) => format!("q:{id}"),
-            Notification::Viewers(count) => format!("v:{count}"),
-        };
-
-        self.pubsub_publish.publish(event_id, &msg).await;
-    }
+    fn organizer_event(e: ApiEventInfo) -> OrganizerEvent {
+        let premium = e.premium();
+        let mut tokens = e.tokens;
 
-    fn send_mail(
-        &self,
-        event_id: String,
-        receiver: String,
-        event_name: String,
-        public_link: String,
-        mod_link: String,
-    ) {
-        if receiver.trim().is_empty() {
-            tracing::debug!("mail not sent, no receiver specified");
-            return;
+        if e.deleted {
+            tokens.moderator_token = None;
         }
 
-        let mail = self.mail_config.clone();
+        OrganizerEvent {
+            tokens,
+            name: e.data.name,
+            create_time_unix: e.create_time_unix,
+            questions: e.questions.len(),
+            likes: e.questions.iter().map(|q| q.likes).sum(),
+            premium,
+            premium_until_unix: e.premium_until_unix.filter(|_| premium),
+            deleted: e.deleted,
+        }
+    }
 
-        tokio::spawn(async move {
-            if let Err(e) = mail
-                .send_mail(
-                    event_id,
-                    receiver.clone(),
-                    event_name,
-                    public_link,
-                    mod_link,
-                )
-                .await
-            {
-                tracing::error!("mail send error: {e}");
-            }
-        });
+    /// An organizer that does not exist (anymore) is as good as a failed login.
+    async fn organizer(&self, id: &str) -> Result<StoredOrganizer> {
+        match self.eventsdb.get_organizer(id).await {
+            Err(eventsdb::Error::ItemNotFound) => Err(InternalError::InvalidLogin),
+            res => Ok(res?),
+        }
     }
 
-    async fn mod_edit_password(
+    /// Apply `update` to organizer `id` and store it, starting over on a fresh copy when a
+    /// concurrent write got there first. With `create` a missing organizer is created.
+    async fn update_organizer<T>(
         &self,
-        e: &mut ApiEventInfo,
-        password: shared::EventPassword,
-    ) -> Result<()> {
-        let edit_type = match (e.password.is_enabled(), password.is_enabled()) {
-            (false, true) => Some(EditEvent::Enabled),
-            (true, false) => Some(EditEvent::Disabled),
-            (true, true) => Some(EditEvent::Changed),
-            _ => None,
-        };
+        id: &str,
+        create: bool,
+        mut update: impl FnMut(&mut StoredOrganizer) -> Result<T> + Send,
+    ) -> Result<T> {
+        let mut attempt = 1;
+
+        loop {
+            let mut organizer = match self.eventsdb.get_organizer(id).await {
+                Ok(organizer) => organizer,
+                Err(eventsdb::Error::ItemNotFound) if create => StoredOrganizer {
+                    id: id.to_string(),
+                    create_time_unix: timestamp_now(),
+                    ..StoredOrganizer::default()
+                },
+                Err(eventsdb::Error::ItemNotFound) => return Err(InternalError::InvalidLogin),
+                Err(e) => return Err(e.into()),
+            };
 
-        if let Some(edit_type) = edit_type {
-            self.tracking
-                .track_event_password_set(e.tokens.public_token.clone(), edit_type)
-                .await?;
-        }
+            let result = update(&mut organizer)?;
 
-        e.password = password;
+            organizer.version += 1;
 
-        Ok(())
+            match self.eventsdb.put_organizer(organizer).await {
+                Ok(()) => return Ok(result),
+                Err(eventsdb::Error::Concurrency) if attempt < MAX_ORGANIZER_WRITE_ATTEMPTS => {
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
-    async fn mod_edit_tag(
-        &self,
-        e: &mut ApiEventInfo,
-        current_tag: &shared::CurrentTag,
-    ) -> Result<()> {
-        if !e.premium() {
-            return Err(InternalError::PremiumOnlyFeature(
-                e.tokens.public_token.clone(),
-            ));
+    /// Count a request against each of `keys`, refusing it once any of them is over `policy`.
+    async fn rate_limit(&self, keys: &[String], policy: Policy) -> Result<()> {
+        for key in keys {
+            if let Some(remaining) = self.throttle.locked_for(key).await {
+                return Err(InternalError::RateLimited(remaining));
+            }
         }
 
-        let edit_type = match (e.tags.current_tag.is_some(), current_tag.is_enabled()) {
-            (false, true) => Some(EditEvent::Enabled),
-            (true, false) => Some(EditEvent::Disabled),
-            (true, true) => Some(EditEvent::Changed),
-            _ => None,
-        };
+        let mut refused = None;
 
-        if let Some(edit_type) = edit_type {
-            self.tracking
-                .track_event_tag_set(e.tokens.public_token.clone(), edit_type, e.age_in_seconds())
-                .await?;
-        }
+        for key in keys {
+            let requests = self.throttle.fail(key).await;
 
-        if let shared::CurrentTag::Enabled(tag) = &current_tag {
-            let mut validation = TagValidation::default();
-            validation.check(tag);
+            if let Some(lockout) = policy.lockout(requests) {
+                tracing::warn!(key, requests, ?lockout, "rate limit lockout");
 
-            if validation.has_any() {
-                return Err(InternalError::TagValidation(validation));
-            }
+                self.throttle.lock(key, lockout).await;
 
-            if !e.tags.set_or_add_tag(tag) {
-                bail!("max tags reached");
+                refused = refused.max(Some(lockout));
             }
-        } else {
-            e.tags.current_tag = None;
         }
 
-        Ok(())
+        refused.map_or(Ok(()), |lockout| Err(InternalError::RateLimited(lockout)))
     }
 
-    async fn mod_context(
-        &self,
-        e: &mut ApiEventInfo,
-        context_link: &shared::EditContextLink,
-    ) -> Result<()> {
-        if !e.premium() {
-            return Err(InternalError::PremiumOnlyFeature(
-                e.tokens.public_token.clone(),
-            ));
-        }
-
-        match context_link {
-            shared::EditContextLink::Disabled => e.context = vec![],
-            shared::EditContextLink::Enabled(item) => {
-                let mut validation = ContextValidation::default();
+    pub fn subscription_url(&self) -> Result<SubscriptionUrlResponse> {
+        let url = self.payment.subscription_url()?;
+        let portal_url = self.payment.portal_login_url_cached();
+        Ok(SubscriptionUrlResponse { url, portal_url })
+    }
 
-                validation.check(&item.label, &item.url);
-                if validation.has_any() {
-                    return Err(InternalError::ContextValidation(validation));
-                }
+    pub async fn subscription_checkout(
+        &self,
+        payload: shared::SubscriptionCheckout,
+    ) -> Result<SubscriptionResponse> {
+        let customer = match payload {
+            shared::SubscriptionCheckout::CheckoutId(checkout) => {
+                self.payment.subscription_checkout(checkout).await?
+            }
+            shared::SubscriptionCheckout::CustomerEmail(email) => {
+                self.payment.subscription_customer_by_email(&email).await?
+            }
+        };
 
-                e.context = vec![item.clone()];
+        tracing::info!(customer, "customer id retrieved");
 
-                self.tracking
-                    .track_event_context_set(e.tokens.public_token.clone(), &item.label, &item.url)
-                    .await?;
+        let email = self.payment.customer_email(customer.as_str()).await?;
+
+        tracing::info!(email_found = %email.is_some(), "customer email retrieved");
+
+        let portal_url = match self
+            .payment
+            .customer_portal_url(&customer, &self.base_url)
+            .await
+        {
+            Ok(url) => Some(url),
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to create customer portal session");
+                None
             }
+        };
+
+        Ok(SubscriptionResponse {
+            customer,
+            email,
+            portal_url,
+        })
+    }
+
+    pub async fn delete_event(&self, id: String, secret: String) -> Result<()> {
+        let mut entry = self.load_entry(&id).await?;
+
+        let e = &mut entry.event;
+
+        // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+        if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
         }
 
+        e.deleted = true;
+        e.delete_time_unix = timestamp_now();
+
+        entry.bump();
+
+        self.eventsdb.put(entry).await?;
+
+        self.notify_subscribers(&id, Notification::Event).await;
+
         Ok(())
     }
 
-    async fn mod_meta(&self, e: &mut ApiEventInfo, edit: &shared::EditMetaData) -> Result<()> {
-        if !shared::EventInfo::during_first_day(e.create_time_unix) {
-            bail!("event meta can only be changed during first 24h")
+    pub async fn request_premium_upgrade(
+        &self,
+        id: String,
+        secret: String,
+        admin: bool,
+        payload: shared::ModRequestPremium,
+    ) -> Result<EventUpgradeResponse> {
+        let mut entry = self.load_entry(&id).await?;
+
+        let e = &mut entry.event;
+
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
         }
 
-        let validation =
-            shared::CreateEventValidation::default().check(&edit.title, &edit.description, "");
-        if validation.has_any() {
-            return Err(InternalError::MetaValidation(edit.clone()));
+        let response = if admin {
+            let upgraded = self
+                .upgrade_event(
+                    id,
+                    PremiumOrder::Admin("unknown".into()),
+                    payload.tier,
+                    None,
+                )
+                .await?;
+            if !upgraded {
+                tracing::error!("admin upgrade failed");
+            }
+            EventUpgradeResponse::AdminUpgrade
+        } else {
+            // fail closed: reject unless a present token equals the secret (a `None` token rejects).
+            if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+                return Err(InternalError::WrongModeratorToken(id));
+            }
+
+            // there is one price, other tiers are sold by invoice or voucher
+            if payload.tier != PremiumTier::SingleEvent {
+                bail!("only the single event tier can be bought");
+            }
+
+            let mod_url = self.mod_link(&e.tokens);
+            let approve_url = self
+                .payment
+                .create_order(
+                    &e.tokens.public_token,
+                    &mod_url,
+                    &format!("{mod_url}?payment=true&token={ORDER_ID_PLACEHOLDER}"),
+                )
+                .await?;
+
+            EventUpgradeResponse::Redirect { url: approve_url }
+        };
+
+        self.tracking.track_event_request_upgrade(
+            e.tokens.public_token.clone(),
+            format!("{:?}", payload.context),
+        );
+
+        Ok(response)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn premium_capture(&self, id: String, order_id: String) -> Result<PaymentCapture> {
+        tracing::info!("premium_capture");
+
+        let order = self.payment.verify_order(&order_id).await?;
+
+        if order.event != id {
+            return Err(InternalError::General("invalid parameter".into()));
         }
 
-        e.data.name.clone_from(&edit.title);
-        e.data.description.clone_from(&edit.description);
+        let order_captured = order.paid;
 
-        self.tracking
-            .track_event_meta_change(e.tokens.public_token.clone(), edit)
+        if order_captured {
+            self.upgrade_event(
+                id,
+                self.payment.premium_order(order_id),
+                PremiumTier::SingleEvent,
+                None,
+            )
             .await?;
+        }
+
+        Ok(PaymentCapture { order_captured })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn payment_webhook(&self, stripe_session_id: String, event_id: String) -> Result<()> {
+        tracing::info!("order processing");
+
+        if !self
+            .upgrade_event(
+                event_id,
+                PremiumOrder::StripeSessionId(stripe_session_id),
+                PremiumTier::SingleEvent,
+                None,
+            )
+            .await?
+        {
+            tracing::warn!("webhook failed");
+        }
 
         Ok(())
     }
 
-    async fn mod_color(&self, e: &mut ApiEventInfo, color: &shared::EditColor) -> Result<()> {
-        e.data.color = Some(Color(color.0.clone()));
+    /// Upgrade event `id` paid outside any payment provider, once an admin saw the invoice
+    /// paid. The invoice decides the tier and how long premium lasts.
+    #[instrument(skip(self))]
+    pub async fn invoice_paid(&self, id: String, paid: &shared::AdminInvoicePaid) -> Result<()> {
+        let invoice = paid.invoice.trim();
+        let invoice_len = invoice.chars().count();
+        if invoice_len == 0 || invoice_len > MAX_INVOICE_LEN {
+            bail!("invalid invoice number length: {invoice_len}");
+        }
 
-        self.tracking
-            .track_event_color_change(e.tokens.public_token.clone(), color, e.premium())
-            .await?;
+        if let Some(until) = paid.until_unix {
+            if until <= timestamp_now() {
+                bail!("premium would already be expired: {until}");
+            }
+        }
+
+        if self.load_entry(&id).await?.event.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
+
+        self.upgrade_event(
+            id,
+            PremiumOrder::Invoice(invoice.to_string()),
+            paid.tier,
+            paid.until_unix,
+        )
+        .await?;
 
         Ok(())
     }
-}
 
-#[async_trait]
-impl PubSubReceiver for App {
-    async fn notify(&self, topic: &str, payload: &str) {
-        let msg = Message::Text(payload.to_string());
+    /// Issue a voucher code, refusing one that exists already.
+    #[instrument(skip(self))]
+    pub async fn create_voucher(
+        &self,
+        create: &shared::AdminCreateVoucher,
+    ) -> Result<shared::VoucherInfo> {
+        let code = StoredVoucher::normalize_code(&create.code);
+        let code_len = code.chars().count();
+        if !(MIN_VOUCHER_CODE_LEN..=MAX_VOUCHER_CODE_LEN).contains(&code_len)
+            || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            bail!("invalid voucher code: {code}");
+        }
 
-        //TODO: lookup subscriber based on topic name
-        // send() is sync, so we hold the read guard across the loop instead of cloning the map;
-        // the caller already spawns notify(), so no inner spawn.
-        let receivers = self.channels.read().await;
-        for (_user_id, (_id, c)) in receivers.iter().filter(|(_, (id, _))| id.as_str() == topic) {
-            if let Err(e) = c.send(Ok(msg.clone())) {
-                if let Err(inner_err) = &e.0 {
-                    tracing::error!("pubsub send err: {} ({})", e, inner_err);
-                } else {
-                    tracing::info!("pubsub not sent: {}", e);
+        if create.max_redemptions == 0 || create.max_redemptions > MAX_VOUCHER_REDEMPTIONS {
+            bail!("invalid voucher redemptions: {}", create.max_redemptions);
+        }
+
+        if let Some(until) = create.valid_until_unix {
+            if until <= timestamp_now() {
+                bail!("voucher would already be expired: {until}");
+            }
+        }
+
+        let voucher = StoredVoucher {
+            code: code.clone(),
+            note: create.note.trim().to_string(),
+            create_time_unix: timestamp_now(),
+            max_redemptions: create.max_redemptions,
+            events: Vec::new(),
+            tier: create.tier,
+            valid_until_unix: create.valid_until_unix,
+            version: 0,
+        };
+
+        match self.eventsdb.put_voucher(voucher.clone()).await {
+            Err(eventsdb::Error::Concurrency) => bail!("voucher exists: {code}"),
+            res => res?,
+        }
+
+        Ok(Self::voucher_info(voucher))
+    }
+
+    pub async fn voucher(&self, code: &str) -> Result<shared::VoucherInfo> {
+        Ok(Self::voucher_info(
+            self.eventsdb
+                .get_voucher(&StoredVoucher::normalize_code(code))
+                .await?,
+        ))
+    }
+
+    fn voucher_info(voucher: StoredVoucher) -> shared::VoucherInfo {
+        shared::VoucherInfo {
+            code: voucher.code,
+            note: voucher.note,
+            create_time_unix: voucher.create_time_unix,
+            max_redemptions: voucher.max_redemptions,
+            events: voucher.events,
+            tier: voucher.tier,
+            valid_until_unix: voucher.valid_until_unix,
+        }
+    }
+
+    /// Upgrade event `id` with a voucher instead of paying. An event that is premium already
+    /// does not use up a redemption.
+    #[instrument(skip(self, secret))]
+    pub async fn redeem_voucher(
+        &self,
+        id: String,
+        secret: String,
+        code: &str,
+        client: &str,
+    ) -> Result<()> {
+        let e = self.load_entry(&id).await?.event;
+
+        if e.deleted {
+            return Err(InternalError::AccessingDeletedEvent(id));
+        }
+
+        if e.tokens.moderator_token.as_deref() != Some(secret.as_str()) {
+            return Err(InternalError::WrongModeratorToken(id));
+        }
+
+        // only after the secret, or anyone could lock the moderator out of the event's limit
+        self.rate_limit(
+            &[
+                format!("voucher/event/{id}"),
+                format!("voucher/client/{client}"),
+            ],
+            VOUCHER_POLICY,
+        )
+        .await?;
+
+        let code = StoredVoucher::normalize_code(code);
+
+        let mut voucher = match self.eventsdb.get_voucher(&code).await {
+            Err(eventsdb::Error::ItemNotFound) => return Err(InternalError::VoucherInvalid(code)),
+            res => res?,
+        };
+
+        // no redemption spent on a tier the event already has
+        if !e.upgradable_to(voucher.tier) {
+            tracing::info!("event already premium");
+            return Ok(());
+        }
+
+        if !voucher.redeem(&id, timestamp_now()) {
+            return Err(InternalError::VoucherInvalid(code));
+        }
+
+        let tier = voucher.tier;
+
+        // a concurrent redemption fails here rather than going over the limit
+        self.eventsdb.put_voucher(voucher).await?;
+
+        if let Err(e) = self
+            .upgrade_event(id.clone(), PremiumOrder::Voucher(code.clone()), tier, None)
+            .await
+        {
+            self.release_voucher(&code, &id).await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Give back the redemption of voucher `code` by `event`, so it can be redeemed again once
+    /// upgrading works.
+    async fn release_voucher(&self, code: &str, event: &str) {
+        for _ in 0..MAX_VOUCHER_RELEASE_ATTEMPTS {
+            let result = match self.eventsdb.get_voucher(code).await {
+                Ok(mut voucher) => {
+                    if !voucher.release(event) {
+                        return;
+                    }
+
+                    self.eventsdb.put_voucher(voucher).await
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => return,
+                Err(eventsdb::Error::Concurrency) => {}
+                Err(e) => {
+                    tracing::error!("failed to release voucher {code} of {event}: {e}");
+                    return;
                 }
             }
         }
+
+        tracing::error!("voucher {code} of {event} not released, too many concurrent writes");
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::{
-        eventsdb::{InMemoryEventsDB, PremiumOrder, event_key},
-        pubsub::{PubSubInMemory, PubSubReceiverInMemory},
-        viewers::MockViewers,
-    };
-    use pretty_assertions::{assert_eq, assert_ne};
-    use shared::{
-        AddQuestion, CurrentTag, EventData, TEST_EVENT_DESC, TEST_EVENT_NAME, TEST_VALID_QUESTION,
-        TagId,
-    };
-    use std::sync::Arc;
+    /// Event paid for by a charge that arrived without it in the webhook payload.
+    pub async fn charge_event(&self, charge: &str) -> Result<Option<String>> {
+        Ok(self.payment.charge_event(charge).await?)
+    }
 
-    #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_event_create_fail_validation() {
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
+    /// Event paid for by the checkout behind `payment_intent`, for a refunded charge without
+    /// it in its metadata.
+    pub async fn payment_intent_event(&self, payment_intent: &str) -> Result<Option<String>> {
+        Ok(self.payment.payment_intent_event(payment_intent).await?)
+    }
+
+    /// Record a payment problem on `event`. Refunds (`reference` is the payment intent) and
+    /// failed async payments (the checkout session) take back premium bought by exactly that
+    /// checkout, disputes only flag the event until they are resolved.
+    #[instrument(skip(self))]
+    pub async fn premium_issue(
+        &self,
+        event: String,
+        reason: PremiumIssueReason,
+        reference: String,
+    ) -> Result<()> {
+        let mut entry = self.load_entry(&event).await?;
+
+        let e = &mut entry.event;
+
+        // webhooks are retried, the same report must not overwrite what it revoked before
+        if e.premium_issue
+            .as_ref()
+            .is_some_and(|issue| issue.reason == reason && issue.reference == reference)
+        {
+            tracing::info!("premium issue already recorded");
+            return Ok(());
+        }
+
+        let revoke = match reason {
+            PremiumIssueReason::Refunded => match &e.premium_id {
+                Some(PremiumOrder::StripeSessionId(session)) => {
+                    self.payment
+                        .checkout_payment_intent(session)
+                        .await?
+                        .as_ref()
+                        == Some(&reference)
+                }
+                _ => false,
+            },
+            PremiumIssueReason::AsyncPaymentFailed => {
+                e.premium_id == Some(PremiumOrder::StripeSessionId(reference.clone()))
+            }
+            PremiumIssueReason::Disputed => false,
+        };
+
+        let revoked = if revoke { e.premium_id.take() } else { None };
+
+        tracing::warn!(?reason, revoked = revoked.is_some(), "premium issue");
+
+        e.premium_issue = Some(PremiumIssue {
+            reason,
+            reference,
+            time_unix: timestamp_now(),
+            revoked: revoked.clone(),
+        });
+
+        entry.bump();
+
+        self.eventsdb.put(entry).await?;
+
+        self.notify_subscribers(&event, Notification::Event).await;
+
+        self.tracking
+            .track_event_premium_issue(event, reason.into(), revoked.is_some());
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn upgrade_event(
+        &self,
+        event: String,
+        order_id: PremiumOrder,
+        tier: PremiumTier,
+        until_unix: Option<i64>,
+    ) -> Result<bool> {
+        tracing::info!("upgrade_event");
+
+        let mut entry = self.load_entry(&event).await?;
+
+        // an expired premium can be bought again, a retention-only one upgraded
+        if !entry.event.upgradable_to(tier) {
+            tracing::info!("event already premium");
+            return Ok(true);
+        }
+
+        entry.event.premium_id = Some(order_id.clone());
+        entry.event.premium_tier = tier;
+        entry.event.premium_until_unix = until_unix;
+
+        entry.bump();
+
+        let (name, long_url, age, no_third_parties) = (
+            entry.event.data.name.clone(),
+            entry.event.data.long_url.clone().unwrap_or_default(),
+            entry.event.age_in_seconds(),
+            entry.event.no_third_parties,
+        );
+
+        self.eventsdb.put(entry).await?;
+
+        self.notify_subscribers(&event, Notification::Event).await;
+
+        self.tracking
+            .redacted(no_third_parties)
+            .track_event_upgrade(event.clone(), name, long_url, age, order_id.into());
+
+        Ok(true)
+    }
+
+    //TODO: fix clippy-allow
+    #[allow(clippy::cast_possible_wrap)]
+    pub async fn add_question(
+        &self,
+        id: String,
+        question: shared::AddQuestion,
+    ) -> Result<QuestionItem> {
+        let trimmed_question = question.text.trim().to_string();
+
+        let mut entry = self.load_entry(&id).await?;
+
+        let e = &mut entry.event;
+
+        let mut validation = shared::AddQuestionValidation::default();
+
+        validation.check(&trimmed_question, &e.question_limits);
+
+        if validation.has_any() {
+            return Err(InternalError::AddQuestionValidation(validation));
+        }
+
+        let mut name_validation = ParticipantNameValidation::default();
+
+        name_validation.check(question.name.as_deref(), e.participant_names);
+
+        if name_validation.has_any() {
+            return Err(InternalError::ParticipantNameValidation(name_validation));
+        }
+
+        let name = question
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from);
+
+        if e.is_timed_out_and_free() {
+            return Err(InternalError::TimedOutFreeEvent(id));
+        }
+
+        if e.questions.len() >= e.question_limits.max_questions {
+            bail!("max number of questions reached");
+        }
+
+        let now = timestamp_now();
+
+        if !e.sessions.effective_state(e.state, now).is_open() {
+            bail!("event not open");
+        }
+
+        let session = e.sessions.active(now).map(|s| s.id);
+
+        if e.questions
+            .iter()
+            .any(|q| q.text.trim() == trimmed_question)
+        {
+            return Err(InternalError::DuplicateQuestion);
+        }
+
+        if !question.allow_similar {
+            // only suggest what the asker can actually see and like, and only recent ones to
+            // keep the comparisons bounded on big events
+            if let Some(similar) = e
+                .questions
+                .iter()
+                .rev()
+                .filter(|q| !q.hidden && !q.screening)
+                .take(MAX_SIMILAR_CANDIDATES)
+                .find(|q| similarity::is_similar(&q.text, &trimmed_question))
+            {
+                return Err(InternalError::SimilarQuestion(similar.clone()));
+            }
+        }
+
+        let filtered_text = name.as_ref().map_or_else(
+            || trimmed_question.clone(),
+            |name| format!("{name}: {trimmed_question}"),
+        );
+
+        let screening = match content_filter::check(&e.content_filter, &filtered_text) {
+            Verdict::Pass => e.do_screening,
+            Verdict::Screen => {
+                tracing::info!("question flagged by content filter: screening");
+                true
+            }
+            Verdict::Reject => return Err(InternalError::QuestionFiltered),
+        };
+
+        let question_id = e.questions.len() as i64;
+
+        let question = shared::QuestionItem {
+            text: trimmed_question,
+            answered: false,
+            create_time_unix: now,
+            hidden: false,
+            screening,
+            id: question_id,
+            likes: 1,
+            // a tag deleted (or never created) since the client loaded the event is ignored
+            tag: question
+                .tag
+                .filter(|tag| e.tags.get(*tag).is_some())
+                .or(e.tags.current_tag),
+            name,
+            session,
+        };
+
+        e.questions.push(question.clone());
+
+        entry.bump();
+
+        self.eventsdb.put(entry).await?;
+
+        self.notify_subscribers(&id, Notification::Question(question_id))
+            .await;
+
+        self.tracking
+            .track_event_question_added(id.clone(), question_id.saturating_add(1));
+
+        Ok(question)
+    }
+
+    pub async fn edit_like(&self, id: String, edit: shared::EditLike) -> Result<QuestionItem> {
+        let mut entry = self.load_entry(&id).await?;
+
+        let e = &mut entry.event;
+
+        if e.is_timed_out_and_free() {
+            return Err(InternalError::TimedOutFreeEvent(id));
+        }
+
+        if matches!(e.state.state, States::Closed) {
+            bail!("event closed");
+        }
+
+        let sessions = &e.sessions;
+        let state = e.state;
+
+        if let Some(f) = e.questions.iter_mut().find(|e| e.id == edit.question_id) {
+            if sessions.question_state(state, f.session).is_closed() {
+                bail!("session closed");
+            }
+
+            f.likes = if edit.like {
+                f.likes.saturating_add(1)
+            } else {
+                f.likes.saturating_sub(1)
+            };
+
+            let res = f.clone();
+
+            entry.bump();
+
+            self.eventsdb.put(entry).await?;
+
+            self.notify_subscribers(&id, Notification::Question(edit.question_id))
+                .await;
+
+            Ok(res)
+        } else {
+            bail!("question not found")
+        }
+    }
+
+    // TODO: cleanup
+    #[allow(clippy::cognitive_complexity)]
+    pub async fn push_subscriber(&self, ws: WebSocket, id: String) {
+        use futures_util::StreamExt;
+
+        let (ws_sender, mut ws_receiver) = ws.split();
+
+        let user_id = NEXT_USER_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let send_channel = Self::create_send_channel(ws_sender);
+
+        self.channels
+            .write()
+            .await
+            .insert(user_id, (id.clone(), send_channel.clone()));
+
+        self.viewers.add(&id).await;
+
+        self.notify_viewer_count_change(&id);
+
+        tracing::info!(
+            "user connected: {} ({} total)",
+            user_id,
+            self.channels.read().await.len()
+        );
+
+        while let Some(result) = ws_receiver.next().await {
+            let msg = match result {
+                Ok(msg) => msg,
+                Err(e) => {
+                    tracing::info!("websocket receive err (id={}): '{}'", user_id, e);
+                    break;
+                }
+            };
+
+            //allow receiving `p` for app based pings
+            if !matches!(&msg, Message::Text(text) if text=="p") {
+                match &msg {
+                    //TODO: do we need to respond manually?
+                    Message::Ping(_) => tracing::info!("received msg:ping"),
+                    Message::Pong(_) => tracing::warn!("received msg:pong"),
+                    Message::Text(txt) => tracing::warn!("received msg:text: '{txt}'"),
+                    Message::Binary(bin) => tracing::warn!("received msg:binary: {}b", bin.len()),
+                    Message::Close(frame) => tracing::info!("received msg:close: {frame:?}"),
+                }
+
+                let (disconnect, sent_data) = match msg {
+                    Message::Ping(_) | Message::Pong(_) => (false, false),
+                    Message::Close(_) => (true, false),
+                    _ => (true, true),
+                };
+
+                if disconnect {
+                    if sent_data {
+                        tracing::warn!("user:{} sent data, disconnecting", user_id);
+                    }
+                    break;
+                }
+            }
+
+            if self.is_shutting_down() {
+                tracing::info!("shutdown: close client connection [{user_id}]");
+
+                if let Err(e) = send_channel.send(Ok(Message::Close(Some(CloseFrame {
+                    code: RESTART,
+                    reason: "server shutdown".into(),
+                })))) {
+                    tracing::error!("shutdown error: {e}");
+                }
+
+                break;
+            }
+        }
+
+        tracing::info!(
+            "user disconnected: {} ({} remain)",
+            user_id,
+            self.channels.read().await.len().saturating_sub(1)
+        );
+
+        self.viewers.remove(&id).await;
+
+        //Note: lets not spam everyone if its a shutdown
+        if !self.is_shutting_down() {
+            self.notify_viewer_count_change(&id);
+        }
+
+        self.channels.write().await.remove(&user_id);
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    fn notify_viewer_count_change(&self, event: &str) {
+        let event = event.to_string();
+        let app = self.clone();
+
+        tokio::spawn(async move {
+            let count = app.viewers.count(&event).await;
+
+            tracing::info!("notify viewer count: {count}");
+
+            app.notify_subscribers(&event, Notification::Viewers(count))
+                .await;
+        });
+    }
+
+    fn create_send_channel(
+        ws_sender: futures_util::stream::SplitSink<WebSocket, axum::extract::ws::Message>,
+    ) -> OutBoundChannel {
+        use futures_util::FutureExt;
+        use futures_util::StreamExt;
+        use tokio_stream::wrappers::UnboundedReceiverStream;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let rx = UnboundedReceiverStream::new(receiver);
+
+        tokio::task::spawn(rx.forward(ws_sender).map(|result| {
+            if let Err(e) = result {
+                let error_string_lowcase = e.to_string().to_lowercase();
+                let well_known = error_string_lowcase.starts_with("connection closed normally")
+                    || error_string_lowcase.starts_with("trying to work with closed connection");
+                if !well_known {
+                    tracing::warn!("websocket send error: {}", e);
+                }
+            }
+        }));
+
+        sender
+    }
+
+    async fn notify_subscribers(&self, event_id: &str, n: Notification) {
+        let msg = match n {
+            Notification::Event => "e".to_string(),
+            Notification::Question(id) => format!("q:{id}"),
+            Notification::Poll(id) => format!("p:{id}"),
+            Notification::Viewers(count) => format!("v:{count}"),
+        };
+
+        self.pubsub_publish.publish(event_id, &msg).await;
+    }
+
+    fn send_mail(
+        &self,
+        event_id: String,
+        receiver: String,
+        event_name: String,
+        public_link: String,
+        mod_link: String,
+        subject: &'static str,
+    ) {
+        if receiver.trim().is_empty() {
+            tracing::debug!("mail not sent, no receiver specified");
+            return;
+        }
+
+        let mail = self.mail_config.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = mail
+                .send_mail(
+                    event_id,
+                    receiver.clone(),
+                    event_name,
+                    public_link,
+                    mod_link,
+                    subject,
+                )
+                .await
+            {
+                tracing::error!("mail send error: {e}");
+            }
+        });
+    }
+
+    async fn mod_edit_password(
+        &self,
+        e: &mut ApiEventInfo,
+        password: shared::EventPassword,
+    ) -> Result<()> {
+        let edit_type = match (e.password.is_enabled(), password.is_enabled()) {
+            (false, true) => Some(EditEvent::Enabled),
+            (true, false) => Some(EditEvent::Disabled),
+            (true, true) => Some(EditEvent::Changed),
+            _ => None,
+        };
+
+        if let Some(edit_type) = edit_type {
+            self.tracking
+                .track_event_password_set(e.tokens.public_token.clone(), edit_type);
+        }
+
+        e.password = match password {
+            shared::EventPassword::Disabled => StoredPassword::Disabled,
+            // argon2 is deliberately slow, keep it off the async executor
+            shared::EventPassword::Enabled(password) => {
+                task::spawn_blocking(move || StoredPassword::hash(&password))
+                    .await
+                    .map_err(|e| InternalError::General(format!("password hash task: {e}")))?
+                    .map_err(|e| InternalError::General(format!("password hash: {e}")))?
+            }
+        };
+
+        Ok(())
+    }
+
+    fn require_premium(e: &ApiEventInfo, feature: PremiumFeature) -> Result<()> {
+        if !e.entitled(feature) {
+            return Err(InternalError::PremiumOnlyFeature(
+                e.tokens.public_token.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn mod_edit_tag(&self, e: &mut ApiEventInfo, current_tag: &shared::CurrentTag) -> Result<()> {
+        Self::require_premium(e, PremiumFeature::Tags)?;
+
+        let edit_type = match (e.tags.current_tag.is_some(), current_tag.is_enabled()) {
+            (false, true) => Some(EditEvent::Enabled),
+            (true, false) => Some(EditEvent::Disabled),
+            (true, true) => Some(EditEvent::Changed),
+            _ => None,
+        };
+
+        if let Some(edit_type) = edit_type {
+            self.tracking.track_event_tag_set(
+                e.tokens.public_token.clone(),
+                edit_type,
+                e.age_in_seconds(),
+            );
+        }
+
+        if let shared::CurrentTag::Enabled(tag) = &current_tag {
+            let mut validation = TagValidation::default();
+            validation.check(tag);
+
+            if validation.has_any() {
+                return Err(InternalError::TagValidation(validation));
+            }
+
+            if !e.tags.set_or_add_tag(tag) {
+                bail!("max tags reached");
+            }
+        } else {
+            e.tags.current_tag = None;
+        }
+
+        Ok(())
+    }
+
+    fn mod_manage_tags(&self, e: &mut ApiEventInfo, edit: &shared::EditTag) -> Result<()> {
+        Self::require_premium(e, PremiumFeature::Tags)?;
+
+        let action = match edit {
+            shared::EditTag::Rename(id, name) => {
+                let mut validation = TagValidation::default();
+                validation.check(name);
+
+                if validation.has_any() {
+                    return Err(InternalError::TagValidation(validation));
+                }
+
+                if !e.tags.rename(*id, name) {
+                    bail!("tag rename failed: {id:?}");
+                }
+
+                "rename"
+            }
+            shared::EditTag::Delete { tag, reassign } => {
+                if reassign.is_some_and(|r| r == *tag || e.tags.get(r).is_none()) {
+                    bail!("invalid tag reassignment: {tag:?} -> {reassign:?}");
+                }
+
+                if !e.tags.delete(*tag) {
+                    bail!("tag not found: {tag:?}");
+                }
+
+                for q in e.questions.iter_mut().filter(|q| q.tag == Some(*tag)) {
+                    q.tag = *reassign;
+                }
+
+                "delete"
+            }
+            shared::EditTag::Move { tag, to } => {
+                if !e.tags.move_tag(*tag, *to) {
+                    bail!("tag move failed: {tag:?} -> {to}");
+                }
+
+                "move"
+            }
+        };
+
+        self.tracking
+            .track_event_tag_edit(e.tokens.public_token.clone(), action);
+
+        Ok(())
+    }
+
+    fn mod_sessions(&self, e: &mut ApiEventInfo, edit: &shared::EditSession) -> Result<()> {
+        Self::require_premium(e, PremiumFeature::Sessions)?;
+
+        let action = match edit {
+            shared::EditSession::Add(data) | shared::EditSession::Edit(_, data) => {
+                let mut validation = SessionValidation::default();
+                validation.check(data);
+
+                if validation.has_any() {
+                    return Err(InternalError::SessionValidation(validation));
+                }
+
+                let data = shared::SessionData {
+                    name: data.name.trim().to_string(),
+                    ..data.clone()
+                };
+
+                if let shared::EditSession::Edit(id, _) = edit {
+                    let Some(session) = e.sessions.get_mut(*id) else {
+                        bail!("session not found: {id:?}");
+                    };
+                    session.data = data;
+
+                    "edit"
+                } else {
+                    if e.sessions.add(data).is_none() {
+                        bail!("max sessions reached");
+                    }
+
+                    "add"
+                }
+            }
+            shared::EditSession::State(id, state) => {
+                let Some(session) = e.sessions.get_mut(*id) else {
+                    bail!("session not found: {id:?}");
+                };
+                session.state = *state;
+
+                "state"
+            }
+            shared::EditSession::Current(current) => {
+                if current.is_some_and(|id| e.sessions.get(id).is_none()) {
+                    bail!("session not found: {current:?}");
+                }
+                e.sessions.current = *current;
+
+                "current"
+            }
+            shared::EditSession::Delete(id) => {
+                if !e.sessions.delete(*id) {
+                    bail!("session not found: {id:?}");
+                }
+
+                for q in e.questions.iter_mut().filter(|q| q.session == Some(*id)) {
+                    q.session = None;
+                }
+
+                "delete"
+            }
+        };
+
+        self.tracking.track_event_session_edit(
+            e.tokens.public_token.clone(),
+            action,
+            e.sessions.sessions.len(),
+        );
+
+        Ok(())
+    }
+
+    fn mod_context(
+        &self,
+        e: &mut ApiEventInfo,
+        context_link: &shared::EditContextLink,
+    ) -> Result<()> {
+        Self::require_premium(e, PremiumFeature::ContextLinks)?;
+
+        let links = e.context.len();
+        let item = match context_link {
+            shared::EditContextLink::Disabled => {
+                e.context = vec![];
+                None
+            }
+            shared::EditContextLink::Enabled(item) => {
+                Self::validate_context(item)?;
+                e.context = vec![item.clone()];
+                Some(item)
+            }
+            shared::EditContextLink::Add(item) => {
+                Self::validate_context(item)?;
+                if links >= MAX_CONTEXT_LINKS {
+                    bail!("max context links reached");
+                }
+                e.context.push(item.clone());
+                Some(item)
+            }
+            shared::EditContextLink::Edit(idx, item) => {
+                Self::validate_context(item)?;
+                let Some(link) = e.context.get_mut(*idx) else {
+                    bail!("context link not found: {idx}");
+                };
+                link.clone_from(item);
+                Some(item)
+            }
+            shared::EditContextLink::Remove(idx) => {
+                if *idx >= links {
+                    bail!("context link not found: {idx}");
+                }
+                e.context.remove(*idx);
+                None
+            }
+            shared::EditContextLink::Move { from, to } => {
+                if *from >= links || *to >= links {
+                    bail!("context link move out of bounds: {from} -> {to} ({links})");
+                }
+                let link = e.context.remove(*from);
+                e.context.insert(*to, link);
+                None
+            }
+        };
+
+        if let Some(item) = item {
+            self.tracking
+                .redacted(e.no_third_parties)
+                .track_event_context_set(e.tokens.public_token.clone(), &item.label, &item.url);
+        }
+
+        Ok(())
+    }
+
+    fn validate_context(item: &ContextItem) -> Result<()> {
+        let mut validation = ContextValidation::default();
+
+        validation.check(&item.label, &item.url);
+        if validation.has_any() {
+            return Err(InternalError::ContextValidation(validation));
+        }
+
+        Ok(())
+    }
+
+    fn mod_meta(&self, e: &mut ApiEventInfo, edit: &shared::EditMetaData) -> Result<()> {
+        if !shared::EventInfo::during_first_day(e.create_time_unix) {
+            bail!("event meta can only be changed during first 24h")
+        }
+
+        let validation =
+            shared::CreateEventValidation::default().check(&edit.title, &edit.description, "");
+        if validation.has_any() {
+            return Err(InternalError::MetaValidation(edit.clone()));
+        }
+
+        e.data.name.clone_from(&edit.title);
+        e.data.description.clone_from(&edit.description);
+
+        self.tracking
+            .redacted(e.no_third_parties)
+            .track_event_meta_change(e.tokens.public_token.clone(), edit);
+
+        Ok(())
+    }
+
+    fn mod_color(&self, e: &mut ApiEventInfo, color: &shared::EditColor) {
+        e.data.color = Some(Color(color.0.clone()));
+
+        self.tracking
+            .track_event_color_change(e.tokens.public_token.clone(), color, e.premium());
+    }
+
+    fn mod_content_filter(&self, e: &mut ApiEventInfo, filter: ContentFilter) -> Result<()> {
+        let mut validation = ContentFilterValidation::default();
+
+        validation.check(&filter.blocklist);
+        if validation.has_any() {
+            return Err(InternalError::ContentFilterValidation(validation));
+        }
+
+        self.tracking.track_event_content_filter_change(
+            e.tokens.public_token.clone(),
+            filter.action,
+            filter.blocklist.len(),
+        );
+
+        e.content_filter = filter;
+
+        Ok(())
+    }
+
+    fn mod_question_limits(&self, e: &mut ApiEventInfo, limits: QuestionLimits) -> Result<()> {
+        Self::require_premium(e, PremiumFeature::QuestionLimits)?;
+
+        let mut validation = QuestionLimitsValidation::default();
+
+        validation.check(&limits);
+        if validation.has_any() {
+            return Err(InternalError::QuestionLimitsValidation(validation));
+        }
+
+        self.tracking
+            .track_event_question_limits_change(e.tokens.public_token.clone(), &limits);
+
+        e.question_limits = limits;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PubSubReceiver for App {
+    async fn notify(&self, topic: &str, payload: &str) {
+        let msg = Message::Text(payload.to_string());
+
+        //TODO: lookup subscriber based on topic name
+        // send() is sync, so we hold the read guard across the loop instead of cloning the map;
+        // the caller already spawns notify(), so no inner spawn.
+        let receivers = self.channels.read().await;
+        for (_user_id, (_id, c)) in receivers.iter().filter(|(_, (id, _))| id.as_str() == topic) {
+            if let Err(e) = c.send(Ok(msg.clone())) {
+                if let Err(inner_err) = &e.0 {
+                    tracing::error!("pubsub send err: {} ({})", e, inner_err);
+                } else {
+                    tracing::info!("pubsub not sent: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        eventsdb::{InMemoryEventsDB, PremiumOrder, event_key, webhook_key},
+        payment::FakePayment,
+        pubsub::{PubSubInMemory, PubSubReceiverInMemory},
+        shortener::MockUrlShortener,
+        throttle::InMemoryThrottle,
+        viewers::MockViewers,
+    };
+    use pretty_assertions::{assert_eq, assert_ne};
+    use shared::{
+        AddPoll, AddQuestion, CurrentTag, EditContextLink, EditSession, EditTag, EventData,
+        ModPoll, PollKind, PollVote, SessionData, SessionId, TEST_EVENT_DESC, TEST_EVENT_NAME,
+        TEST_VALID_QUESTION, TagId,
+    };
+    use std::sync::Arc;
+
+    /// App over the in-memory backends, tests swap single fields via struct update
+    fn test_app(events: Arc<InMemoryEventsDB>) -> App {
+        App::new(
+            events,
+            Arc::new(PubSubInMemory::default()),
+            Arc::new(MockViewers::new()),
+            Arc::new(InMemoryThrottle::default()),
+            Arc::new(FakePayment::default()),
+            Tracking::default(),
+            String::new(),
+            false,
+        )
+    }
+
+    /// valid event without mod mail, customer or template
+    fn test_event() -> AddEvent {
+        AddEvent {
+            data: EventData {
+                name: String::from(TEST_EVENT_NAME),
+                description: String::from(TEST_EVENT_DESC),
+                ..EventData::default()
+            },
+            moderator_email: None,
+            test: false,
+            customer: None,
+            template: None,
+            no_third_parties: false,
+        }
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_create_fail_validation() {
+        let app = test_app(Arc::default());
+
+        let res = app
+            .create_event(AddEvent {
+                data: EventData {
+                    name: String::from("too short"),
+                    ..EventData::default()
+                },
+                ..test_event()
+            })
+            .await;
+
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_create_email_fail_validation() {
+        let app = test_app(Arc::default());
+
+        let res = app
+            .create_event(AddEvent {
+                moderator_email: Option::Some("a@a".to_string()),
+                ..test_event()
+            })
+            .await;
+
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_mod_auth_fails_closed_on_missing_token() {
+        // Regression: a moderator guard must reject a provided secret when the stored
+        // moderator_token is None, instead of silently treating the caller as a mod.
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        // Simulate a stored event that has no moderator token.
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .tokens
+            .moderator_token = None;
+
+        // The previously-valid secret must now be rejected, not accepted.
+        let err = app.delete_event(id, secret).await.unwrap_err();
+        assert!(matches!(err, InternalError::WrongModeratorToken(_)));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_create_email_pass_validation() {
+        let app = test_app(Arc::default());
+
+        let res = app
+            .create_event(AddEvent {
+                moderator_email: Option::Some("testuser@live-ask.com".to_string()),
+                ..test_event()
+            })
+            .await;
+
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_create() {
+        let eventdb = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(eventdb.clone());
+
+        app.create_event(test_event()).await.unwrap();
+
+        assert_eq!(eventdb.db.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_no_third_parties() {
+        let eventdb = Arc::new(InMemoryEventsDB::default());
+        let new_app = |privacy_mode| App {
+            privacy_mode,
+            ..test_app(eventdb.clone())
+        };
+
+        // only the public event is shortened, checked when the app is dropped
+        let mut shortener = MockUrlShortener::new();
+        shortener
+            .expect_shorten()
+            .times(1)
+            .returning(|_| Some(String::from("https://short")));
+
+        let mut app = new_app(false);
+        app.shortener = Arc::new(shortener);
+
+        let add_event = |no_third_parties| AddEvent {
+            no_third_parties,
+            ..test_event()
+        };
+
+        let public = app.create_event(add_event(false)).await.unwrap();
+        let private = app.create_event(add_event(true)).await.unwrap();
+
+        let public = eventdb
+            .get(&public.tokens.public_token)
+            .await
+            .unwrap()
+            .event;
+        assert!(app.third_parties(&public));
+        assert_eq!(public.data.short_url, "https://short");
+
+        let private_id = private.tokens.public_token.clone();
+        let stored = eventdb.get(&private_id).await.unwrap().event;
+        assert!(stored.no_third_parties);
+        assert!(!app.third_parties(&stored));
+        assert_eq!(Some(stored.data.short_url), stored.data.long_url);
+
+        let clone = app
+            .mod_clone_event(
+                private_id,
+                private.tokens.moderator_token.unwrap(),
+                CloneEvent::default(),
+            )
+            .await
+            .unwrap();
+        let clone = eventdb.get(&clone.tokens.public_token).await.unwrap().event;
+        assert!(clone.no_third_parties);
+
+        assert!(!new_app(true).third_parties(&public));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_mod_question_notification() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(Arc::default())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let q = app
+            .add_question(
+                res.tokens.public_token.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        app.mod_edit_question(
+            res.tokens.public_token.clone(),
+            res.tokens.moderator_token.unwrap(),
+            q.id,
+            ModQuestion {
+                hide: true,
+                answered: false,
+                screened: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            pubsubreceiver.log.read().await[0].clone(),
+            (res.tokens.public_token.clone(), format!("q:{}", q.id))
+        );
+
+        assert_eq!(
+            pubsubreceiver.log.read().await[1].clone(),
+            (res.tokens.public_token.clone(), format!("q:{}", q.id))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_fans_out_only_to_matching_topic() {
+        let app = test_app(Arc::default());
+
+        let (tx_match, mut rx_match) = mpsc::unbounded_channel();
+        let (tx_other, mut rx_other) = mpsc::unbounded_channel();
+        {
+            let mut channels = app.channels.write().await;
+            channels.insert(1, ("topic-a".to_string(), tx_match));
+            channels.insert(2, ("topic-b".to_string(), tx_other));
+        }
+
+        app.notify("topic-a", "hello").await;
+
+        match rx_match.try_recv().unwrap().unwrap() {
+            Message::Text(t) => assert_eq!(t, "hello"),
+            other => panic!("unexpected message: {other:?}"),
+        }
+        // exactly one message to the matching topic, none to the other
+        assert!(rx_match.try_recv().is_err());
+        assert!(rx_other.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_closes_all_connections_with_restart() {
+        let app = test_app(Arc::default());
+
+        let (tx_a, mut rx_a) = mpsc::unbounded_channel();
+        let (tx_b, mut rx_b) = mpsc::unbounded_channel();
+        {
+            let mut channels = app.channels.write().await;
+            channels.insert(1, ("event-a".to_string(), tx_a));
+            channels.insert(2, ("event-b".to_string(), tx_b));
+        }
+
+        // proactively closes every socket regardless of topic, without waiting for a client msg
+        app.close_all_connections().await;
+
+        for rx in [&mut rx_a, &mut rx_b] {
+            match rx.try_recv().unwrap().unwrap() {
+                Message::Close(Some(frame)) => assert_eq!(frame.code, RESTART),
+                other => panic!("expected RESTART close frame, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_screening_question() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&res.tokens.public_token))
+            .unwrap()
+            .event
+            .do_screening = true;
+
+        let q = app
+            .add_question(
+                res.tokens.public_token.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(q.screening, true);
+
+        let e = app
+            .get_event(res.tokens.public_token.clone(), None, false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(e.info.questions.len(), 0);
+
+        let e = app
+            .get_event(
+                res.tokens.public_token.clone(),
+                Some(res.tokens.moderator_token.clone().unwrap()),
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(e.info.questions.len(), 1);
+
+        app.mod_edit_question(
+            res.tokens.public_token.clone(),
+            res.tokens.moderator_token.clone().unwrap(),
+            q.id,
+            ModQuestion {
+                hide: false,
+                answered: false,
+                screened: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let e = app
+            .get_event(res.tokens.public_token.clone(), None, false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(e.info.questions.len(), 1);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_screening_question_disapprove() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&res.tokens.public_token))
+            .unwrap()
+            .event
+            .do_screening = true;
+
+        let q = app
+            .add_question(
+                res.tokens.public_token.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(q.screening, true);
+
+        let e = app
+            .get_event(res.tokens.public_token.clone(), None, false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(e.info.questions.len(), 0);
+
+        let e = app
+            .mod_edit_question(
+                res.tokens.public_token.clone(),
+                res.tokens.moderator_token.clone().unwrap(),
+                q.id,
+                ModQuestion {
+                    hide: true,
+                    answered: false,
+                    screened: false,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(e.questions[0].screening, false);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_screening_enable() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&res.tokens.public_token))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        let e = app
+            .mod_edit_event(
+                res.tokens.public_token.clone(),
+                res.tokens.moderator_token.clone().unwrap(),
+                ModEvent {
+                    screening: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(e.is_screening());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_content_filter() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(Arc::default())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let set_filter = |action, blocklist: &[&str]| {
+            app.mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    content_filter: Some(ContentFilter {
+                        action,
+                        blocklist: blocklist.iter().map(ToString::to_string).collect(),
+                    }),
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(matches!(
+            set_filter(shared::ContentFilterAction::Screen, &["x"])
+                .await
+                .unwrap_err(),
+            InternalError::ContentFilterValidation(_)
+        ));
+
+        set_filter(shared::ContentFilterAction::Screen, &["crypto"])
+            .await
+            .unwrap();
+
+        let add = |text: &str| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+        };
+
+        // only the suspicious question is held back
+        assert!(!add("how do you do this?").await.unwrap().screening);
+        assert!(add("what about Crypto now?").await.unwrap().screening);
+
+        set_filter(shared::ContentFilterAction::Reject, &["crypto"])
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            add("is crypto the future?").await.unwrap_err(),
+            InternalError::QuestionFiltered
+        ));
+
+        let e = app
+            .get_event(id.clone(), Some(secret.clone()), false, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            e.mod_info.unwrap().content_filter.blocklist,
+            vec![String::from("crypto")]
+        );
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_question_limits() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let set_limits = |limits| {
+            app.mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    question_limits: Some(limits),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let workshop = QuestionLimits {
+            min_len: 3,
+            min_words: 1,
+            max_questions: 10,
+            ..QuestionLimits::default()
+        };
+
+        assert!(matches!(
+            set_limits(workshop).await.unwrap_err(),
+            InternalError::PremiumOnlyFeature(_)
+        ));
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        assert!(matches!(
+            set_limits(QuestionLimits {
+                max_questions: 0,
+                ..workshop
+            })
+            .await
+            .unwrap_err(),
+            InternalError::QuestionLimitsValidation(_)
+        ));
+
+        let e = set_limits(workshop).await.unwrap();
+        assert_eq!(e.question_limits, workshop);
+
+        let add = |text: String| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text,
+                    tag: None,
+                    allow_similar: true,
+                    name: None,
+                },
+            )
+        };
+
+        // below the global default but within the event's limits
+        add(String::from("why")).await.unwrap();
+
+        for i in 1..10 {
+            add(format!("question {i}")).await.unwrap();
+        }
+
+        assert!(add(String::from("one too many")).await.is_err());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_participant_names() {
+        let app = test_app(Arc::default());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let add = |text: &str, name: Option<&str>| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: None,
+                    allow_similar: true,
+                    name: name.map(String::from),
+                },
+            )
+        };
+
+        // anonymous by default
+        assert!(matches!(
+            add("who signs this one?", Some("Jo")).await.unwrap_err(),
+            InternalError::ParticipantNameValidation(_)
+        ));
+        assert_eq!(add("who signs this one?", None).await.unwrap().name, None);
+
+        let e = app
+            .mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    participant_names: Some(ParticipantNames::Required),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(e.participant_names, ParticipantNames::Required);
+
+        assert!(matches!(
+            add("where is my name?", Some("  ")).await.unwrap_err(),
+            InternalError::ParticipantNameValidation(_)
+        ));
+        assert_eq!(
+            add("what about names?", Some(" Jo Doe "))
+                .await
+                .unwrap()
+                .name,
+            Some(String::from("Jo Doe"))
+        );
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_similar_question_and_merge() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(Arc::default())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let add = |text: &str, allow_similar: bool| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: None,
+                    allow_similar,
+                    name: None,
+                },
+            )
+        };
+
+        let first = add("What about pricing?", false).await.unwrap();
+
+        // the existing question is suggested instead
+        let err = add("what about the pricing??", false).await.unwrap_err();
+        assert!(matches!(err, InternalError::SimilarQuestion(q) if q.id == first.id));
+
+        // unless the asker insists
+        let second = add("what about the pricing??", true).await.unwrap();
+
+        app.edit_like(
+            id.clone(),
+            shared::EditLike {
+                question_id: second.id,
+                like: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let e = app
+            .mod_merge_question(id.clone(), secret.clone(), second.id, first.id)
+            .await
+            .unwrap();
+
+        let question = |id: i64| e.questions.iter().find(|q| q.id == id).unwrap();
+
+        assert_eq!(question(first.id).likes, 3);
+        assert_eq!(question(first.id).text, "What about pricing?");
+        assert!(question(second.id).hidden);
+        assert_eq!(question(second.id).likes, 0);
+
+        // a merged question is gone, merging it again fails
+        assert!(
+            app.mod_merge_question(id.clone(), secret.clone(), second.id, first.id)
+                .await
+                .is_err()
+        );
+
+        // nor can the target
+        let third = add("what about pricing, again?", true).await.unwrap();
+        assert!(
+            app.mod_merge_question(id.clone(), secret.clone(), third.id, second.id)
+                .await
+                .is_err()
+        );
+
+        // only the latest questions are checked
+        for i in 0..MAX_SIMILAR_CANDIDATES {
+            add(&format!("Filler question number {i}"), true)
+                .await
+                .unwrap();
+        }
+        add("what about the pricing??", false).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_question_target() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let mut questions = Vec::new();
+        for text in [
+            "Which question is merged here?",
+            "Is the answered one a target?",
+            "Is a screened one a target?",
+        ] {
+            let q = app
+                .add_question(
+                    id.clone(),
+                    AddQuestion {
+                        text: String::from(text),
+                        tag: None,
+                        allow_similar: true,
+                        name: None,
+                    },
+                )
+                .await
+                .unwrap();
+            questions.push(q.id);
+        }
+        let (merged, answered, screened) = (questions[0], questions[1], questions[2]);
+
+        app.mod_edit_question(
+            id.clone(),
+            secret.clone(),
+            answered,
+            ModQuestion {
+                hide: false,
+                answered: true,
+                screened: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .questions
+            .iter_mut()
+            .find(|q| q.id == screened)
+            .unwrap()
+            .screening = true;
+
+        for into in [merged, answered, screened] {
+            assert!(
+                app.mod_merge_question(id.clone(), secret.clone(), merged, into)
+                    .await
+                    .is_err()
+            );
+        }
+
+        // nothing was merged away
+        let e = events.db.lock().await[&event_key(&id)].event.clone();
+        assert!(!e.questions.iter().find(|q| q.id == merged).unwrap().hidden);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_duplicate_question_check() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        app.add_question(
+            res.tokens.public_token.clone(),
+            AddQuestion {
+                text: String::from(TEST_VALID_QUESTION),
+                tag: None,
+                allow_similar: false,
+                name: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let request = app
+            .add_question(
+                res.tokens.public_token.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await;
+
+        assert!(matches!(
+            request.unwrap_err(),
+            InternalError::DuplicateQuestion
+        ))
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_password_protection() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let event_id = res.tokens.public_token.clone();
+        let mod_token = res.tokens.moderator_token.clone().unwrap();
+
+        let question_text = "very long, sophisticated question you can ask!";
+        app.add_question(
+            event_id.clone(),
+            AddQuestion {
+                text: String::from(question_text),
+                tag: None,
+                allow_similar: false,
+                name: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        app.mod_edit_event(
+            event_id.clone(),
+            mod_token.clone(),
+            ModEvent {
+                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // locked: no grant -> masked + wrong-password flag
+        let e = app
+            .get_event(event_id.clone(), None, false, None)
+            .await
+            .unwrap();
+
+        assert_ne!(&e.info.questions[0].text, question_text);
+        assert!(e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
+
+        // the stored password is a hash, never returned to moderators
+        let e = app
+            .get_event(event_id.clone(), Some(mod_token.clone()), false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            e.mod_info.unwrap().pwd,
+            shared::EventPassword::Enabled(String::new())
+        );
+
+        assert_eq!(
+            app.check_event_password(event_id.clone(), "wrong", "client")
+                .await
+                .unwrap(),
+            None
+        );
+
+        let grant = app
+            .check_event_password(event_id.clone(), "pwd", "client")
+            .await
+            .unwrap();
+        assert!(grant.is_some());
+
+        // unlocked: grant carries the fingerprint of the current password -> full data
+        let e = app
+            .get_event(event_id.clone(), None, false, grant.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(&e.info.questions[0].text, question_text);
+        assert!(!e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
+
+        // rotated password -> the old grant fingerprint no longer matches -> re-masked
+        app.mod_edit_event(
+            event_id.clone(),
+            mod_token.clone(),
+            shared::ModEvent {
+                password: Some(shared::EventPassword::Enabled("rotated".into())),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let e = app
+            .get_event(event_id.clone(), None, false, grant)
+            .await
+            .unwrap();
+
+        assert_ne!(&e.info.questions[0].text, question_text);
+        assert!(e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_password_migration() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .password = StoredPassword::Legacy(String::from("pwd"));
+
+        assert!(
+            app.check_event_password(id.clone(), "pwd", "client")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let stored = events.db.lock().await[&event_key(&id)].clone();
+        assert!(matches!(stored.event.password, StoredPassword::Hashed(_)));
+        assert_eq!(stored.version, 1);
+        assert!(stored.event.password.verify("pwd"));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_password_lockout() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(Arc::default())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let event_id = res.tokens.public_token.clone();
+
+        app.mod_edit_event(
+            event_id.clone(),
+            res.tokens.moderator_token.clone().unwrap(),
+            ModEvent {
+                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        for _ in 0..PWD_CLIENT_POLICY.free_attempts {
+            assert_eq!(
+                app.check_event_password(event_id.clone(), "wrong", "guesser")
+                    .await
+                    .unwrap(),
+                None
+            );
+        }
+
+        assert_eq!(
+            app.check_event_password(event_id.clone(), "wrong", "guesser")
+                .await
+                .unwrap(),
+            None
+        );
+
+        // locked out: even the right password is refused for this client
+        assert!(matches!(
+            app.check_event_password(event_id.clone(), "pwd", "guesser")
+                .await
+                .unwrap_err(),
+            InternalError::PasswordLockout(_)
+        ));
+
+        // other clients are unaffected while the event stays below its limit
+        assert!(
+            app.check_event_password(event_id.clone(), "pwd", "other")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // the client lock is scoped to the event, a shared address can still use others
+        let res = app.create_event(test_event()).await.unwrap();
+
+        app.mod_edit_event(
+            res.tokens.public_token.clone(),
+            res.tokens.moderator_token.clone().unwrap(),
+            ModEvent {
+                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            app.check_event_password(res.tokens.public_token.clone(), "pwd", "guesser")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_password_lockout_per_event() {
+        let throttle = InMemoryThrottle::default();
+        let app = App {
+            throttle: Arc::new(throttle.clone()),
+            ..test_app(Arc::default())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let event_id = res.tokens.public_token.clone();
+
+        app.mod_edit_event(
+            event_id.clone(),
+            res.tokens.moderator_token.clone().unwrap(),
+            ModEvent {
+                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // a distributed guess: every client stays below its own limit
+        let event_key = format!("pwd/event/{event_id}");
+        for _ in 0..PWD_EVENT_POLICY.free_attempts {
+            throttle.fail(&event_key).await;
+        }
+
+        assert_eq!(
+            app.check_event_password(event_id.clone(), "wrong", "guesser-1")
+                .await
+                .unwrap(),
+            None
+        );
+
+        assert!(matches!(
+            app.check_event_password(event_id.clone(), "pwd", "guesser-2")
+                .await
+                .unwrap_err(),
+            InternalError::PasswordLockout(_)
+        ));
+
+        // the client counter only saw a single failure
+        assert_eq!(
+            throttle
+                .fail(&format!("pwd/{event_id}/client/guesser-1"))
+                .await,
+            2
+        );
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_event_question_tags() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&res.tokens.public_token))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        assert_eq!(
+            app.mod_edit_event(
+                res.tokens.public_token.clone(),
+                res.tokens.moderator_token.clone().unwrap(),
+                ModEvent {
+                    current_tag: Some(CurrentTag::Enabled(String::from("tag1"))),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .tags
+            .current_tag
+            .unwrap(),
+            TagId(0)
+        );
+
+        let request = app
+            .add_question(
+                res.tokens.public_token.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(request.tag.unwrap(), TagId(0))
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_manage_tags() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        let edit = |changes: ModEvent| app.mod_edit_event(id.clone(), secret.clone(), changes);
+        let set_tag = |tag: &str| ModEvent {
+            current_tag: Some(CurrentTag::Enabled(String::from(tag))),
+            ..Default::default()
+        };
+        let manage = |tag: EditTag| ModEvent {
+            edit_tag: Some(tag),
+            ..Default::default()
+        };
+
+        edit(set_tag("day1")).await.unwrap();
+        edit(set_tag("day2")).await.unwrap();
+
+        let question = app
+            .add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(TEST_VALID_QUESTION),
+                    tag: None,
+                    allow_similar: false,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(question.tag, Some(TagId(1)));
+
+        // reassigning to the deleted tag itself or an unknown one is refused
+        for reassign in [TagId(1), TagId(7)] {
+            assert!(
+                edit(manage(EditTag::Delete {
+                    tag: TagId(1),
+                    reassign: Some(reassign),
+                }))
+                .await
+                .is_err()
+            );
+        }
+
+        let e = edit(manage(EditTag::Delete {
+            tag: TagId(1),
+            reassign: Some(TagId(0)),
+        }))
+        .await
+        .unwrap();
+        assert_eq!(e.tags.current_tag, None);
+        assert_eq!(e.questions[0].tag, Some(TagId(0)));
+
+        // ids of deleted tags are not reused
+        let e = edit(set_tag("day3")).await.unwrap();
+        assert_eq!(e.tags.current_tag, Some(TagId(2)));
+
+        assert!(matches!(
+            edit(manage(EditTag::Rename(TagId(0), String::new())))
+                .await
+                .unwrap_err(),
+            InternalError::TagValidation(_)
+        ));
+        assert!(
+            edit(manage(EditTag::Rename(TagId(0), String::from("day3"))))
+                .await
+                .is_err()
+        );
+
+        let e = edit(manage(EditTag::Rename(TagId(0), String::from("Intro"))))
+            .await
+            .unwrap();
+        assert_eq!(e.tags.get(TagId(0)).unwrap().name, "intro");
+
+        let e = edit(manage(EditTag::Move {
+            tag: TagId(2),
+            to: 0,
+        }))
+        .await
+        .unwrap();
+        assert_eq!(
+            e.tags.tags.iter().map(|tag| tag.id).collect::<Vec<_>>(),
+            [TagId(2), TagId(0)]
+        );
+
+        let e = edit(manage(EditTag::Delete {
+            tag: TagId(0),
+            reassign: None,
+        }))
+        .await
+        .unwrap();
+        assert_eq!(e.questions[0].tag, None);
+
+        // a question posted with a deleted tag gets the current one instead
+        let add = |text: &str, tag: TagId| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: Some(tag),
+                    allow_similar: true,
+                    name: None,
+                },
+            )
+        };
+        assert_eq!(
+            add("Posted with a deleted tag?", TagId(0))
+                .await
+                .unwrap()
+                .tag,
+            Some(TagId(2))
+        );
+        assert_eq!(
+            add("Posted with the current tag?", TagId(2))
+                .await
+                .unwrap()
+                .tag,
+            Some(TagId(2))
+        );
+
+        edit(ModEvent {
+            current_tag: Some(CurrentTag::Disabled),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            add("Posted without any current tag?", TagId(0))
+                .await
+                .unwrap()
+                .tag,
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_sessions() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let edit = |session: EditSession| {
+            app.mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    session: Some(session),
+                    ..Default::default()
+                },
+            )
+        };
+        let add_session = |name: &str, start_unix: Option<i64>| {
+            EditSession::Add(SessionData {
+                name: String::from(name),
+                start_unix,
+                end_unix: None,
+            })
+        };
+        let ask = |text: &str| {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: None,
+                    allow_similar: true,
+                    name: None,
+                },
+            )
+        };
+
+        // premium only
+        assert!(matches!(
+            edit(add_session("keynote", None)).await.unwrap_err(),
+            InternalError::PremiumOnlyFeature(_)
+        ));
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        assert!(matches!(
+            edit(add_session("k", None)).await.unwrap_err(),
+            InternalError::SessionValidation(_)
+        ));
+
+        edit(add_session("keynote", None)).await.unwrap();
+        // already running, picked up without being pinned
+        let e = edit(add_session("workshop", Some(timestamp_now() - 60)))
+            .await
+            .unwrap();
+        assert_eq!(e.sessions.sessions.len(), 2);
+
+        let question = ask(TEST_VALID_QUESTION).await.unwrap();
+        assert_eq!(question.session, Some(SessionId(1)));
+
+        edit(EditSession::Current(Some(SessionId(0))))
+            .await
+            .unwrap();
+        let question = ask("4 5 6sevenheight").await.unwrap();
+        assert_eq!(question.session, Some(SessionId(0)));
+
+        // a closed session takes no questions and votes, the other one still takes votes
+        edit(EditSession::State(
+            SessionId(0),
+            EventState {
+                state: States::Closed,
+            },
+        ))
+        .await
+        .unwrap();
+        assert!(ask("7 8 9teneleven").await.is_err());
+        assert!(
+            app.edit_like(
+                id.clone(),
+                shared::EditLike {
+                    question_id: 1,
+                    like: true,
+                },
+            )
+            .await
+            .is_err()
+        );
+        assert_eq!(
+            app.edit_like(
+                id.clone(),
+                shared::EditLike {
+                    question_id: 0,
+                    like: true,
+                },
+            )
+            .await
+            .unwrap()
+            .likes,
+            2
+        );
+
+        assert!(
+            edit(EditSession::Current(Some(SessionId(9))))
+                .await
+                .is_err()
+        );
+
+        let e = edit(EditSession::Delete(SessionId(0))).await.unwrap();
+        assert_eq!(e.sessions.current, None);
+        assert_eq!(e.questions[1].session, None);
+        assert_eq!(e.questions[0].session, Some(SessionId(1)));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_polls() {
+        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
+        let pubsub = PubSubInMemory::default();
+        pubsub.set_receiver(pubsubreceiver.clone()).await;
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = App {
+            pubsub_publish: Arc::new(pubsub),
+            ..test_app(events.clone())
+        };
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let add = |question: &str, kind: PollKind, options: &[&str]| {
+            app.mod_add_poll(
+                id.clone(),
+                secret.clone(),
+                AddPoll {
+                    question: String::from(question),
+                    kind,
+                    options: options.iter().map(ToString::to_string).collect(),
+                },
+            )
+        };
+
+        assert!(matches!(
+            add("Lunch?", PollKind::SingleChoice, &["pizza"])
+                .await
+                .unwrap_err(),
+            InternalError::PollValidation(_)
+        ));
+
+        let e = add(" Lunch? ", PollKind::SingleChoice, &["pizza", "salad"])
+            .await
+            .unwrap();
+        assert_eq!(e.polls.len(), 1);
+        assert_eq!(e.polls[0].question, "Lunch?");
+        assert_eq!(
+            pubsubreceiver.log.read().await[0].clone(),
+            (id.clone(), String::from("p:0"))
         );
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("too short"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
-            .await;
+        let vote = |voter: &str, choices: Vec<usize>| {
+            app.vote_poll(id.clone(), 0, voter, PollVote { choices })
+        };
 
-        assert!(res.is_err());
-    }
+        assert!(matches!(
+            vote("a", vec![0, 1]).await.unwrap_err(),
+            InternalError::InvalidPollVote(0)
+        ));
 
-    #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_event_create_email_fail_validation() {
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let poll = vote("a", vec![1]).await.unwrap();
+        assert_eq!(poll.voters, 1);
+        assert_eq!(poll.options[1].votes, 1);
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: TEST_EVENT_NAME.to_string(),
-                    description: TEST_EVENT_DESC.to_string(),
-                    ..EventData::default()
+        assert!(matches!(
+            vote("a", vec![0]).await.unwrap_err(),
+            InternalError::AlreadyVoted(0)
+        ));
+
+        let poll = vote("b", vec![1]).await.unwrap();
+        assert_eq!(poll.voters, 2);
+        assert_eq!(poll.options[1].votes, 2);
+        assert_eq!(events.poll_votes.lock().await.len(), 2);
+
+        let e = app
+            .mod_edit_poll(
+                id.clone(),
+                secret.clone(),
+                0,
+                ModPoll {
+                    closed: true,
+                    hidden: false,
                 },
-                moderator_email: Option::Some("a@a".to_string()),
-                test: false,
-                customer: None,
-            })
-            .await;
+            )
+            .await
+            .unwrap();
+        assert!(e.polls[0].closed);
 
-        assert!(res.is_err());
+        assert!(matches!(
+            vote("c", vec![0]).await.unwrap_err(),
+            InternalError::InvalidPollVote(0)
+        ));
+
+        app.mod_edit_poll(
+            id.clone(),
+            secret.clone(),
+            0,
+            ModPoll {
+                closed: true,
+                hidden: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let participant = app.get_event(id.clone(), None, false, None).await.unwrap();
+        assert!(participant.info.polls.is_empty());
+
+        let moderator = app
+            .get_event(id.clone(), Some(secret.clone()), false, None)
+            .await
+            .unwrap();
+        assert_eq!(moderator.info.polls.len(), 1);
+        assert!(moderator.info.polls[0].hidden);
     }
 
     #[tokio::test]
     #[tracing_test::traced_test]
-    async fn test_mod_auth_fails_closed_on_missing_token() {
-        // Regression: a moderator guard must reject a provided secret when the stored
-        // moderator_token is None, instead of silently treating the caller as a mod.
+    async fn test_clone_and_templates() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let payment = Arc::new(FakePayment::default());
+        let app = App {
+            payment: payment.clone(),
+            ..test_app(events.clone())
+        };
 
         let res = app
             .create_event(AddEvent {
-                data: EventData {
-                    name: TEST_EVENT_NAME.to_string(),
-                    description: TEST_EVENT_DESC.to_string(),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
+                moderator_email: Some(String::from("mod@example.com")),
+                ..test_event()
             })
             .await
             .unwrap();
@@ -1255,7 +4354,6 @@ mod test {
         let id = res.tokens.public_token.clone();
         let secret = res.tokens.moderator_token.clone().unwrap();
 
-        // Simulate a stored event that has no moderator token.
         events
             .db
             .lock()
@@ -1263,644 +4361,966 @@ mod test {
             .get_mut(&event_key(&id))
             .unwrap()
             .event
-            .tokens
-            .moderator_token = None;
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
 
-        // The previously-valid secret must now be rejected, not accepted.
-        let err = app.delete_event(id, secret).await.unwrap_err();
-        assert!(matches!(err, InternalError::WrongModeratorToken(_)));
-    }
+        app.mod_edit_event(
+            id.clone(),
+            secret.clone(),
+            ModEvent {
+                screening: Some(true),
+                color: Some(shared::EditColor(String::from("#FF2C5E"))),
+                current_tag: Some(CurrentTag::Enabled(String::from("day1"))),
+                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
 
-    #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_event_create_email_pass_validation() {
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
+        for text in ["first open question?", "second open question?"] {
+            app.add_question(
+                id.clone(),
+                AddQuestion {
+                    text: String::from(text),
+                    tag: None,
+                    allow_similar: true,
+                    name: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        app.mod_edit_question(
+            id.clone(),
+            secret.clone(),
+            0,
+            ModQuestion {
+                hide: false,
+                answered: true,
+                screened: true,
+            },
+        )
+        .await
+        .unwrap();
+        app.mod_edit_question(
+            id.clone(),
+            secret.clone(),
+            1,
+            ModQuestion {
+                hide: false,
+                answered: false,
+                screened: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        // sessions are not cloned, neither are the questions' references to them
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .questions[1]
+            .session = Some(SessionId(3));
+
+        assert!(matches!(
+            app.mod_clone_event(id.clone(), String::from("wrong"), CloneEvent::default())
+                .await
+                .unwrap_err(),
+            InternalError::WrongModeratorToken(_)
+        ));
+
+        let clone = app
+            .mod_clone_event(
+                id.clone(),
+                secret.clone(),
+                CloneEvent {
+                    questions: true,
+                    customer: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_ne!(clone.tokens.public_token, id);
+        assert_eq!(clone.data.name, TEST_EVENT_NAME);
+        assert_eq!(clone.data.color, Some(Color(String::from("#FF2C5E"))));
+        assert!(clone.is_screening());
+        // the clone is not premium, premium only settings stay behind
+        assert!(clone.tags.tags.is_empty());
+        assert_eq!(clone.questions.len(), 1);
+        assert_eq!(clone.questions[0].id, 0);
+        assert_eq!(clone.questions[0].text, "second open question?");
+        assert_eq!(clone.questions[0].tag, None);
+        assert_eq!(clone.questions[0].session, None);
+
+        // the clone keeps the password and its mod link can be recovered with the same address
+        let stored = events.db.lock().await[&event_key(&clone.tokens.public_token)]
+            .event
+            .clone();
+        assert!(stored.password.is_enabled());
+        assert!(stored.is_mod_email("mod@example.com"));
+        assert_eq!(stored.mod_email_salt, Some(id.clone()));
+
+        let template = app
+            .mod_save_template(
+                id.clone(),
+                secret.clone(),
+                SaveTemplate {
+                    name: String::from(" weekly meetup "),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(template.name, "weekly meetup");
+
+        let from_template = app
+            .create_event(AddEvent {
+                template: Some(template.id.clone()),
+                ..test_event()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            from_template.data.color,
+            Some(Color(String::from("#FF2C5E")))
         );
+        assert!(from_template.is_screening());
+        assert!(from_template.questions.is_empty());
+        assert!(from_template.tags.tags.is_empty());
 
-        let res = app
+        // templates carry no password
+        let stored = events.db.lock().await[&event_key(&from_template.tokens.public_token)]
+            .event
+            .clone();
+        assert!(!stored.password.is_enabled());
+        assert_eq!(stored.mod_email_hash, None);
+
+        // premium only settings follow the new event's own premium
+        let customer = payment.add_subscriber("sub@example.com").await;
+        let premium_from_template = app
             .create_event(AddEvent {
-                data: EventData {
-                    name: TEST_EVENT_NAME.to_string(),
-                    description: TEST_EVENT_DESC.to_string(),
-                    ..EventData::default()
-                },
-                moderator_email: Option::Some("testuser@live-ask.com".to_string()),
-                test: false,
-                customer: None,
+                customer: Some(customer),
+                template: Some(template.id.clone()),
+                ..test_event()
             })
-            .await;
+            .await
+            .unwrap();
+        assert_eq!(premium_from_template.tags.tags.len(), 1);
 
-        assert!(res.is_ok());
+        assert!(
+            app.create_event(AddEvent {
+                template: Some(String::from("unknown")),
+                ..test_event()
+            })
+            .await
+            .is_err()
+        );
     }
 
     #[tokio::test]
     #[tracing_test::traced_test]
-    async fn test_event_create() {
-        let eventdb = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            eventdb.clone(),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
+    async fn test_context_links() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        let edit = |context: EditContextLink| {
+            app.mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    context: Some(context),
+                    ..Default::default()
+                },
+            )
+        };
+        let link = |label: &str| ContextItem {
+            label: String::from(label),
+            url: format!("https://example.com/{label}"),
+        };
+        let labels = |e: &EventInfo| {
+            e.context
+                .iter()
+                .map(|c| c.label.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(matches!(
+            edit(EditContextLink::Add(link("slides")))
+                .await
+                .unwrap_err(),
+            InternalError::PremiumOnlyFeature(_)
+        ));
+
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .premium_id = Some(PremiumOrder::Admin(String::from("foo")));
+
+        for label in ["slides", "recording", "survey"] {
+            edit(EditContextLink::Add(link(label))).await.unwrap();
+        }
+
+        assert!(matches!(
+            edit(EditContextLink::Add(ContextItem {
+                label: String::from("evil"),
+                url: String::from("javascript:alert(1)"),
+            }))
+            .await
+            .unwrap_err(),
+            InternalError::ContextValidation(_)
+        ));
+
+        let e = edit(EditContextLink::Move { from: 2, to: 0 })
+            .await
+            .unwrap();
+        assert_eq!(labels(&e), ["survey", "slides", "recording"]);
+
+        let e = edit(EditContextLink::Edit(1, link("deck"))).await.unwrap();
+        assert_eq!(labels(&e), ["survey", "deck", "recording"]);
+
+        let e = edit(EditContextLink::Remove(0)).await.unwrap();
+        assert_eq!(labels(&e), ["deck", "recording"]);
+
+        assert!(edit(EditContextLink::Remove(2)).await.is_err());
+        assert!(
+            edit(EditContextLink::Move { from: 0, to: 2 })
+                .await
+                .is_err()
         );
 
-        app.create_event(AddEvent {
-            data: EventData {
-                name: String::from("123456789"),
-                description: String::from("123456789 123456789 123456789 !"),
-                ..EventData::default()
-            },
-            moderator_email: None,
-            test: false,
-            customer: None,
-        })
-        .await
-        .unwrap();
+        for label in ["one1", "two2", "three"] {
+            edit(EditContextLink::Add(link(label))).await.unwrap();
+        }
+        assert!(edit(EditContextLink::Add(link("six6"))).await.is_err());
 
-        assert_eq!(eventdb.db.lock().await.len(), 1);
+        let e = edit(EditContextLink::Disabled).await.unwrap();
+        assert!(e.context.is_empty());
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_event_mod_question_notification() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+    async fn test_organizer_accounts() {
+        let app = test_app(Arc::default());
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
-            .await
-            .unwrap();
+        assert!(matches!(
+            app.organizer_login("no-mail", "client").await.unwrap_err(),
+            InternalError::InvalidEmail
+        ));
 
-        let q = app
-            .add_question(
-                res.tokens.public_token.clone(),
-                AddQuestion {
-                    text: String::from(TEST_VALID_QUESTION),
-                    tag: None,
-                },
-            )
+        let (org, nonce) = app
+            .organizer_login("Jo@example.com", "client")
             .await
             .unwrap();
+        assert_eq!(org, StoredOrganizer::id_from_email("jo@example.com"));
 
-        app.mod_edit_question(
-            res.tokens.public_token.clone(),
-            res.tokens.moderator_token.unwrap(),
-            q.id,
-            ModQuestion {
-                hide: true,
-                answered: false,
-                screened: true,
-            },
-        )
-        .await
-        .unwrap();
+        assert!(app.organizer_verify(&org, "guess").await.is_err());
+        app.organizer_verify(&org, &nonce).await.unwrap();
+        assert!(matches!(
+            app.organizer_verify(&org, &nonce).await.unwrap_err(),
+            InternalError::InvalidLogin
+        ));
 
-        assert_eq!(
-            pubsubreceiver.log.read().await[0].clone(),
-            (res.tokens.public_token.clone(), format!("q:{}", q.id))
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        assert!(
+            app.organizer_add_event(&org, id.clone(), "wrong")
+                .await
+                .is_err()
         );
+        app.organizer_add_event(&org, id.clone(), &secret)
+            .await
+            .unwrap();
+        app.organizer_add_event(&org, id.clone(), &secret)
+            .await
+            .unwrap();
 
-        assert_eq!(
-            pubsubreceiver.log.read().await[1].clone(),
-            (res.tokens.public_token.clone(), format!("q:{}", q.id))
+        let dashboard = app.organizer_dashboard(&org).await.unwrap();
+        assert_eq!(dashboard.events.len(), 1);
+        assert_eq!(dashboard.events[0].tokens, res.tokens);
+        assert_eq!(dashboard.events[0].name, TEST_EVENT_NAME);
+
+        app.organizer_remove_event(&org, &id).await.unwrap();
+        assert!(
+            app.organizer_dashboard(&org)
+                .await
+                .unwrap()
+                .events
+                .is_empty()
         );
+
+        assert!(matches!(
+            app.organizer_dashboard("unknown").await.unwrap_err(),
+            InternalError::InvalidLogin
+        ));
+
+        for _ in 1..LOGIN_MAIL_POLICY.free_attempts {
+            app.organizer_login("jo@example.com", "other")
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            app.organizer_login("jo@example.com", "third")
+                .await
+                .unwrap_err(),
+            InternalError::RateLimited(_)
+        ));
     }
 
     #[tokio::test]
-    async fn test_notify_fans_out_only_to_matching_topic() {
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+    async fn test_organizer_stale_write() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
 
-        let (tx_match, mut rx_match) = mpsc::unbounded_channel();
-        let (tx_other, mut rx_other) = mpsc::unbounded_channel();
-        {
-            let mut channels = app.channels.write().await;
-            channels.insert(1, ("topic-a".to_string(), tx_match));
-            channels.insert(2, ("topic-b".to_string(), tx_other));
-        }
+        let (org, nonce) = app
+            .organizer_login("jo@example.com", "client")
+            .await
+            .unwrap();
 
-        app.notify("topic-a", "hello").await;
+        let mut stale = events.get_organizer(&org).await.unwrap();
+        assert_eq!(stale.version, 1);
 
-        match rx_match.try_recv().unwrap().unwrap() {
-            Message::Text(t) => assert_eq!(t, "hello"),
-            other => panic!("unexpected message: {other:?}"),
-        }
-        // exactly one message to the matching topic, none to the other
-        assert!(rx_match.try_recv().is_err());
-        assert!(rx_other.try_recv().is_err());
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        app.organizer_add_event(&org, id.clone(), &res.tokens.moderator_token.unwrap())
+            .await
+            .unwrap();
+
+        // e.g. a login link redeemed from a copy read before the event was added
+        stale.login_nonce = None;
+        stale.version += 1;
+        assert!(matches!(
+            events.put_organizer(stale).await.unwrap_err(),
+            eventsdb::Error::Concurrency
+        ));
+
+        app.organizer_verify(&org, &nonce).await.unwrap();
+
+        let stored = events.get_organizer(&org).await.unwrap();
+        assert_eq!(stored.events, vec![id]);
+        assert_eq!(stored.login_nonce, None);
+        assert_eq!(stored.version, 3);
     }
 
     #[tokio::test]
-    async fn test_shutdown_closes_all_connections_with_restart() {
-        let app = App::new(
-            Arc::new(InMemoryEventsDB::default()),
-            Arc::new(PubSubInMemory::default()),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
+    async fn test_recover_mod_link() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
+
+        let res = app
+            .create_event(AddEvent {
+                moderator_email: Some(String::from("Mod@example.com")),
+                test: true,
+                ..test_event()
+            })
+            .await
+            .unwrap();
+
+        let id = res.tokens.public_token.clone();
+
+        let stored = events.db.lock().await[&event_key(&id)].event.clone();
+        assert!(stored.is_mod_email(" mod@EXAMPLE.com"));
+        assert!(!stored.is_mod_email("other@example.com"));
+        assert_ne!(
+            stored.mod_email_hash,
+            Some(ApiEventInfo::mod_email_hash("other", "mod@example.com"))
         );
 
-        let (tx_a, mut rx_a) = mpsc::unbounded_channel();
-        let (tx_b, mut rx_b) = mpsc::unbounded_channel();
-        {
-            let mut channels = app.channels.write().await;
-            channels.insert(1, ("event-a".to_string(), tx_a));
-            channels.insert(2, ("event-b".to_string(), tx_b));
-        }
+        assert!(matches!(
+            app.recover_mod_link(id.clone(), "nope", "client")
+                .await
+                .unwrap_err(),
+            InternalError::InvalidEmail
+        ));
 
-        // proactively closes every socket regardless of topic, without waiting for a client msg
-        app.close_all_connections().await;
+        // matching, wrong and unknown look the same to the caller
+        app.recover_mod_link(id.clone(), "mod@example.com", "client")
+            .await
+            .unwrap();
+        app.recover_mod_link(id.clone(), "other@example.com", "client")
+            .await
+            .unwrap();
+        app.recover_mod_link(String::from("unknown"), "mod@example.com", "client")
+            .await
+            .unwrap();
 
-        for rx in [&mut rx_a, &mut rx_b] {
-            match rx.try_recv().unwrap().unwrap() {
-                Message::Close(Some(frame)) => assert_eq!(frame.code, RESTART),
-                other => panic!("expected RESTART close frame, got {other:?}"),
-            }
+        // guesses of other addresses do not lock out the moderator
+        for client in ["a", "b", "c", "d"] {
+            app.recover_mod_link(id.clone(), &format!("{client}@example.com"), client)
+                .await
+                .unwrap();
         }
+        app.recover_mod_link(id.clone(), "mod@example.com", "other")
+            .await
+            .unwrap();
+
+        // but the same address is only mailed a few times
+        assert!(matches!(
+            app.recover_mod_link(id, "mod@example.com", "third")
+                .await
+                .unwrap_err(),
+            InternalError::RateLimited(_)
+        ));
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_screening_question() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
+    async fn test_webhook_ledger() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let app = test_app(events.clone());
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
+        let kind = String::from("charge.refunded");
+
+        assert!(app.webhook_claim("evt_1", &kind).await.unwrap());
+        // a concurrent retry leaves it to the request processing it
+        assert!(!app.webhook_claim("evt_1", &kind).await.unwrap());
+
+        // a failed delivery is retried
+        app.webhook_processed(
+            String::from("evt_1"),
+            kind.clone(),
+            WebhookOutcome::Failed(String::from("db down")),
+        )
+        .await
+        .unwrap();
+        assert!(app.webhook_claim("evt_1", &kind).await.unwrap());
+
+        app.webhook_processed(
+            String::from("evt_1"),
+            kind.clone(),
+            WebhookOutcome::Processed,
+        )
+        .await
+        .unwrap();
+        assert!(!app.webhook_claim("evt_1", &kind).await.unwrap());
+
+        assert!(app.webhook_claim("evt_2", &kind).await.unwrap());
+        app.webhook_processed(String::from("evt_2"), kind.clone(), WebhookOutcome::Ignored)
             .await
             .unwrap();
+        assert!(!app.webhook_claim("evt_2", &kind).await.unwrap());
 
+        // a claim that was never finished is taken over once stale
+        assert!(app.webhook_claim("evt_3", &kind).await.unwrap());
         events
-            .db
+            .webhooks
             .lock()
             .await
-            .get_mut(&event_key(&res.tokens.public_token))
+            .get_mut(&webhook_key("evt_3"))
             .unwrap()
-            .event
-            .do_screening = true;
+            .time_unix -= WEBHOOK_CLAIM_TIMEOUT_SECS + 1;
+        assert!(app.webhook_claim("evt_3", &kind).await.unwrap());
 
-        let q = app
-            .add_question(
-                res.tokens.public_token.clone(),
-                AddQuestion {
-                    text: String::from(TEST_VALID_QUESTION),
-                    tag: None,
-                },
-            )
-            .await
-            .unwrap();
+        let deliveries = app.webhook_deliveries().await.unwrap().deliveries;
+        assert_eq!(
+            deliveries
+                .iter()
+                .map(|d| (d.id.as_str(), d.outcome.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("evt_2", WebhookOutcome::Ignored),
+                ("evt_1", WebhookOutcome::Processed),
+            ]
+        );
 
-        assert_eq!(q.screening, true);
+        // a stale log write is refused instead of dropping the deliveries recorded since
+        let stale = StoredWebhookLog {
+            version: 2,
+            ..StoredWebhookLog::default()
+        };
+        assert!(matches!(
+            events.put_webhook_log(stale).await.unwrap_err(),
+            eventsdb::Error::Concurrency
+        ));
+    }
 
-        let e = app
-            .get_event(res.tokens.public_token.clone(), None, false, None)
+    #[tokio::test]
+    async fn test_subscription_lifecycle() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let payment = Arc::new(FakePayment::default());
+        let app = App {
+            payment: payment.clone(),
+            ..test_app(events.clone())
+        };
+
+        let customer = payment.add_subscriber("sub@example.com").await;
+        let subscription = payment.verify_customer(&customer).await.unwrap();
+
+        let add_event = || AddEvent {
+            customer: Some(customer.clone()),
+            ..test_event()
+        };
+
+        let id = app
+            .create_event(add_event())
             .await
-            .unwrap();
+            .unwrap()
+            .tokens
+            .public_token;
+        let order = PremiumOrder::StripeSubscriptionId(subscription.clone());
 
-        assert_eq!(e.info.questions.len(), 0);
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)].event.premium_id,
+            Some(order.clone())
+        );
 
-        let e = app
-            .get_event(
-                res.tokens.public_token.clone(),
-                Some(res.tokens.moderator_token.clone().unwrap()),
-                false,
-                None,
-            )
+        // loaded by an event creation racing the cancellation below
+        let racing = app
+            .active_subscription(subscription.clone(), customer.clone())
             .await
             .unwrap();
 
-        assert_eq!(e.info.questions.len(), 1);
+        app.subscription_changed(
+            subscription.clone(),
+            customer.clone(),
+            String::from("canceled"),
+            20,
+        )
+        .await
+        .unwrap();
 
-        app.mod_edit_question(
-            res.tokens.public_token.clone(),
-            res.tokens.moderator_token.clone().unwrap(),
-            q.id,
-            ModQuestion {
-                hide: false,
-                answered: false,
-                screened: true,
-            },
+        // a late `updated` from before the cancellation changes nothing
+        app.subscription_changed(
+            subscription.clone(),
+            customer.clone(),
+            String::from("active"),
+            10,
         )
         .await
         .unwrap();
 
-        let e = app
-            .get_event(res.tokens.public_token.clone(), None, false, None)
-            .await
-            .unwrap();
+        // recording the racing event does not revive the subscription
+        app.subscription_add_event(Some(racing), "racing").await;
 
-        assert_eq!(e.info.questions.len(), 1);
+        let stored = app.eventsdb.get_subscription(&subscription).await.unwrap();
+        assert_eq!(stored.status, "canceled");
+        assert_eq!(stored.update_time_unix, 20);
+        assert_eq!(stored.events, vec![id.clone(), String::from("racing")]);
+
+        // the existing event stays premium, new ones are refused
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)].event.premium_id,
+            Some(order)
+        );
+        assert!(matches!(
+            app.create_event(add_event()).await.unwrap_err(),
+            InternalError::SubscriptionInactive(_)
+        ));
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_screening_question_disapprove() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
+    async fn test_premium_checkout() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let app = test_app(events.clone());
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
+
+        // other tiers are not for sale
+        assert!(
+            app.request_premium_upgrade(
+                id.clone(),
+                secret.clone(),
+                false,
+                shared::ModRequestPremium {
+                    context: shared::ModRequestPremiumContext::Regular,
+                    tier: PremiumTier::ExtendedRetention,
                 },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
+            )
             .await
-            .unwrap();
+            .is_err()
+        );
 
-        events
-            .db
-            .lock()
+        let EventUpgradeResponse::Redirect { url } = app
+            .request_premium_upgrade(
+                id.clone(),
+                secret,
+                false,
+                shared::ModRequestPremium {
+                    context: shared::ModRequestPremiumContext::Regular,
+                    tier: PremiumTier::SingleEvent,
+                },
+            )
             .await
-            .get_mut(&event_key(&res.tokens.public_token))
             .unwrap()
-            .event
-            .do_screening = true;
+        else {
+            panic!("expected a checkout redirect");
+        };
 
-        let q = app
-            .add_question(
-                res.tokens.public_token.clone(),
-                AddQuestion {
-                    text: String::from(TEST_VALID_QUESTION),
-                    tag: None,
+        let (_, session) = url.split_once("token=").unwrap();
+
+        // the moderator returns from the checkout
+        assert!(
+            app.premium_capture(id.clone(), session.to_string())
+                .await
+                .unwrap()
+                .order_captured
+        );
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)].event.premium_id,
+            Some(PremiumOrder::StripeSessionId(session.to_string()))
+        );
+
+        // a session of another event does not capture this one
+        assert!(
+            app.premium_capture(String::from("other"), session.to_string())
+                .await
+                .is_err()
+        );
+
+        // admins upgrade to any tier
+        let res = app.create_event(test_event()).await.unwrap();
+        let id = res.tokens.public_token.clone();
+
+        assert_eq!(
+            app.request_premium_upgrade(
+                id.clone(),
+                String::new(),
+                true,
+                shared::ModRequestPremium {
+                    context: shared::ModRequestPremiumContext::Regular,
+                    tier: PremiumTier::ExtendedRetention,
                 },
             )
             .await
-            .unwrap();
+            .unwrap(),
+            EventUpgradeResponse::AdminUpgrade
+        );
+        let e = events.db.lock().await[&event_key(&id)].event.clone();
+        assert_eq!(
+            e.active_premium_tier(),
+            Some(PremiumTier::ExtendedRetention)
+        );
+    }
 
-        assert_eq!(q.screening, true);
+    #[tokio::test]
+    async fn test_invoice_paid() {
+        let events = Arc::new(InMemoryEventsDB::default());
+        let app = test_app(events.clone());
 
-        let e = app
-            .get_event(res.tokens.public_token.clone(), None, false, None)
-            .await
-            .unwrap();
+        let res = app.create_event(test_event()).await.unwrap();
 
-        assert_eq!(e.info.questions.len(), 0);
+        let id = res.tokens.public_token.clone();
 
-        let e = app
-            .mod_edit_question(
-                res.tokens.public_token.clone(),
-                res.tokens.moderator_token.clone().unwrap(),
-                q.id,
-                ModQuestion {
-                    hide: true,
-                    answered: false,
-                    screened: false,
-                },
-            )
+        let paid = |invoice: &str| shared::AdminInvoicePaid {
+            invoice: String::from(invoice),
+            ..Default::default()
+        };
+
+        assert!(app.invoice_paid(id.clone(), &paid(" ")).await.is_err());
+        assert!(
+            events.db.lock().await[&event_key(&id)]
+                .event
+                .premium_id
+                .is_none()
+        );
+
+        app.invoice_paid(id.clone(), &paid(" RE-2024-017 "))
             .await
             .unwrap();
-
-        assert_eq!(e.questions[0].screening, false);
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)].event.premium_id,
+            Some(PremiumOrder::Invoice(String::from("RE-2024-017")))
+        );
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_screening_enable() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
+    async fn test_vouchers() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let app = test_app(events.clone());
+
+        let create = |code: &str| shared::AdminCreateVoucher {
+            code: String::from(code),
+            note: String::from("meetup"),
+            max_redemptions: 1,
+            tier: PremiumTier::SingleEvent,
+            valid_until_unix: None,
+        };
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
+        assert!(app.create_voucher(&create("a b")).await.is_err());
+        assert!(
+            app.create_voucher(&shared::AdminCreateVoucher {
+                max_redemptions: 0,
+                ..create("MEETUP-24")
             })
             .await
-            .unwrap();
+            .is_err()
+        );
+        app.create_voucher(&create(" meetup-24 ")).await.unwrap();
+        assert!(app.create_voucher(&create("MEETUP-24")).await.is_err());
 
-        events
-            .db
-            .lock()
-            .await
-            .get_mut(&event_key(&res.tokens.public_token))
-            .unwrap()
-            .event
-            .premium_id = Some(PremiumOrder::PaypalOrderId(String::from("foo")));
+        let mut tokens = Vec::new();
+        for _ in 0..2 {
+            let res = app.create_event(test_event()).await.unwrap();
+            tokens.push(res.tokens);
+        }
+        let id = tokens[0].public_token.clone();
+        let secret = tokens[0].moderator_token.clone().unwrap();
+
+        // wrong secrets do not count towards the event's rate limit
+        for _ in 0..=VOUCHER_POLICY.free_attempts {
+            assert!(matches!(
+                app.redeem_voucher(id.clone(), String::from("wrong"), "meetup-24", "client")
+                    .await
+                    .unwrap_err(),
+                InternalError::WrongModeratorToken(_)
+            ));
+        }
 
-        let e = app
-            .mod_edit_event(
-                res.tokens.public_token.clone(),
-                res.tokens.moderator_token.clone().unwrap(),
-                ModEvent {
-                    screening: Some(true),
-                    ..Default::default()
-                },
-            )
+        app.redeem_voucher(id.clone(), secret, "meetup-24", "client")
             .await
             .unwrap();
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)].event.premium_id,
+            Some(PremiumOrder::Voucher(String::from("MEETUP-24")))
+        );
 
-        assert!(e.is_screening());
-    }
+        // used up
+        let other = tokens[1].public_token.clone();
+        let other_secret = tokens[1].moderator_token.clone().unwrap();
+        for code in ["MEETUP-24", "UNKNOWN"] {
+            assert!(matches!(
+                app.redeem_voucher(other.clone(), other_secret.clone(), code, "client")
+                    .await
+                    .unwrap_err(),
+                InternalError::VoucherInvalid(_)
+            ));
+        }
+        assert!(
+            events.db.lock().await[&event_key(&other)]
+                .event
+                .premium_id
+                .is_none()
+        );
 
-    #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_duplicate_question_check() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
-        let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
+        assert_eq!(
+            app.voucher("meetup-24").await.unwrap().events,
+            vec![id.clone()]
         );
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
+        // a redemption whose upgrade failed is given back
+        app.release_voucher("MEETUP-24", &id).await;
+        assert!(app.voucher("meetup-24").await.unwrap().events.is_empty());
+        app.redeem_voucher(other.clone(), other_secret, "meetup-24", "client")
             .await
             .unwrap();
-
-        app.add_question(
-            res.tokens.public_token.clone(),
-            AddQuestion {
-                text: String::from(TEST_VALID_QUESTION),
-                tag: None,
-            },
-        )
-        .await
-        .unwrap();
-
-        let request = app
-            .add_question(
-                res.tokens.public_token.clone(),
-                AddQuestion {
-                    text: String::from(TEST_VALID_QUESTION),
-                    tag: None,
-                },
-            )
-            .await;
-
-        assert!(matches!(
-            request.unwrap_err(),
-            InternalError::DuplicateQuestion
-        ))
+        assert_eq!(app.voucher("meetup-24").await.unwrap().events, vec![other]);
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_password_protection() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
+    async fn test_premium_tiers() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let app = test_app(events.clone());
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
-            .await
-            .unwrap();
+        let res = app.create_event(test_event()).await.unwrap();
 
-        let event_id = res.tokens.public_token.clone();
-        let mod_token = res.tokens.moderator_token.clone().unwrap();
+        let id = res.tokens.public_token.clone();
+        let secret = res.tokens.moderator_token.clone().unwrap();
 
-        let question_text = "very long, sophisticated question you can ask!";
-        app.add_question(
-            event_id.clone(),
-            AddQuestion {
-                text: String::from(question_text),
-                tag: None,
-            },
-        )
-        .await
-        .unwrap();
+        let set_limits = || {
+            app.mod_edit_event(
+                id.clone(),
+                secret.clone(),
+                ModEvent {
+                    question_limits: Some(QuestionLimits {
+                        max_questions: 10,
+                        ..QuestionLimits::default()
+                    }),
+                    ..Default::default()
+                },
+            )
+        };
 
-        app.mod_edit_event(
-            event_id.clone(),
-            mod_token.clone(),
-            ModEvent {
-                password: Some(shared::EventPassword::Enabled(String::from("pwd"))),
-                ..Default::default()
+        // an expiry in the past is refused
+        assert!(
+            app.invoice_paid(
+                id.clone(),
+                &shared::AdminInvoicePaid {
+                    invoice: String::from("RE-1"),
+                    tier: PremiumTier::ExtendedRetention,
+                    until_unix: Some(timestamp_now() - 1),
+                }
+            )
+            .await
+            .is_err()
+        );
+
+        app.invoice_paid(
+            id.clone(),
+            &shared::AdminInvoicePaid {
+                invoice: String::from("RE-1"),
+                tier: PremiumTier::ExtendedRetention,
+                until_unix: Some(timestamp_now() + 60),
             },
         )
         .await
         .unwrap();
 
-        // locked: no grant -> masked + wrong-password flag
-        let e = app
-            .get_event(event_id.clone(), None, false, None)
+        // retention only: the data survives the free period but no premium features
+        {
+            let mut db = events.db.lock().await;
+            let e = &mut db.get_mut(&event_key(&id)).unwrap().event;
+            e.create_time_unix -= 8 * 24 * 60 * 60;
+            assert!(e.premium());
+            assert!(!e.is_timed_out_and_free());
+        }
+        assert!(matches!(
+            set_limits().await.unwrap_err(),
+            InternalError::PremiumOnlyFeature(_)
+        ));
+        let info = app
+            .get_event(id.clone(), Some(secret.clone()), false, None)
             .await
             .unwrap();
+        assert!(!info.info.is_premium());
+        assert_eq!(info.info.premium_tier, Some(PremiumTier::ExtendedRetention));
 
-        assert_ne!(&e.info.questions[0].text, question_text);
-        assert!(e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
-
-        // unlocked: grant carries the fingerprint of the current password -> full data
-        let e = app
-            .get_event(
-                event_id.clone(),
-                None,
-                false,
-                Some(crate::utils::pwd_fingerprint("pwd")),
-            )
+        // buying the premium features on top of the retention is not refused as already premium
+        app.payment_webhook(String::from("cs_1"), id.clone())
+            .await
+            .unwrap();
+        set_limits().await.unwrap();
+        let info = app
+            .get_event(id.clone(), Some(secret.clone()), false, None)
             .await
             .unwrap();
+        assert!(info.info.is_premium());
+        assert_eq!(info.info.premium_tier, Some(PremiumTier::SingleEvent));
 
-        assert_eq!(&e.info.questions[0].text, question_text);
-        assert!(!e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
+        // once expired it is a free event again
+        events
+            .db
+            .lock()
+            .await
+            .get_mut(&event_key(&id))
+            .unwrap()
+            .event
+            .premium_until_unix = Some(timestamp_now() - 1);
+        {
+            let db = events.db.lock().await;
+            let e = &db[&event_key(&id)].event;
+            assert!(!e.premium());
+            assert!(e.is_timed_out_and_free());
+        }
+        let info = app
+            .get_event(id.clone(), Some(secret.clone()), false, None)
+            .await
+            .unwrap();
+        assert!(!info.info.is_premium());
 
-        // rotated password -> the old grant fingerprint no longer matches -> re-masked
-        app.mod_edit_event(
-            event_id.clone(),
-            mod_token.clone(),
-            shared::ModEvent {
-                password: Some(shared::EventPassword::Enabled("rotated".into())),
+        // and can be upgraded again, this time for good
+        app.invoice_paid(
+            id.clone(),
+            &shared::AdminInvoicePaid {
+                invoice: String::from("RE-2"),
                 ..Default::default()
             },
         )
         .await
         .unwrap();
-
-        let e = app
-            .get_event(
-                event_id.clone(),
-                None,
-                false,
-                Some(crate::utils::pwd_fingerprint("pwd")),
-            )
-            .await
-            .unwrap();
-
-        assert_ne!(&e.info.questions[0].text, question_text);
-        assert!(e.flags.contains(EventResponseFlags::WRONG_PASSWORD));
+        set_limits().await.unwrap();
+        assert_eq!(
+            events.db.lock().await[&event_key(&id)]
+                .event
+                .premium_until_unix,
+            None
+        );
     }
 
     #[tokio::test]
-    #[tracing_test::traced_test]
-    async fn test_event_question_tags() {
-        let pubsubreceiver = Arc::new(PubSubReceiverInMemory::default());
-        let pubsub = PubSubInMemory::default();
-        pubsub.set_receiver(pubsubreceiver.clone()).await;
+    async fn test_premium_issues() {
         let events = Arc::new(InMemoryEventsDB::default());
-        let app = App::new(
-            events.clone(),
-            Arc::new(pubsub),
-            Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
-            Tracking::default(),
-            String::new(),
-        );
+        let app = test_app(events.clone());
 
-        let res = app
-            .create_event(AddEvent {
-                data: EventData {
-                    name: String::from("123456789"),
-                    description: String::from("123456789 123456789 123456789 !"),
-                    ..EventData::default()
-                },
-                moderator_email: None,
-                test: false,
-                customer: None,
-            })
+        let res = app.create_event(test_event()).await.unwrap();
+
+        let id = res.tokens.public_token.clone();
+        let order = PremiumOrder::StripeSessionId(String::from("cs_1"));
+
+        app.payment_webhook(String::from("cs_1"), id.clone())
             .await
             .unwrap();
 
-        events
-            .db
-            .lock()
-            .await
-            .get_mut(&event_key(&res.tokens.public_token))
-            .unwrap()
-            .event
-            .premium_id = Some(PremiumOrder::PaypalOrderId(String::from("foo")));
+        let event = async || events.db.lock().await[&event_key(&id)].event.clone();
 
+        // a dispute flags but keeps premium
+        app.premium_issue(
+            id.clone(),
+            PremiumIssueReason::Disputed,
+            String::from("dp_1"),
+        )
+        .await
+        .unwrap();
+        let e = event().await;
+        assert_eq!(e.premium_id, Some(order.clone()));
         assert_eq!(
-            app.mod_edit_event(
-                res.tokens.public_token.clone(),
-                res.tokens.moderator_token.clone().unwrap(),
-                ModEvent {
-                    current_tag: Some(CurrentTag::Enabled(String::from("tag1"))),
-                    ..Default::default()
-                },
-            )
-            .await
-            .unwrap()
-            .tags
-            .current_tag
-            .unwrap(),
-            TagId(0)
+            e.premium_issue.map(|issue| (issue.reason, issue.revoked)),
+            Some((PremiumIssueReason::Disputed, None))
         );
 
-        let request = app
-            .add_question(
-                res.tokens.public_token.clone(),
-                AddQuestion {
-                    text: String::from(TEST_VALID_QUESTION),
-                    tag: None,
-                },
-            )
-            .await
-            .unwrap();
+        // a failed async payment of another checkout does not touch this order
+        app.premium_issue(
+            id.clone(),
+            PremiumIssueReason::AsyncPaymentFailed,
+            String::from("cs_2"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(event().await.premium_id, Some(order.clone()));
 
-        assert_eq!(request.tag.unwrap(), TagId(0))
+        // neither does the refund of another payment
+        app.premium_issue(
+            id.clone(),
+            PremiumIssueReason::Refunded,
+            String::from("pi_2"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(event().await.premium_id, Some(order.clone()));
+
+        app.premium_issue(
+            id.clone(),
+            PremiumIssueReason::Refunded,
+            String::from("pi_1"),
+        )
+        .await
+        .unwrap();
+        let e = event().await;
+        assert_eq!(e.premium_id, None);
+        assert_eq!(
+            e.premium_issue.map(|issue| issue.revoked),
+            Some(Some(order.clone()))
+        );
+
+        // the retried webhook keeps what was revoked
+        app.premium_issue(
+            id.clone(),
+            PremiumIssueReason::Refunded,
+            String::from("pi_1"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            event().await.premium_issue.map(|issue| issue.revoked),
+            Some(Some(order))
+        );
     }
 }
@@ -7,39 +7,90 @@ use axum::{
     http::{HeaderMap, header, request::Parts},
     response::{AppendHeaders, IntoResponse},
 };
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use shared::{GetUserInfo, UserInfo};
 use tracing::instrument;
 
-use crate::{env::admin_pwd_hash, error::InternalError, utils::pwd_fingerprint};
+use crate::{env::admin_pwd_hash, error::InternalError};
 
 /// Cookie carrying the admin JWT.
 const AUTH_COOKIE: &str = "auth";
 /// Cookie carrying a per-event "password proven" grant JWT.
 const PWD_COOKIE: &str = "pwd";
+/// Prefix of the per-event moderator grant cookie (`mod-<event>`).
+const MOD_COOKIE_PREFIX: &str = "mod-";
+/// Cookie carrying the anonymous poll voter id.
+const VOTER_COOKIE: &str = "voter";
+/// Cookie carrying the organizer session JWT.
+const ORG_COOKIE: &str = "org";
 /// `sub` values that scope a token to one purpose so it cannot be replayed as another.
 const ADMIN_NAME: &str = "admin";
 const PWD_KIND: &str = "pwd";
+const MOD_KIND: &str = "mod";
+const VOTER_KIND: &str = "voter";
+const LOGIN_KIND: &str = "login";
+const ORG_KIND: &str = "org";
 /// Token / cookie lifetime (was the session ttl).
 const COOKIE_TTL: Duration = Duration::from_secs(2 * 60 * 60);
+/// Moderator grants live longer: the mod link is meant to be exchanged once, not per visit.
+const MOD_COOKIE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Voter ids only need to outlive the events they voted in.
+const VOTER_COOKIE_TTL: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+/// Magic login links travel by mail, keep the window a leaked mail is useful in short.
+const LOGIN_TOKEN_TTL: Duration = Duration::from_secs(15 * 60);
+const ORG_COOKIE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// HMAC key plus the `kid` naming it in the JWT header, so a verifier picks the right key
+/// without trial decoding.
+#[derive(Clone)]
+struct SigningKey {
+    kid: String,
+    secret: Arc<[u8]>,
+}
 
-/// JWT signing key + cookie flags, shared via request extension so the handlers and the
+impl SigningKey {
+    fn new(secret: Vec<u8>) -> Self {
+        Self {
+            kid: key_id(&secret),
+            secret: secret.into(),
+        }
+    }
+}
+
+/// Non-reversible id of a signing key: derived from the key itself so rotating only means
+/// changing env vars, with no separate id to keep in sync.
+fn key_id(secret: &[u8]) -> String {
+    hex::encode(Sha256::digest(secret).get(..8).unwrap_or_default())
+}
+
+/// JWT signing keys + cookie flags, shared via request extension so the handlers and the
 /// `OptionalUser` extractor can verify tokens without any session store.
 #[derive(Clone)]
 pub struct AuthConfig {
-    secret: Arc<[u8]>,
+    /// signs every new token; verifies too.
+    current: SigningKey,
+    /// retired keys: only verify tokens issued before a rotation, never sign.
+    previous: Arc<[SigningKey]>,
     /// harden cookies (SameSite=Strict) — true on every public deployment, not just prod.
     secure: bool,
 }
 
 impl AuthConfig {
-    fn new(secret: Vec<u8>, secure: bool) -> Self {
+    fn new(secret: Vec<u8>, previous: Vec<Vec<u8>>, secure: bool) -> Self {
         Self {
-            secret: secret.into(),
+            current: SigningKey::new(secret),
+            previous: previous.into_iter().map(SigningKey::new).collect(),
             secure,
         }
     }
+
+    fn keys(&self) -> impl Iterator<Item = &SigningKey> {
+        std::iter::once(&self.current).chain(self.previous.iter())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -53,6 +104,17 @@ struct Claims {
     /// is rotated. Absent on admin tokens.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pfp: Option<String>,
+    /// moderator secret a `mod` grant was exchanged for. The cookie is `HttpOnly` and only ever
+    /// holds what the mod link already carries; it is still checked against the event on use.
+    /// On a `login` token: the one-time nonce stored with the organizer when it was mailed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sec: Option<String>,
+    /// random id of a poll voter, lets votes be deduplicated without knowing who voted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vid: Option<String>,
+    /// organizer (hashed email) a `login` token or `org` session belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    org: Option<String>,
     exp: u64,
 }
 
@@ -71,22 +133,35 @@ fn validation() -> Validation {
 }
 
 fn encode_token(cfg: &AuthConfig, claims: &Claims) -> Result<String, InternalError> {
+    let header = Header {
+        kid: Some(cfg.current.kid.clone()),
+        ..Header::default()
+    };
+
     encode(
-        &Header::default(),
+        &header,
         claims,
-        &EncodingKey::from_secret(cfg.secret.as_ref()),
+        &EncodingKey::from_secret(cfg.current.secret.as_ref()),
     )
     .map_err(|e| InternalError::General(format!("jwt encode: {e}")))
 }
 
 fn decode_token(cfg: &AuthConfig, token: &str) -> Option<Claims> {
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(cfg.secret.as_ref()),
-        &validation(),
-    )
-    .ok()
-    .map(|data| data.claims)
+    let kid = decode_header(token).ok()?.kid;
+
+    cfg.keys()
+        // a `kid` pins the key; tokens minted before rotation support carry none, so those
+        // are tried against every key we still accept
+        .filter(|key| kid.as_ref().is_none_or(|kid| *kid == key.kid))
+        .find_map(|key| {
+            decode::<Claims>(
+                token,
+                &DecodingKey::from_secret(key.secret.as_ref()),
+                &validation(),
+            )
+            .ok()
+        })
+        .map(|data| data.claims)
 }
 
 fn issue_admin_token(cfg: &AuthConfig) -> Result<String, InternalError> {
@@ -96,6 +171,9 @@ fn issue_admin_token(cfg: &AuthConfig) -> Result<String, InternalError> {
             sub: ADMIN_NAME.to_string(),
             event: None,
             pfp: None,
+            sec: None,
+            vid: None,
+            org: None,
             exp: now_secs() + COOKIE_TTL.as_secs(),
         },
     )
@@ -110,15 +188,22 @@ fn verify_admin(cfg: &AuthConfig, token: &str) -> Option<AdminUser> {
     })
 }
 
-/// `Set-Cookie` value granting the caller access to the event whose password (`pwd`) they just
-/// proved. The password fingerprint is bound in so the grant self-invalidates on rotation.
-pub fn pwd_grant_cookie(cfg: &AuthConfig, event: &str, pwd: &str) -> Result<String, InternalError> {
+/// `Set-Cookie` value granting the caller access to the event whose password they just proved.
+/// The stored password's `fingerprint` is bound in so the grant self-invalidates on rotation.
+pub fn pwd_grant_cookie(
+    cfg: &AuthConfig,
+    event: &str,
+    fingerprint: &str,
+) -> Result<String, InternalError> {
     let token = encode_token(
         cfg,
         &Claims {
             sub: PWD_KIND.to_string(),
             event: Some(event.to_string()),
-            pfp: Some(pwd_fingerprint(pwd)),
+            pfp: Some(fingerprint.to_string()),
+            sec: None,
+            vid: None,
+            org: None,
             exp: now_secs() + COOKIE_TTL.as_secs(),
         },
     )?;
@@ -133,6 +218,143 @@ pub fn pwd_grant_fingerprint(cfg: &AuthConfig, headers: &HeaderMap, event: &str)
     (claims.sub == PWD_KIND && claims.event.as_deref() == Some(event)).then_some(claims.pfp)?
 }
 
+/// `Set-Cookie` value granting moderator access to `event` without the secret in any URL.
+/// Only hand this out after `secret` was checked against the event.
+pub fn mod_grant_cookie(
+    cfg: &AuthConfig,
+    event: &str,
+    secret: &str,
+) -> Result<String, InternalError> {
+    let token = encode_token(
+        cfg,
+        &Claims {
+            sub: MOD_KIND.to_string(),
+            event: Some(event.to_string()),
+            pfp: None,
+            sec: Some(secret.to_string()),
+            vid: None,
+            org: None,
+            exp: now_secs() + MOD_COOKIE_TTL.as_secs(),
+        },
+    )?;
+    Ok(set_cookie(
+        cfg,
+        &mod_cookie_name(event),
+        &token,
+        MOD_COOKIE_TTL,
+    ))
+}
+
+/// Moderator secret presented for `event`: an `Authorization: Bearer` header, else the grant
+/// cookie from [`mod_grant_cookie`]. Callers still verify it against the event.
+pub fn mod_secret(cfg: &AuthConfig, headers: &HeaderMap, event: &str) -> Option<String> {
+    if let Some(secret) = bearer_token(headers) {
+        return Some(secret.to_string());
+    }
+
+    let claims =
+        read_cookie(headers, &mod_cookie_name(event)).and_then(|token| decode_token(cfg, token))?;
+
+    (claims.sub == MOD_KIND && claims.event.as_deref() == Some(event)).then_some(claims.sec)?
+}
+
+/// `Set-Cookie` value handing the caller the voter id `voter`, not bound to any event so one
+/// id dedups votes across all polls.
+pub fn voter_cookie(cfg: &AuthConfig, voter: &str) -> Result<String, InternalError> {
+    let token = encode_token(
+        cfg,
+        &Claims {
+            sub: VOTER_KIND.to_string(),
+            event: None,
+            pfp: None,
+            sec: None,
+            vid: Some(voter.to_string()),
+            org: None,
+            exp: now_secs() + VOTER_COOKIE_TTL.as_secs(),
+        },
+    )?;
+    Ok(set_cookie(cfg, VOTER_COOKIE, &token, VOTER_COOKIE_TTL))
+}
+
+/// Voter id of a valid cookie from [`voter_cookie`], if any.
+pub fn voter_id(cfg: &AuthConfig, headers: &HeaderMap) -> Option<String> {
+    let claims = read_cookie(headers, VOTER_COOKIE).and_then(|token| decode_token(cfg, token))?;
+
+    (claims.sub == VOTER_KIND).then_some(claims.vid)?
+}
+
+/// Token for the magic login link mailed to `organizer`, only good together with `nonce`.
+pub fn organizer_login_token(
+    cfg: &AuthConfig,
+    organizer: &str,
+    nonce: &str,
+) -> Result<String, InternalError> {
+    encode_token(
+        cfg,
+        &Claims {
+            sub: LOGIN_KIND.to_string(),
+            event: None,
+            pfp: None,
+            sec: Some(nonce.to_string()),
+            vid: None,
+            org: Some(organizer.to_string()),
+            exp: now_secs() + LOGIN_TOKEN_TTL.as_secs(),
+        },
+    )
+}
+
+/// Organizer and nonce of a valid, unexpired token from [`organizer_login_token`]. The caller
+/// still checks the nonce against the organizer so a link works only once.
+pub fn verify_login_token(cfg: &AuthConfig, token: &str) -> Option<(String, String)> {
+    let claims = decode_token(cfg, token)?;
+
+    (claims.sub == LOGIN_KIND).then_some(claims.org.zip(claims.sec))?
+}
+
+/// `Set-Cookie` value logging the caller in as `organizer`.
+pub fn organizer_cookie(cfg: &AuthConfig, organizer: &str) -> Result<String, InternalError> {
+    let token = encode_token(
+        cfg,
+        &Claims {
+            sub: ORG_KIND.to_string(),
+            event: None,
+            pfp: None,
+            sec: None,
+            vid: None,
+            org: Some(organizer.to_string()),
+            exp: now_secs() + ORG_COOKIE_TTL.as_secs(),
+        },
+    )?;
+    Ok(set_cookie(cfg, ORG_COOKIE, &token, ORG_COOKIE_TTL))
+}
+
+/// `Set-Cookie` value logging an organizer out.
+pub fn clear_organizer_cookie(cfg: &AuthConfig) -> String {
+    set_cookie(cfg, ORG_COOKIE, "", Duration::ZERO)
+}
+
+/// Organizer of a valid session cookie from [`organizer_cookie`], if any.
+pub fn organizer_id(cfg: &AuthConfig, headers: &HeaderMap) -> Option<String> {
+    let claims = read_cookie(headers, ORG_COOKIE).and_then(|token| decode_token(cfg, token))?;
+
+    (claims.sub == ORG_KIND).then_some(claims.org)?
+}
+
+fn mod_cookie_name(event: &str) -> String {
+    format!("{MOD_COOKIE_PREFIX}{event}")
+}
+
+/// Token of an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
 /// Read a single cookie value out of the `Cookie` request header.
 fn read_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
     headers
@@ -222,16 +444,18 @@ where
     }
 }
 
-/// Stateless JWT config shared into the router as a request extension. `secure` hardens
-/// cookies (SameSite=Strict) and is set for every public (non-local) deployment.
-pub fn setup(secret: Vec<u8>, secure: bool) -> AuthConfig {
-    AuthConfig::new(secret, secure)
+/// Stateless JWT config shared into the router as a request extension. Tokens are signed with
+/// `secret` and verified against it and every `previous` key. `secure` hardens cookies
+/// (SameSite=Strict) and is set for every public (non-local) deployment.
+pub fn setup(secret: Vec<u8>, previous: Vec<Vec<u8>>, secure: bool) -> AuthConfig {
+    AuthConfig::new(secret, previous, secure)
 }
 
 #[cfg(test)]
 pub fn setup_test() -> AuthConfig {
     AuthConfig::new(
         b"0123456789012345678901234567890123456789012345678901234567890123".to_vec(),
+        Vec::new(),
         false,
     )
 }
@@ -239,20 +463,22 @@ pub fn setup_test() -> AuthConfig {
 #[cfg(test)]
 mod test {
     use super::*;
-    use pretty_assertions::assert_eq;
+    use crate::utils::pwd_fingerprint;
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    const KEY_A: &[u8] = b"0123456789012345678901234567890123456789012345678901234567890123";
+    const KEY_B: &[u8] = b"abcdefghijabcdefghijabcdefghijabcdefghijabcdefghijabcdefghij1234";
 
     fn cfg() -> AuthConfig {
-        AuthConfig::new(
-            b"0123456789012345678901234567890123456789012345678901234567890123".to_vec(),
-            false,
-        )
+        AuthConfig::new(KEY_A.to_vec(), Vec::new(), false)
     }
 
+    /// signs with the current key but without a `kid`, like tokens minted before rotation
     fn sign(cfg: &AuthConfig, claims: &Claims) -> String {
         encode(
             &Header::default(),
             claims,
-            &EncodingKey::from_secret(cfg.secret.as_ref()),
+            &EncodingKey::from_secret(cfg.current.secret.as_ref()),
         )
         .unwrap()
     }
@@ -270,6 +496,7 @@ mod test {
         let token = issue_admin_token(&cfg()).unwrap();
         let other = AuthConfig::new(
             b"9999999999999999999999999999999999999999999999999999999999999999".to_vec(),
+            Vec::new(),
             false,
         );
         assert!(verify_admin(&other, &token).is_none());
@@ -286,6 +513,9 @@ mod test {
                 sub: PWD_KIND.to_string(),
                 event: Some("EVENT".to_string()),
                 pfp: Some(pwd_fingerprint("secret")),
+                sec: None,
+                vid: None,
+                org: None,
                 exp: now_secs() + 60,
             },
         );
@@ -301,6 +531,9 @@ mod test {
                 sub: ADMIN_NAME.to_string(),
                 event: None,
                 pfp: None,
+                sec: None,
+                vid: None,
+                org: None,
                 exp: now_secs().saturating_sub(3600),
             },
         );
@@ -329,7 +562,8 @@ mod test {
     #[test]
     fn pwd_grant_is_event_scoped_and_carries_fingerprint() {
         let cfg = cfg();
-        let headers = headers_with(&pwd_grant_cookie(&cfg, "EVENT_A", "secret").unwrap());
+        let headers =
+            headers_with(&pwd_grant_cookie(&cfg, "EVENT_A", &pwd_fingerprint("secret")).unwrap());
         // a valid grant returns the fingerprint of the proven password for its own event ...
         assert_eq!(
             pwd_grant_fingerprint(&cfg, &headers, "EVENT_A"),
@@ -349,7 +583,7 @@ mod test {
     #[test]
     fn pwd_grant_is_not_accepted_as_admin() {
         let cfg = cfg();
-        let cookie = pwd_grant_cookie(&cfg, "EVENT_A", "secret").unwrap();
+        let cookie = pwd_grant_cookie(&cfg, "EVENT_A", &pwd_fingerprint("secret")).unwrap();
         let token = cookie
             .split(';')
             .next()
@@ -358,4 +592,165 @@ mod test {
             .1;
         assert!(verify_admin(&cfg, token).is_none());
     }
+
+    #[test]
+    fn mod_secret_from_bearer_header() {
+        let cfg = cfg();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert_eq!(
+            mod_secret(&cfg, &headers, "EVENT_A"),
+            Some(String::from("s3cret"))
+        );
+
+        headers.insert(header::AUTHORIZATION, "Basic s3cret".parse().unwrap());
+        assert_eq!(mod_secret(&cfg, &headers, "EVENT_A"), None);
+    }
+
+    #[test]
+    fn voter_id_roundtrips() {
+        let cfg = cfg();
+        let headers = headers_with(&voter_cookie(&cfg, "V1").unwrap());
+        assert_eq!(voter_id(&cfg, &headers), Some(String::from("V1")));
+
+        // a mod grant is no voter id
+        let headers = headers_with(
+            &mod_grant_cookie(&cfg, "EVENT_A", "s3cret")
+                .unwrap()
+                .replacen("mod-EVENT_A=", "voter=", 1),
+        );
+        assert_eq!(voter_id(&cfg, &headers), None);
+    }
+
+    #[test]
+    fn login_token_is_not_a_session() {
+        let cfg = cfg();
+        let token = organizer_login_token(&cfg, "ORG", "NONCE").unwrap();
+        assert_eq!(
+            verify_login_token(&cfg, &token),
+            Some((String::from("ORG"), String::from("NONCE")))
+        );
+        assert_eq!(
+            organizer_id(&cfg, &headers_with(&format!("org={token}"))),
+            None
+        );
+
+        let headers = headers_with(&organizer_cookie(&cfg, "ORG").unwrap());
+        assert_eq!(organizer_id(&cfg, &headers), Some(String::from("ORG")));
+
+        let session = read_cookie(&headers, ORG_COOKIE).unwrap();
+        assert_eq!(verify_login_token(&cfg, session), None);
+    }
+
+    #[test]
+    fn mod_grant_is_event_scoped() {
+        let cfg = cfg();
+        let headers = headers_with(&mod_grant_cookie(&cfg, "EVENT_A", "s3cret").unwrap());
+        assert_eq!(
+            mod_secret(&cfg, &headers, "EVENT_A"),
+            Some(String::from("s3cret"))
+        );
+        assert_eq!(mod_secret(&cfg, &headers, "EVENT_B"), None);
+    }
+
+    #[test]
+    fn pwd_grant_is_not_accepted_as_mod_grant() {
+        let cfg = cfg();
+        let token = pwd_grant_cookie(&cfg, "EVENT_A", &pwd_fingerprint("secret")).unwrap();
+        let token = token
+            .split(';')
+            .next()
+            .and_then(|p| p.split_once('='))
+            .unwrap()
+            .1;
+        let headers = headers_with(&format!("mod-EVENT_A={token}"));
+        assert_eq!(mod_secret(&cfg, &headers, "EVENT_A"), None);
+    }
+
+    #[test]
+    fn tokens_carry_kid_of_current_key() {
+        let cfg = cfg();
+        let header = decode_header(&issue_admin_token(&cfg).unwrap()).unwrap();
+        assert_eq!(header.kid, Some(key_id(KEY_A)));
+        assert_ne!(key_id(KEY_A), key_id(KEY_B));
+    }
+
+    #[test]
+    fn rotation_keeps_tokens_of_previous_key_valid() {
+        let before = cfg();
+        let admin = issue_admin_token(&before).unwrap();
+        let grant = headers_with(
+            &pwd_grant_cookie(&before, "EVENT_A", &pwd_fingerprint("secret")).unwrap(),
+        );
+
+        // KEY_B becomes current, KEY_A is kept for verification only
+        let after = AuthConfig::new(KEY_B.to_vec(), vec![KEY_A.to_vec()], false);
+
+        assert!(verify_admin(&after, &admin).is_some());
+        assert_eq!(
+            pwd_grant_fingerprint(&after, &grant, "EVENT_A"),
+            Some(pwd_fingerprint("secret"))
+        );
+
+        // new tokens are signed with the new key only
+        let fresh = issue_admin_token(&after).unwrap();
+        assert_eq!(decode_header(&fresh).unwrap().kid, Some(key_id(KEY_B)));
+        assert!(verify_admin(&before, &fresh).is_none());
+    }
+
+    #[test]
+    fn retired_key_no_longer_verifies() {
+        let admin = issue_admin_token(&cfg()).unwrap();
+
+        let retired = AuthConfig::new(KEY_B.to_vec(), Vec::new(), false);
+
+        assert!(verify_admin(&retired, &admin).is_none());
+    }
+
+    #[test]
+    fn legacy_token_without_kid_verifies_against_previous_key() {
+        let legacy = sign(
+            &cfg(),
+            &Claims {
+                sub: ADMIN_NAME.to_string(),
+                event: None,
+                pfp: None,
+                sec: None,
+                vid: None,
+                org: None,
+                exp: now_secs() + 60,
+            },
+        );
+
+        let rotated = AuthConfig::new(KEY_B.to_vec(), vec![KEY_A.to_vec()], false);
+
+        assert!(verify_admin(&rotated, &legacy).is_some());
+    }
+
+    #[test]
+    fn kid_pins_the_verifying_key() {
+        // signed with KEY_A but claiming to be KEY_B: must not fall back to trying KEY_A
+        let header = Header {
+            kid: Some(key_id(KEY_B)),
+            ..Header::default()
+        };
+        let token = encode(
+            &header,
+            &Claims {
+                sub: ADMIN_NAME.to_string(),
+                event: None,
+                pfp: None,
+                sec: None,
+                vid: None,
+                org: None,
+                exp: now_secs() + 60,
+            },
+            &EncodingKey::from_secret(KEY_A),
+        )
+        .unwrap();
+
+        let rotated = AuthConfig::new(KEY_B.to_vec(), vec![KEY_A.to_vec()], false);
+
+        assert!(verify_admin(&rotated, &token).is_none());
+    }
 }
@@ -0,0 +1,153 @@
+//! Flags questions that should not go live unreviewed: profanity from a built-in list, the
+//! event's own blocklist and link spam. Matching works on whole words after folding case and
+//! common character substitutions, so innocent words merely containing a blocked one pass.
+
+use shared::{ContentFilter, ContentFilterAction};
+
+/// Kept short on purpose: this only picks what a moderator looks at, it is no censor.
+const BUILTIN_WORDS: &[&str] = &[
+    "arsch",
+    "arschloch",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bullshit",
+    "cunt",
+    "dick",
+    "dickhead",
+    "fick",
+    "ficken",
+    "fotze",
+    "fuck",
+    "fucker",
+    "fucking",
+    "hurensohn",
+    "motherfucker",
+    "nigger",
+    "penner",
+    "pussy",
+    "scheisse",
+    "schlampe",
+    "shit",
+    "slut",
+    "twat",
+    "wanker",
+    "whore",
+    "wichser",
+];
+
+/// Link prefixes typical for spam; legit questions rarely need one.
+const LINK_MARKERS: &[&str] = &["http://", "https://", "www."];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Screen,
+    Reject,
+}
+
+pub fn check(filter: &ContentFilter, text: &str) -> Verdict {
+    let action = match filter.action {
+        ContentFilterAction::Off => return Verdict::Pass,
+        ContentFilterAction::Screen => Verdict::Screen,
+        ContentFilterAction::Reject => Verdict::Reject,
+    };
+
+    if is_suspicious(&filter.blocklist, text) {
+        action
+    } else {
+        Verdict::Pass
+    }
+}
+
+fn is_suspicious(blocklist: &[String], text: &str) -> bool {
+    let lower = text.to_lowercase();
+    if LINK_MARKERS.iter().any(|marker| lower.contains(marker)) {
+        return true;
+    }
+
+    let tokens = words(text);
+
+    tokens
+        .iter()
+        .any(|word| BUILTIN_WORDS.contains(&word.as_str()))
+        || blocklist
+            .iter()
+            .map(|entry| words(entry))
+            .filter(|phrase| !phrase.is_empty())
+            .any(|phrase| tokens.windows(phrase.len()).any(|window| window == phrase))
+}
+
+/// Lowercased words with digits/symbols commonly used to dodge filters mapped back to letters.
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '0' | '\u{f6}' => 'o',
+            '1' => 'i',
+            '3' => 'e',
+            '4' | '@' | '\u{e4}' => 'a',
+            '5' | '$' | '\u{df}' => 's',
+            '7' => 't',
+            '\u{fc}' => 'u',
+            c => c,
+        })
+        .collect::<String>()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn filter(action: ContentFilterAction, blocklist: &[&str]) -> ContentFilter {
+        ContentFilter {
+            action,
+            blocklist: blocklist.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_off_passes_everything() {
+        let f = filter(ContentFilterAction::Off, &["competitor"]);
+        assert_eq!(
+            check(&f, "what the fuck is competitor doing?"),
+            Verdict::Pass
+        );
+    }
+
+    #[test]
+    fn test_builtin_words_are_whole_word_and_normalized() {
+        let f = filter(ContentFilterAction::Screen, &[]);
+
+        assert_eq!(check(&f, "is this sh1t for real?"), Verdict::Screen);
+        assert_eq!(check(&f, "FUCK! why?"), Verdict::Screen);
+        // substrings of innocent words are fine
+        assert_eq!(check(&f, "how was the trip to Scunthorpe?"), Verdict::Pass);
+        assert_eq!(check(&f, "who wrote Moby Dickens?"), Verdict::Pass);
+    }
+
+    #[test]
+    fn test_event_blocklist_phrases() {
+        let f = filter(ContentFilterAction::Reject, &["Acme Corp", "crypto"]);
+
+        assert_eq!(check(&f, "is acme   corp hiring?"), Verdict::Reject);
+        assert_eq!(check(&f, "thoughts on CRYPTO?"), Verdict::Reject);
+        assert_eq!(check(&f, "is acme hiring?"), Verdict::Pass);
+    }
+
+    #[test]
+    fn test_links_are_suspicious() {
+        let f = filter(ContentFilterAction::Screen, &[]);
+
+        assert_eq!(check(&f, "great deals at www.example.com"), Verdict::Screen);
+        assert_eq!(
+            check(&f, "see HTTPS://example.com for more"),
+            Verdict::Screen
+        );
+    }
+}
@@ -8,10 +8,27 @@ pub const ENV_WEEME_KEY: &str = "WEEME_KEY";
 pub const ENV_SENTRY_DSN: &str = "LA_SENTRY_DSN";
 pub const ENV_PORT: &str = "LA_PORT";
 pub const ENV_POSTHOG_KEY: &str = "LA_POSTHOG_KEY";
+/// `posthog`, `jsonl`, `stdout`, `webhook` or `none`, see [`SinkConfig`](crate::tracking::SinkConfig).
+pub const ENV_TRACKING_SINK: &str = "LA_TRACKING_SINK";
+/// File the `jsonl` tracking sink appends to.
+pub const ENV_TRACKING_FILE: &str = "LA_TRACKING_FILE";
+/// Url the `webhook` tracking sink posts batches to.
+pub const ENV_TRACKING_WEBHOOK_URL: &str = "LA_TRACKING_WEBHOOK_URL";
 const ENV_ADMIN_PWD_HASH: &str = "LA_ADMIN_PWD_HASH";
 const ENV_SESSION_SECRET: &str = "LA_SESSION_SECRET";
+const ENV_SESSION_SECRET_PREVIOUS: &str = "LA_SESSION_SECRET_PREVIOUS";
 pub const ENV_STRIPE_SECRET: &str = "LA_STRIPE_SECRET";
 pub const ENV_STRIPE_HOOK_SECRET: &str = "LA_STRIPE_HOOK_SECRET";
+/// `1` keeps the whole deployment away from third parties, see [`privacy_mode`].
+const ENV_PRIVACY_MODE: &str = "LA_PRIVACY_MODE";
+/// `1` replaces stripe with [`FakePayment`](crate::payment::FakePayment), refused in prod.
+pub const ENV_PAYMENT_FAKE: &str = "LA_PAYMENT_FAKE";
+
+/// Privacy mode treats every event as [`shared::AddEvent::no_third_parties`]: no link
+/// shortener, tracking without event content and not to posthog, no sentry breadcrumbs.
+pub fn privacy_mode() -> bool {
+    std::env::var(ENV_PRIVACY_MODE).is_ok_and(|var| var == "1")
+}
 
 pub fn admin_pwd_hash() -> String {
     std::env::var(ENV_ADMIN_PWD_HASH).unwrap_or_default()
@@ -37,6 +54,24 @@ pub fn session_secret() -> Option<Vec<u8>> {
     (vec.len() >= 64).then_some(vec)
 }
 
+/// Retired JWT signing keys from `LA_SESSION_SECRET_PREVIOUS` (comma separated, so keys must
+/// not contain `,`). Tokens signed with these still verify, but nothing new is signed with them;
+/// this lets `LA_SESSION_SECRET` be rotated without logging out admins or re-locking pwd grants.
+/// A key can be dropped from the list once the token lifetime (2h) has passed since rotating.
+///
+/// Unset yields no previous keys. Any key shorter than 64 bytes yields `None`.
+pub fn previous_session_secrets() -> Option<Vec<Vec<u8>>> {
+    parse_secret_list(&std::env::var(ENV_SESSION_SECRET_PREVIOUS).unwrap_or_default())
+}
+
+fn parse_secret_list(var: &str) -> Option<Vec<Vec<u8>>> {
+    var.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| (key.len() >= 64).then(|| key.as_bytes().to_vec()))
+        .collect()
+}
+
 /// Whether `secret` is the built-in dev fallback; the caller refuses to start with it in prod.
 pub fn is_default_session_secret(secret: &[u8]) -> bool {
     secret == DEFAULT_DEV_SECRET.as_bytes()
@@ -55,4 +90,18 @@ mod test {
             b"some other 64+ byte secret ................................"
         ));
     }
+
+    #[test]
+    fn previous_secrets_are_comma_separated_and_length_checked() {
+        let a = "a".repeat(64);
+        let b = "b".repeat(64);
+
+        assert_eq!(parse_secret_list(""), Some(Vec::new()));
+        assert_eq!(
+            parse_secret_list(&format!("{a}, {b},")),
+            Some(vec![a.as_bytes().to_vec(), b.as_bytes().to_vec()])
+        );
+        // one short key fails the whole list rather than silently dropping it
+        assert_eq!(parse_secret_list(&format!("{a},short")), None);
+    }
 }
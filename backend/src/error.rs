@@ -1,13 +1,21 @@
-use axum::response::{IntoResponse, Response};
+use std::time::Duration;
+
+use axum::{
+    Json,
+    http::header,
+    response::{IntoResponse, Response},
+};
 use deadpool_redis::{CreatePoolError, PoolError};
 use redis::RedisError;
 use reqwest::StatusCode;
 use shared::{
-    AddQuestionValidation, ContextValidation, EditMetaData, PasswordValidation, TagValidation,
+    AddQuestionValidation, ContentFilterValidation, ContextValidation, EditMetaData,
+    ParticipantNameValidation, PasswordValidation, PollValidation, QuestionItem,
+    QuestionLimitsValidation, SessionValidation, SimilarQuestion, TagValidation,
 };
 use thiserror::Error;
 
-use crate::{eventsdb, payment::PaymentError, tracking};
+use crate::{eventsdb, payment::PaymentError};
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Error, Debug)]
@@ -18,6 +26,12 @@ pub enum InternalError {
     #[error("Invalid Login Error")]
     InvalidLogin,
 
+    #[error("Invalid Email")]
+    InvalidEmail,
+
+    #[error("Rate limited for {0:?}")]
+    RateLimited(Duration),
+
     #[error("Accessing Deleted Event: {0}")]
     AccessingDeletedEvent(String),
 
@@ -30,21 +44,57 @@ pub enum InternalError {
     #[error("Premium Only Feature: {0}")]
     PremiumOnlyFeature(String),
 
+    #[error("Subscription not active: {0}")]
+    SubscriptionInactive(String),
+
+    #[error("Voucher not redeemable: {0}")]
+    VoucherInvalid(String),
+
     #[error("Duplicate Question Error")]
     DuplicateQuestion,
 
+    #[error("Question rejected by content filter")]
+    QuestionFiltered,
+
+    #[error("Similar Question Exists: {}", .0.id)]
+    SimilarQuestion(QuestionItem),
+
     #[error("Add Question Validation")]
     AddQuestionValidation(AddQuestionValidation),
 
+    #[error("Participant Name Validation")]
+    ParticipantNameValidation(ParticipantNameValidation),
+
+    #[error("Poll Validation")]
+    PollValidation(PollValidation),
+
+    #[error("Invalid vote on poll: {0}")]
+    InvalidPollVote(i64),
+
+    #[error("Already voted on poll: {0}")]
+    AlreadyVoted(i64),
+
     #[error("Password Validation")]
     PasswordValidation(PasswordValidation),
 
+    #[error("Password attempts locked out for {0:?}")]
+    PasswordLockout(Duration),
+
     #[error("Tag Validation")]
     TagValidation(TagValidation),
 
+    #[error("Session Validation")]
+    SessionValidation(SessionValidation),
+
     #[error("Context Validation")]
     ContextValidation(ContextValidation),
 
+    #[error("Content Filter Validation")]
+    ContentFilterValidation(ContentFilterValidation),
+
+    #[error("Question Limits Validation")]
+    QuestionLimitsValidation(QuestionLimitsValidation),
+
     #[error("Meta Validation")]
     MetaValidation(EditMetaData),
 
@@ -68,9 +118,6 @@ pub enum InternalError {
 
     #[error("Uri Error: {0}")]
     Uri(#[from] axum::http::uri::InvalidUri),
-
-    #[error("Tracking Error: {0}")]
-    Tracking(#[from] tracking::TrackingError),
 }
 
 impl IntoResponse for InternalError {
@@ -87,6 +134,21 @@ impl IntoResponse for InternalError {
                 (StatusCode::FORBIDDEN, "").into_response()
             }
 
+            Self::InvalidEmail => {
+                tracing::info!("invalid email");
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+
+            Self::RateLimited(remaining) => {
+                tracing::warn!("rate limited: {:?}", remaining);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, remaining.as_secs().max(1).to_string())],
+                    "",
+                )
+                    .into_response()
+            }
+
             Self::AccessingDeletedEvent(id) => {
                 tracing::info!("accessing deleted event: {id}");
                 (StatusCode::BAD_REQUEST, "").into_response()
@@ -107,7 +169,25 @@ impl IntoResponse for InternalError {
                 (StatusCode::BAD_REQUEST, "").into_response()
             }
 
+            Self::SubscriptionInactive(id) => {
+                tracing::warn!("subscription not active: {id}");
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+
+            Self::VoucherInvalid(code) => {
+                tracing::warn!("voucher not redeemable: {code}");
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+
             Self::DuplicateQuestion => (StatusCode::BAD_REQUEST, "").into_response(),
+            Self::SimilarQuestion(question) => {
+                tracing::info!("similar question exists: {}", question.id);
+                (StatusCode::CONFLICT, Json(SimilarQuestion { question })).into_response()
+            }
+            Self::QuestionFiltered => {
+                tracing::info!("question rejected by content filter");
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
 
             Self::Payment(e) => {
                 tracing::error!("payment error: {e}");
@@ -127,18 +207,55 @@ impl IntoResponse for InternalError {
                 tracing::warn!("add question validation: {:?}", e);
                 (StatusCode::BAD_REQUEST, "").into_response()
             }
+            Self::ParticipantNameValidation(e) => {
+                tracing::warn!("participant name validation: {:?}", e);
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+            Self::PollValidation(e) => {
+                tracing::warn!("poll validation: {:?}", e);
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+            Self::InvalidPollVote(id) => {
+                tracing::warn!("invalid vote on poll: {id}");
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+            Self::AlreadyVoted(id) => {
+                tracing::info!("already voted on poll: {id}");
+                (StatusCode::CONFLICT, "").into_response()
+            }
             Self::PasswordValidation(e) => {
                 tracing::warn!("password validation: {:?}", e);
                 (StatusCode::BAD_REQUEST, "").into_response()
             }
+            Self::PasswordLockout(remaining) => {
+                tracing::warn!("password lockout: {:?}", remaining);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, remaining.as_secs().max(1).to_string())],
+                    "",
+                )
+                    .into_response()
+            }
             Self::TagValidation(e) => {
                 tracing::warn!("tag validation: {:?}", e);
                 (StatusCode::BAD_REQUEST, "").into_response()
             }
+            Self::SessionValidation(e) => {
+                tracing::warn!("session validation: {:?}", e);
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
             Self::ContextValidation(e) => {
                 tracing::warn!("context validation: {:?}", e);
                 (StatusCode::BAD_REQUEST, "").into_response()
             }
+            Self::ContentFilterValidation(e) => {
+                tracing::warn!("content filter validation: {:?}", e);
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
+            Self::QuestionLimitsValidation(e) => {
+                tracing::warn!("question limits validation: {:?}", e);
+                (StatusCode::BAD_REQUEST, "").into_response()
+            }
 
             Self::EventsDB(e) if matches!(e, eventsdb::Error::Concurrency) => {
                 tracing::info!("concurrency collision: {e}");
@@ -161,7 +278,6 @@ impl IntoResponse for InternalError {
             Self::Uri(e) => convert_error(e),
             Self::DeadPoolCreatePool(e) => convert_error(e),
             Self::DeadPoolRedis(e) => convert_error(e),
-            Self::Tracking(e) => convert_error(e),
         }
     }
 }
@@ -3,23 +3,36 @@
 use async_trait::async_trait;
 use aws_sdk_dynamodb::{
     error::SdkError,
-    operation::put_item::PutItemError,
+    operation::{put_item::PutItemError, update_item::UpdateItemError},
     types::{
         AttributeDefinition, AttributeValue, KeySchemaElement, KeyType, ProvisionedThroughput,
-        ScalarAttributeType,
+        ReturnValue, ScalarAttributeType,
     },
 };
+use shared::WebhookDelivery;
+use std::collections::HashMap;
 use tracing::instrument;
 
-use crate::eventsdb::event_key;
+use crate::{
+    eventsdb::{
+        WEBHOOK_LOG_KEY, event_key, organizer_key, poll_vote_key, subscription_key, template_key,
+        voucher_key, webhook_key,
+    },
+    utils::timestamp_now,
+};
 
 use super::{
-    EventEntry, EventsDB,
+    EventEntry, EventsDB, StoredOrganizer, StoredSubscription, StoredTemplate, StoredVoucher,
+    StoredWebhookLog,
     error::{Error, Result},
     types::{ApiEventInfo, AttributeMap},
 };
 
 const DB_TABLE_NAME: &str = "liveask";
+/// how long processed webhook ids are kept to detect retried deliveries
+const WEBHOOK_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+/// how long poll votes are remembered, polls are not voted on for longer than an event runs
+const POLL_VOTE_TTL_SECS: i64 = 365 * 24 * 60 * 60;
 
 #[derive(Clone)]
 pub struct DynamoEventsDB {
@@ -105,9 +118,430 @@ impl EventsDB for DynamoEventsDB {
 
         Ok(())
     }
+
+    #[instrument(skip(self), err)]
+    async fn get_template(&self, key: &str) -> Result<StoredTemplate> {
+        let res = self
+            .db
+            .get_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(template_key(key)))
+            .send()
+            .await?;
+
+        let item = res.item().ok_or(Error::ItemNotFound)?;
+
+        Ok(serde_dynamo::from_item(item.clone())?)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_template(&self, template: StoredTemplate) -> Result<()> {
+        let key = template_key(&template.id);
+
+        let mut attributes: AttributeMap = serde_dynamo::to_item(template)?;
+        attributes.insert("key".into(), AttributeValue::S(key));
+
+        self.db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(attributes))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_organizer(&self, key: &str) -> Result<StoredOrganizer> {
+        let res = self
+            .db
+            .get_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(organizer_key(key)))
+            .send()
+            .await?;
+
+        let item = res.item().ok_or(Error::ItemNotFound)?;
+
+        Ok(serde_dynamo::from_item(item.clone())?)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_organizer(&self, organizer: StoredOrganizer) -> Result<()> {
+        let key = organizer_key(&organizer.id);
+        let version = organizer.version;
+
+        let mut attributes: AttributeMap = serde_dynamo::to_item(organizer)?;
+        attributes.insert("key".into(), AttributeValue::S(key));
+
+        let request = self
+            .db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(attributes))
+            .expression_attribute_names("#v", "version");
+
+        // the first versioned write also replaces an organizer stored before it had a version
+        let request = if version > 1 {
+            request
+                .condition_expression("#v = :ver")
+                .expression_attribute_values(
+                    ":ver",
+                    AttributeValue::N(version.saturating_sub(1).to_string()),
+                )
+        } else {
+            request.condition_expression("attribute_not_exists(#v)")
+        };
+
+        //Note: filter out conditional error
+        if let Err(e) = request.send().await {
+            if matches!(&e,SdkError::<PutItemError>::ServiceError (err)
+            if matches!(
+                err.err(),PutItemError::ConditionalCheckFailedException(_)
+
+            )) {
+                return Err(Error::Concurrency);
+            }
+
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_subscription(&self, key: &str) -> Result<StoredSubscription> {
+        let res = self
+            .db
+            .get_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(subscription_key(key)))
+            .send()
+            .await?;
+
+        let item = res.item().ok_or(Error::ItemNotFound)?;
+
+        Ok(serde_dynamo::from_item(item.clone())?)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn add_subscription_event(
+        &self,
+        subscription: &StoredSubscription,
+        event: &str,
+    ) -> Result<()> {
+        self.db
+            .update_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(subscription_key(&subscription.id)))
+            .update_expression(
+                "SET #e = list_append(if_not_exists(#e, :empty), :event), \
+                 #id = if_not_exists(#id, :id), #c = if_not_exists(#c, :customer), \
+                 #s = if_not_exists(#s, :status), #t = if_not_exists(#t, :time)",
+            )
+            .set_expression_attribute_names(Some(Self::subscription_names()))
+            .expression_attribute_names("#e", "events")
+            .set_expression_attribute_values(Some(Self::subscription_values(subscription)))
+            .expression_attribute_values(":empty", AttributeValue::L(Vec::new()))
+            .expression_attribute_values(
+                ":event",
+                AttributeValue::L(vec![AttributeValue::S(event.to_string())]),
+            )
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn update_subscription(
+        &self,
+        subscription: &StoredSubscription,
+    ) -> Result<Option<StoredSubscription>> {
+        let res = self
+            .db
+            .update_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(subscription_key(&subscription.id)))
+            .update_expression("SET #id = :id, #c = :customer, #s = :status, #t = :time")
+            .condition_expression("attribute_not_exists(#t) OR #t <= :time")
+            .set_expression_attribute_names(Some(Self::subscription_names()))
+            .set_expression_attribute_values(Some(Self::subscription_values(subscription)))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+
+        let res = match res {
+            Ok(res) => res,
+            //Note: filter out conditional error
+            Err(e) => {
+                if matches!(&e,SdkError::<UpdateItemError>::ServiceError (err)
+                if matches!(
+                    err.err(),UpdateItemError::ConditionalCheckFailedException(_)
+
+                )) {
+                    return Ok(None);
+                }
+
+                return Err(e.into());
+            }
+        };
+
+        let item = res.attributes().ok_or(Error::ItemNotFound)?;
+
+        Ok(Some(serde_dynamo::from_item(item.clone())?))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn claim_webhook(&self, delivery: WebhookDelivery, stale_before: i64) -> Result<bool> {
+        let res = self
+            .db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(Self::webhook_attributes(delivery)?))
+            .condition_expression(
+                "attribute_not_exists(#k) OR attribute_exists(#o.#f) \
+                 OR (#o = :processing AND #t < :stale)",
+            )
+            .expression_attribute_names("#k", "key")
+            .expression_attribute_names("#o", "outcome")
+            .expression_attribute_names("#f", "Failed")
+            .expression_attribute_names("#t", "timeUnix")
+            .expression_attribute_values(":processing", AttributeValue::S("Processing".into()))
+            .expression_attribute_values(":stale", AttributeValue::N(stale_before.to_string()))
+            .send()
+            .await;
+
+        //Note: filter out conditional error
+        if let Err(e) = res {
+            if matches!(&e,SdkError::<PutItemError>::ServiceError (err)
+            if matches!(
+                err.err(),PutItemError::ConditionalCheckFailedException(_)
+
+            )) {
+                return Ok(false);
+            }
+
+            return Err(e.into());
+        }
+
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_webhook(&self, delivery: WebhookDelivery) -> Result<()> {
+        self.db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(Self::webhook_attributes(delivery)?))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_webhook_log(&self) -> Result<StoredWebhookLog> {
+        let res = self
+            .db
+            .get_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(WEBHOOK_LOG_KEY.to_string()))
+            .send()
+            .await?;
+
+        let item = res.item().ok_or(Error::ItemNotFound)?;
+
+        Ok(serde_dynamo::from_item(item.clone())?)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_webhook_log(&self, log: StoredWebhookLog) -> Result<()> {
+        let version = log.version;
+
+        let mut attributes: AttributeMap = serde_dynamo::to_item(log)?;
+        attributes.insert("key".into(), AttributeValue::S(WEBHOOK_LOG_KEY.to_string()));
+
+        let request = self
+            .db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(attributes))
+            .expression_attribute_names("#v", "version");
+
+        // the first versioned write also replaces a log stored before it had a version
+        let request = if version > 1 {
+            request
+                .condition_expression("#v = :ver")
+                .expression_attribute_values(
+                    ":ver",
+                    AttributeValue::N(version.saturating_sub(1).to_string()),
+                )
+        } else {
+            request.condition_expression("attribute_not_exists(#v)")
+        };
+
+        //Note: filter out conditional error
+        if let Err(e) = request.send().await {
+            if matches!(&e,SdkError::<PutItemError>::ServiceError (err)
+            if matches!(
+                err.err(),PutItemError::ConditionalCheckFailedException(_)
+
+            )) {
+                return Err(Error::Concurrency);
+            }
+
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_voucher(&self, code: &str) -> Result<StoredVoucher> {
+        let res = self
+            .db
+            .get_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(voucher_key(code)))
+            .send()
+            .await?;
+
+        let item = res.item().ok_or(Error::ItemNotFound)?;
+
+        Ok(serde_dynamo::from_item(item.clone())?)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_voucher(&self, voucher: StoredVoucher) -> Result<()> {
+        let key = voucher_key(&voucher.code);
+        let version = voucher.version;
+
+        let mut attributes: AttributeMap = serde_dynamo::to_item(voucher)?;
+        attributes.insert("key".into(), AttributeValue::S(key));
+
+        let request = self
+            .db
+            .put_item()
+            .table_name(&self.table)
+            .set_item(Some(attributes))
+            .expression_attribute_names("#v", "version");
+
+        let request = if version > 0 {
+            request
+                .condition_expression("#v = :ver")
+                .expression_attribute_values(
+                    ":ver",
+                    AttributeValue::N(version.saturating_sub(1).to_string()),
+                )
+        } else {
+            request.condition_expression("attribute_not_exists(#v)")
+        };
+
+        //Note: filter out conditional error
+        if let Err(e) = request.send().await {
+            if matches!(&e,SdkError::<PutItemError>::ServiceError (err)
+            if matches!(
+                err.err(),PutItemError::ConditionalCheckFailedException(_)
+
+            )) {
+                return Err(Error::Concurrency);
+            }
+
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn claim_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<bool> {
+        let ttl = timestamp_now() + POLL_VOTE_TTL_SECS;
+
+        let res = self
+            .db
+            .put_item()
+            .table_name(&self.table)
+            .item("key", AttributeValue::S(poll_vote_key(event, poll, voter)))
+            .item("ttl", AttributeValue::N(ttl.to_string()))
+            .condition_expression("attribute_not_exists(#k)")
+            .expression_attribute_names("#k", "key")
+            .send()
+            .await;
+
+        //Note: filter out conditional error
+        if let Err(e) = res {
+            if matches!(&e,SdkError::<PutItemError>::ServiceError (err)
+            if matches!(
+                err.err(),PutItemError::ConditionalCheckFailedException(_)
+
+            )) {
+                return Ok(false);
+            }
+
+            return Err(e.into());
+        }
+
+        Ok(true)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn release_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<()> {
+        self.db
+            .delete_item()
+            .table_name(&self.table)
+            .key("key", AttributeValue::S(poll_vote_key(event, poll, voter)))
+            .send()
+            .await?;
+
+        Ok(())
+    }
 }
 
 impl DynamoEventsDB {
+    /// the expression names shared by the subscription updates, dynamo refuses unused ones
+    fn subscription_names() -> HashMap<String, String> {
+        [
+            ("#id", "id"),
+            ("#c", "customer"),
+            ("#s", "status"),
+            ("#t", "updateTimeUnix"),
+        ]
+        .into_iter()
+        .map(|(name, attribute)| (name.to_string(), attribute.to_string()))
+        .collect()
+    }
+
+    fn subscription_values(subscription: &StoredSubscription) -> AttributeMap {
+        AttributeMap::from([
+            (":id".into(), AttributeValue::S(subscription.id.clone())),
+            (
+                ":customer".into(),
+                AttributeValue::S(subscription.customer.clone()),
+            ),
+            (
+                ":status".into(),
+                AttributeValue::S(subscription.status.clone()),
+            ),
+            (
+                ":time".into(),
+                AttributeValue::N(subscription.update_time_unix.to_string()),
+            ),
+        ])
+    }
+
+    fn webhook_attributes(delivery: WebhookDelivery) -> Result<AttributeMap> {
+        let key = webhook_key(&delivery.id);
+        // stripe gives up retrying after three days, the table ttl cleans up after that
+        let ttl = delivery.time_unix + WEBHOOK_TTL_SECS;
+
+        let mut attributes: AttributeMap = serde_dynamo::to_item(delivery)?;
+        attributes.insert("key".into(), AttributeValue::S(key));
+        attributes.insert("ttl".into(), AttributeValue::N(ttl.to_string()));
+
+        Ok(attributes)
+    }
+
     pub async fn new(db: aws_sdk_dynamodb::Client, check_table_exists: bool) -> Result<Self> {
         if check_table_exists {
             let resp = db.list_tables().send().await?;
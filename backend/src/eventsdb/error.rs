@@ -3,8 +3,8 @@ use std::num::ParseIntError;
 use aws_sdk_dynamodb::{
     error::SdkError,
     operation::{
-        create_table::CreateTableError, get_item::GetItemError, list_tables::ListTablesError,
-        put_item::PutItemError,
+        create_table::CreateTableError, delete_item::DeleteItemError, get_item::GetItemError,
+        list_tables::ListTablesError, put_item::PutItemError, update_item::UpdateItemError,
     },
 };
 use thiserror::Error;
@@ -41,6 +41,12 @@ pub enum Error {
     #[error("Dynamo GetItemError: {0}")]
     DynamoGetItem(Box<SdkError<GetItemError>>),
 
+    #[error("Dynamo DeleteItemError: {0}")]
+    DynamoDeleteItem(Box<SdkError<DeleteItemError>>),
+
+    #[error("Dynamo UpdateItemError: {0}")]
+    DynamoUpdateItem(Box<SdkError<UpdateItemError>>),
+
     #[error("Dynamo BuildError: {0}")]
     DynamoBuild(#[from] aws_sdk_dynamodb::error::BuildError),
 
@@ -73,3 +79,15 @@ impl From<SdkError<GetItemError>> for Error {
         Self::DynamoGetItem(Box::new(e))
     }
 }
+
+impl From<SdkError<DeleteItemError>> for Error {
+    fn from(e: SdkError<DeleteItemError>) -> Self {
+        Self::DynamoDeleteItem(Box::new(e))
+    }
+}
+
+impl From<SdkError<UpdateItemError>> for Error {
+    fn from(e: SdkError<UpdateItemError>) -> Self {
+        Self::DynamoUpdateItem(Box::new(e))
+    }
+}
@@ -1,18 +1,31 @@
 #![allow(renamed_and_removed_lints, clippy::blocks_in_if_conditions)]
 
 use super::{
-    EventEntry, EventsDB,
+    EventEntry, EventsDB, StoredOrganizer, StoredSubscription, StoredTemplate, StoredVoucher,
+    StoredWebhookLog,
     error::{Error, Result},
-    event_key,
+    event_key, organizer_key, poll_vote_key, subscription_key, template_key, voucher_key,
+    webhook_key,
 };
 use async_trait::async_trait;
-use std::{collections::HashMap, sync::Arc};
+use shared::{WebhookDelivery, WebhookOutcome};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 use tracing::instrument;
 
 #[derive(Default)]
 pub struct InMemoryEventsDB {
     pub db: Arc<Mutex<HashMap<String, EventEntry>>>,
+    pub templates: Arc<Mutex<HashMap<String, StoredTemplate>>>,
+    pub organizers: Arc<Mutex<HashMap<String, StoredOrganizer>>>,
+    pub subscriptions: Arc<Mutex<HashMap<String, StoredSubscription>>>,
+    pub webhooks: Arc<Mutex<HashMap<String, WebhookDelivery>>>,
+    pub webhook_log: Arc<Mutex<Option<StoredWebhookLog>>>,
+    pub vouchers: Arc<Mutex<HashMap<String, StoredVoucher>>>,
+    pub poll_votes: Arc<Mutex<HashSet<String>>>,
 }
 
 #[async_trait]
@@ -46,4 +59,212 @@ impl EventsDB for InMemoryEventsDB {
 
         Ok(())
     }
+
+    #[instrument(skip(self), err)]
+    async fn get_template(&self, key: &str) -> Result<StoredTemplate> {
+        let templates = self.templates.lock().await;
+
+        templates
+            .get(&template_key(key))
+            .cloned()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_template(&self, template: StoredTemplate) -> Result<()> {
+        self.templates
+            .lock()
+            .await
+            .insert(template_key(&template.id), template);
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_organizer(&self, key: &str) -> Result<StoredOrganizer> {
+        let organizers = self.organizers.lock().await;
+
+        organizers
+            .get(&organizer_key(key))
+            .cloned()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_organizer(&self, organizer: StoredOrganizer) -> Result<()> {
+        let key = organizer_key(&organizer.id);
+
+        let mut organizers = self.organizers.lock().await;
+
+        let stored_version = organizers.get(&key).map_or(0, |stored| stored.version);
+
+        if organizer.version != stored_version + 1 {
+            return Err(Error::Concurrency);
+        }
+
+        organizers.insert(key, organizer);
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_subscription(&self, key: &str) -> Result<StoredSubscription> {
+        let subscriptions = self.subscriptions.lock().await;
+
+        subscriptions
+            .get(&subscription_key(key))
+            .cloned()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn add_subscription_event(
+        &self,
+        subscription: &StoredSubscription,
+        event: &str,
+    ) -> Result<()> {
+        self.subscriptions
+            .lock()
+            .await
+            .entry(subscription_key(&subscription.id))
+            .or_insert_with(|| StoredSubscription {
+                events: Vec::new(),
+                ..subscription.clone()
+            })
+            .events
+            .push(event.to_string());
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    #[allow(clippy::significant_drop_tightening)]
+    async fn update_subscription(
+        &self,
+        subscription: &StoredSubscription,
+    ) -> Result<Option<StoredSubscription>> {
+        let mut subscriptions = self.subscriptions.lock().await;
+
+        let stored = subscriptions
+            .entry(subscription_key(&subscription.id))
+            .or_insert_with(|| StoredSubscription {
+                events: Vec::new(),
+                ..subscription.clone()
+            });
+
+        if stored.update_time_unix > subscription.update_time_unix {
+            return Ok(None);
+        }
+
+        stored.customer.clone_from(&subscription.customer);
+        stored.status.clone_from(&subscription.status);
+        stored.update_time_unix = subscription.update_time_unix;
+
+        Ok(Some(stored.clone()))
+    }
+
+    #[instrument(skip(self), err)]
+    #[allow(clippy::significant_drop_tightening)]
+    async fn claim_webhook(&self, delivery: WebhookDelivery, stale_before: i64) -> Result<bool> {
+        let key = webhook_key(&delivery.id);
+
+        let mut webhooks = self.webhooks.lock().await;
+
+        let claimable = webhooks
+            .get(&key)
+            .is_none_or(|stored| match stored.outcome {
+                WebhookOutcome::Failed(_) => true,
+                WebhookOutcome::Processing => stored.time_unix < stale_before,
+                WebhookOutcome::Processed | WebhookOutcome::Ignored => false,
+            });
+
+        if claimable {
+            webhooks.insert(key, delivery);
+        }
+
+        Ok(claimable)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn put_webhook(&self, delivery: WebhookDelivery) -> Result<()> {
+        self.webhooks
+            .lock()
+            .await
+            .insert(webhook_key(&delivery.id), delivery);
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_webhook_log(&self) -> Result<StoredWebhookLog> {
+        self.webhook_log
+            .lock()
+            .await
+            .clone()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    #[instrument(skip(self), err)]
+    #[allow(clippy::significant_drop_tightening)]
+    async fn put_webhook_log(&self, log: StoredWebhookLog) -> Result<()> {
+        let mut stored = self.webhook_log.lock().await;
+
+        let stored_version = stored.as_ref().map_or(0, |stored| stored.version);
+
+        if log.version != stored_version + 1 {
+            return Err(Error::Concurrency);
+        }
+
+        *stored = Some(log);
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn get_voucher(&self, code: &str) -> Result<StoredVoucher> {
+        let vouchers = self.vouchers.lock().await;
+
+        vouchers
+            .get(&voucher_key(code))
+            .cloned()
+            .ok_or(Error::ItemNotFound)
+    }
+
+    #[instrument(skip(self), err)]
+    #[allow(clippy::significant_drop_tightening)]
+    async fn put_voucher(&self, voucher: StoredVoucher) -> Result<()> {
+        let key = voucher_key(&voucher.code);
+
+        let mut vouchers = self.vouchers.lock().await;
+
+        if let Some(stored) = vouchers.get_mut(&key) {
+            if voucher.version <= stored.version {
+                return Err(Error::Concurrency);
+            }
+            *stored = voucher;
+        } else {
+            vouchers.insert(key, voucher);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn claim_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<bool> {
+        Ok(self
+            .poll_votes
+            .lock()
+            .await
+            .insert(poll_vote_key(event, poll, voter)))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn release_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<()> {
+        self.poll_votes
+            .lock()
+            .await
+            .remove(&poll_vote_key(event, poll, voter));
+
+        Ok(())
+    }
 }
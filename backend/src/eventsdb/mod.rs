@@ -10,15 +10,79 @@ pub use error::{Error, Result};
 pub use in_memory::InMemoryEventsDB;
 
 use async_trait::async_trait;
+use shared::WebhookDelivery;
 
-pub use self::types::{ApiEventInfo, EventEntry, PremiumOrder};
+pub use self::types::{
+    ApiEventInfo, EventEntry, PremiumFeature, PremiumIssue, PremiumIssueReason, PremiumOrder,
+    StoredOrganizer, StoredPassword, StoredPoll, StoredSubscription, StoredTemplate, StoredVoucher,
+    StoredWebhookLog,
+};
 
 pub fn event_key(key: &str) -> String {
     format!("events/ev-{key}.json")
 }
 
+pub fn template_key(key: &str) -> String {
+    format!("templates/tpl-{key}.json")
+}
+
+pub fn organizer_key(key: &str) -> String {
+    format!("organizers/org-{key}.json")
+}
+
+pub fn subscription_key(key: &str) -> String {
+    format!("subscriptions/sub-{key}.json")
+}
+
+pub fn webhook_key(key: &str) -> String {
+    format!("webhooks/evt-{key}.json")
+}
+
+pub const WEBHOOK_LOG_KEY: &str = "webhooks/log.json";
+
+pub fn voucher_key(key: &str) -> String {
+    format!("vouchers/vou-{key}.json")
+}
+
+pub fn poll_vote_key(event: &str, poll: i64, voter: &str) -> String {
+    format!("polls/vote-{event}-{poll}-{voter}.json")
+}
+
 #[async_trait]
 pub trait EventsDB: Send + Sync {
     async fn get(&self, key: &str) -> Result<EventEntry>;
     async fn put(&self, event: EventEntry) -> Result<()>;
+    async fn get_template(&self, key: &str) -> Result<StoredTemplate>;
+    async fn put_template(&self, template: StoredTemplate) -> Result<()>;
+    async fn get_organizer(&self, key: &str) -> Result<StoredOrganizer>;
+    /// fails with [`Error::Concurrency`] unless `organizer.version` is one past the stored one
+    async fn put_organizer(&self, organizer: StoredOrganizer) -> Result<()>;
+    async fn get_subscription(&self, key: &str) -> Result<StoredSubscription>;
+    /// Append `event` to the events of `subscription`, storing the rest of it only if it is new.
+    async fn add_subscription_event(
+        &self,
+        subscription: &StoredSubscription,
+        event: &str,
+    ) -> Result<()>;
+    /// Store everything but the events of `subscription`, the stored one after, `None` when the
+    /// stored one is more recent.
+    async fn update_subscription(
+        &self,
+        subscription: &StoredSubscription,
+    ) -> Result<Option<StoredSubscription>>;
+    /// Store `delivery` unless it is known and did not fail, or is processing since
+    /// `stale_before` or later. `false` when someone else has it.
+    async fn claim_webhook(&self, delivery: WebhookDelivery, stale_before: i64) -> Result<bool>;
+    async fn put_webhook(&self, delivery: WebhookDelivery) -> Result<()>;
+    async fn get_webhook_log(&self) -> Result<StoredWebhookLog>;
+    /// fails with [`Error::Concurrency`] unless `log.version` is one past the stored one
+    async fn put_webhook_log(&self, log: StoredWebhookLog) -> Result<()>;
+    async fn get_voucher(&self, code: &str) -> Result<StoredVoucher>;
+    /// fails with [`Error::Concurrency`] unless `voucher.version` is one past the stored one,
+    /// or it is a new voucher with version 0
+    async fn put_voucher(&self, voucher: StoredVoucher) -> Result<()>;
+    /// Record that `voter` voted in `poll` of `event`, `false` if they did before.
+    async fn claim_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<bool>;
+    /// Undo [`EventsDB::claim_poll_vote`] when the vote could not be counted.
+    async fn release_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<()>;
 }
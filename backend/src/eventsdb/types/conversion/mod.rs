@@ -10,11 +10,13 @@ use self::{
     tokens::{attributes_to_tokens, tokens_to_attributes},
 };
 
-use super::{ApiEventInfo, AttributeMap, PremiumOrder};
+use super::{ApiEventInfo, AttributeMap, PremiumIssue, PremiumOrder, StoredPassword};
 use crate::eventsdb::Error;
 use aws_sdk_dynamodb::types::AttributeValue;
-use serde_dynamo::{from_item, to_item};
-use shared::{EventPassword, EventState, EventTags, States};
+use serde_dynamo::{from_attribute_value, from_item, to_attribute_value, to_item};
+use shared::{
+    ContentFilter, EventSessions, EventState, EventTags, ParticipantNames, QuestionLimits, States,
+};
 
 const ATTR_EVENT_INFO_LAST_EDIT: &str = "last_edit";
 const ATTR_EVENT_INFO_DELETE_TIME: &str = "delete_time";
@@ -26,9 +28,20 @@ const ATTR_EVENT_INFO_TOKENS: &str = "tokens";
 const ATTR_EVENT_INFO_ITEMS: &str = "items";
 const ATTR_EVENT_INFO_DATA: &str = "data";
 const ATTR_EVENT_INFO_PREMIUM: &str = "premium_id";
+const ATTR_EVENT_INFO_PREMIUM_TIER: &str = "premium_tier";
+const ATTR_EVENT_INFO_PREMIUM_UNTIL: &str = "premium_until";
 const ATTR_EVENT_INFO_PASSWORD: &str = "password";
 const ATTR_EVENT_INFO_CONTEXT: &str = "ctx";
 const ATTR_EVENT_INFO_TAGS: &str = "tags";
+const ATTR_EVENT_INFO_CONTENT_FILTER: &str = "filter";
+const ATTR_EVENT_INFO_QUESTION_LIMITS: &str = "limits";
+const ATTR_EVENT_INFO_PARTICIPANT_NAMES: &str = "names";
+const ATTR_EVENT_INFO_POLLS: &str = "polls";
+const ATTR_EVENT_INFO_SESSIONS: &str = "sessions";
+const ATTR_EVENT_INFO_MOD_EMAIL: &str = "mod_mail";
+const ATTR_EVENT_INFO_MOD_EMAIL_SALT: &str = "mod_mail_salt";
+const ATTR_EVENT_INFO_PREMIUM_ISSUE: &str = "premium_issue";
+const ATTR_EVENT_INFO_NO_THIRD_PARTIES: &str = "no_third_parties";
 
 pub fn event_to_attributes(value: ApiEventInfo) -> AttributeMap {
     let vec = vec![
@@ -56,6 +69,10 @@ pub fn event_to_attributes(value: ApiEventInfo) -> AttributeMap {
             ATTR_EVENT_INFO_DO_SCREENING.into(),
             AttributeValue::Bool(value.do_screening),
         ),
+        (
+            ATTR_EVENT_INFO_NO_THIRD_PARTIES.into(),
+            AttributeValue::Bool(value.no_third_parties),
+        ),
         (
             ATTR_EVENT_INFO_CREATE_TIME.into(),
             AttributeValue::N(value.create_time_unix.to_string()),
@@ -76,6 +93,26 @@ pub fn event_to_attributes(value: ApiEventInfo) -> AttributeMap {
             ATTR_EVENT_INFO_TAGS.into(),
             AttributeValue::M(to_item(value.tags).unwrap_or_default()),
         ),
+        (
+            ATTR_EVENT_INFO_CONTENT_FILTER.into(),
+            AttributeValue::M(to_item(value.content_filter).unwrap_or_default()),
+        ),
+        (
+            ATTR_EVENT_INFO_QUESTION_LIMITS.into(),
+            AttributeValue::M(to_item(value.question_limits).unwrap_or_default()),
+        ),
+        (
+            ATTR_EVENT_INFO_PARTICIPANT_NAMES.into(),
+            AttributeValue::N((value.participant_names as u8).to_string()),
+        ),
+        (
+            ATTR_EVENT_INFO_POLLS.into(),
+            to_attribute_value(value.polls).unwrap_or_else(|_| AttributeValue::L(Vec::new())),
+        ),
+        (
+            ATTR_EVENT_INFO_SESSIONS.into(),
+            AttributeValue::M(to_item(value.sessions).unwrap_or_default()),
+        ),
     ];
     let mut map: AttributeMap = vec.into_iter().collect();
 
@@ -88,9 +125,45 @@ pub fn event_to_attributes(value: ApiEventInfo) -> AttributeMap {
                     .unwrap_or_default(),
             ),
         );
+
+        if let Ok(tier) = to_attribute_value(value.premium_tier)
+            .map_err(|e| tracing::error!("[conv] error with premium tier: {e:?}"))
+        {
+            map.insert(ATTR_EVENT_INFO_PREMIUM_TIER.into(), tier);
+        }
+
+        if let Some(until) = value.premium_until_unix {
+            map.insert(
+                ATTR_EVENT_INFO_PREMIUM_UNTIL.into(),
+                AttributeValue::N(until.to_string()),
+            );
+        }
     }
 
-    if let EventPassword::Enabled(password) = value.password {
+    if let Some(issue) = value.premium_issue {
+        map.insert(
+            ATTR_EVENT_INFO_PREMIUM_ISSUE.into(),
+            AttributeValue::M(
+                to_item(issue)
+                    .map_err(|e| tracing::error!("[conv] error with premium issue: {e:?}"))
+                    .unwrap_or_default(),
+            ),
+        );
+    }
+
+    if let Some(hash) = value.mod_email_hash {
+        map.insert(ATTR_EVENT_INFO_MOD_EMAIL.into(), AttributeValue::S(hash));
+    }
+
+    if let Some(salt) = value.mod_email_salt {
+        map.insert(
+            ATTR_EVENT_INFO_MOD_EMAIL_SALT.into(),
+            AttributeValue::S(salt),
+        );
+    }
+
+    // a legacy plaintext is written as is, `App::load_entry` hashes it off the executor
+    if let StoredPassword::Hashed(password) | StoredPassword::Legacy(password) = value.password {
         map.insert(ATTR_EVENT_INFO_PASSWORD.into(), AttributeValue::S(password));
     }
 
@@ -151,15 +224,39 @@ pub fn attributes_to_event(value: &AttributeMap) -> Result<ApiEventInfo, super::
         .copied()
         .unwrap_or_default();
 
+    let no_third_parties = value
+        .get(ATTR_EVENT_INFO_NO_THIRD_PARTIES)
+        .and_then(|val| val.as_bool().ok())
+        .copied()
+        .unwrap_or_default();
+
     let premium_receipt: Option<PremiumOrder> = value
         .get(ATTR_EVENT_INFO_PREMIUM)
         .and_then(|value| value.as_m().ok().cloned())
         .and_then(|v| from_item(v).ok());
 
+    let premium_tier = value
+        .get(ATTR_EVENT_INFO_PREMIUM_TIER)
+        .cloned()
+        .and_then(|value| from_attribute_value(value).ok())
+        .unwrap_or_default();
+
+    let premium_until_unix = value
+        .get(ATTR_EVENT_INFO_PREMIUM_UNTIL)
+        .map(|value| {
+            value
+                .as_n()
+                .map_err(|_| Error::MalformedObject(ATTR_EVENT_INFO_PREMIUM_UNTIL.into()))?
+                .parse::<i64>()
+                .map_err(Error::from)
+        })
+        .transpose()?;
+
     let password = value
         .get(ATTR_EVENT_INFO_PASSWORD)
         .and_then(|value| value.as_s().ok().cloned())
-        .into();
+        .map(StoredPassword::from_stored)
+        .unwrap_or_default();
 
     let state = EventState::from_value(
         value[ATTR_EVENT_INFO_STATE]
@@ -182,6 +279,66 @@ pub fn attributes_to_event(value: &AttributeMap) -> Result<ApiEventInfo, super::
         EventTags::default()
     };
 
+    let content_filter = if let Some(attr) = value.get(ATTR_EVENT_INFO_CONTENT_FILTER) {
+        let map = attr
+            .as_m()
+            .map_err(|_| Error::MalformedObject(ATTR_EVENT_INFO_CONTENT_FILTER.into()))?
+            .clone();
+
+        from_item(map).unwrap_or_default()
+    } else {
+        ContentFilter::default()
+    };
+
+    let question_limits = if let Some(attr) = value.get(ATTR_EVENT_INFO_QUESTION_LIMITS) {
+        let map = attr
+            .as_m()
+            .map_err(|_| Error::MalformedObject(ATTR_EVENT_INFO_QUESTION_LIMITS.into()))?
+            .clone();
+
+        from_item(map).unwrap_or_default()
+    } else {
+        QuestionLimits::default()
+    };
+
+    let participant_names = value
+        .get(ATTR_EVENT_INFO_PARTICIPANT_NAMES)
+        .and_then(|value| value.as_n().ok())
+        .and_then(|value| value.parse::<ParticipantNames>().ok())
+        .unwrap_or_default();
+
+    let polls = value
+        .get(ATTR_EVENT_INFO_POLLS)
+        .cloned()
+        .map(from_attribute_value)
+        .transpose()
+        .map_err(|_| Error::MalformedObject(ATTR_EVENT_INFO_POLLS.into()))?
+        .unwrap_or_default();
+
+    let sessions = if let Some(attr) = value.get(ATTR_EVENT_INFO_SESSIONS) {
+        let map = attr
+            .as_m()
+            .map_err(|_| Error::MalformedObject(ATTR_EVENT_INFO_SESSIONS.into()))?
+            .clone();
+
+        from_item(map).unwrap_or_default()
+    } else {
+        EventSessions::default()
+    };
+
+    let mod_email_hash = value
+        .get(ATTR_EVENT_INFO_MOD_EMAIL)
+        .and_then(|value| value.as_s().ok().cloned());
+
+    let mod_email_salt = value
+        .get(ATTR_EVENT_INFO_MOD_EMAIL_SALT)
+        .and_then(|value| value.as_s().ok().cloned());
+
+    let premium_issue: Option<PremiumIssue> = value
+        .get(ATTR_EVENT_INFO_PREMIUM_ISSUE)
+        .and_then(|value| value.as_m().ok().cloned())
+        .and_then(|v| from_item(v).ok());
+
     Ok(ApiEventInfo {
         tokens,
         data,
@@ -194,8 +351,19 @@ pub fn attributes_to_event(value: &AttributeMap) -> Result<ApiEventInfo, super::
         state,
         password,
         premium_id: premium_receipt,
+        premium_tier,
+        premium_until_unix,
         context,
         tags,
+        content_filter,
+        question_limits,
+        participant_names,
+        polls,
+        sessions,
+        mod_email_hash,
+        mod_email_salt,
+        premium_issue,
+        no_third_parties,
     })
 }
 
@@ -206,11 +374,12 @@ mod test {
     use pretty_assertions::assert_eq;
     use serde_dynamo::{aws_sdk_dynamodb_1::to_item, from_item};
     use shared::{
-        ContextItem, EventData, EventState, EventTags, EventTokens, QuestionItem, States, Tag,
-        TagId,
+        ContentFilterAction, ContextItem, EventData, EventState, EventTags, EventTokens, Poll,
+        PollKind, PollOption, PremiumTier, QuestionItem, Session, SessionData, SessionId, States,
+        Tag, TagId,
     };
 
-    use crate::eventsdb::{ApiEventInfo, types::AttributeMap};
+    use crate::eventsdb::{ApiEventInfo, PremiumIssueReason, StoredPoll, types::AttributeMap};
 
     #[test]
     #[tracing_test::traced_test]
@@ -229,8 +398,13 @@ mod test {
             create_time_unix: 1,
             delete_time_unix: 0,
             deleted: false,
-            password: shared::EventPassword::Enabled(String::from("pwd")),
-            premium_id: Some(PremiumOrder::PaypalOrderId(String::from("order"))),
+            password: StoredPassword::hash("pwd").unwrap(),
+            premium_id: Some(PremiumOrder::Provider {
+                provider: String::from("paypal"),
+                order: String::from("order"),
+            }),
+            premium_tier: PremiumTier::Organization,
+            premium_until_unix: Some(5),
             last_edit_unix: 2,
             questions: vec![QuestionItem {
                 id: 0,
@@ -241,6 +415,8 @@ mod test {
                 screening: true,
                 create_time_unix: 3,
                 tag: Some(TagId(0)),
+                name: Some(String::from("Jo")),
+                session: Some(SessionId(0)),
             }],
             do_screening: false,
             state: EventState {
@@ -256,7 +432,57 @@ mod test {
                     id: TagId(0),
                 }],
                 current_tag: Some(TagId(0)),
+                next_id: 1,
+            },
+            content_filter: ContentFilter {
+                action: ContentFilterAction::Screen,
+                blocklist: vec![String::from("spam")],
+            },
+            question_limits: QuestionLimits {
+                min_len: 5,
+                max_questions: 500,
+                ..QuestionLimits::default()
+            },
+            participant_names: ParticipantNames::Optional,
+            polls: vec![StoredPoll {
+                poll: Poll {
+                    id: 0,
+                    question: String::from("lunch?"),
+                    kind: PollKind::MultipleChoice,
+                    options: vec![PollOption {
+                        text: String::from("pizza"),
+                        votes: 1,
+                    }],
+                    voters: 1,
+                    closed: false,
+                    hidden: false,
+                    create_time_unix: 4,
+                },
+            }],
+            sessions: EventSessions {
+                current: Some(SessionId(0)),
+                sessions: vec![Session {
+                    id: SessionId(0),
+                    data: SessionData {
+                        name: String::from("keynote"),
+                        start_unix: Some(5),
+                        end_unix: None,
+                    },
+                    state: EventState {
+                        state: States::VotingOnly,
+                    },
+                }],
+                next_id: 1,
             },
+            mod_email_hash: Some(String::from("hash")),
+            mod_email_salt: Some(String::from("source")),
+            premium_issue: Some(PremiumIssue {
+                reason: PremiumIssueReason::Disputed,
+                reference: String::from("dp_1"),
+                time_unix: 5,
+                revoked: None,
+            }),
+            no_third_parties: true,
         };
 
         let map: AttributeMap = event_to_attributes(entry.clone());
@@ -269,4 +495,20 @@ mod test {
 
         assert_eq!(map_deserialized, serde_map_deserialized);
     }
+
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_legacy_plaintext_password_roundtrip() {
+        let mut map = event_to_attributes(ApiEventInfo::default());
+        map.insert(
+            ATTR_EVENT_INFO_PASSWORD.into(),
+            AttributeValue::S(String::from("pwd")),
+        );
+
+        let legacy = attributes_to_event(&map).unwrap();
+        assert_eq!(legacy.password, StoredPassword::Legacy(String::from("pwd")));
+
+        let stored = attributes_to_event(&event_to_attributes(legacy.clone())).unwrap();
+        assert_eq!(stored.password, legacy.password);
+    }
 }
@@ -1,7 +1,7 @@
 use super::AttributeMap;
 use crate::eventsdb::Error;
 use aws_sdk_dynamodb::types::AttributeValue;
-use shared::{QuestionItem, TagId};
+use shared::{QuestionItem, SessionId, TagId};
 
 pub fn questions_to_attributes(value: Vec<QuestionItem>) -> Vec<AttributeValue> {
     value
@@ -33,6 +33,8 @@ const ATTR_QUESTION_ANSWERED: &str = "answered";
 const ATTR_QUESTION_SCREENING: &str = "screening";
 const ATTR_QUESTION_HIDDEN: &str = "hidden";
 const ATTR_QUESTION_TAG: &str = "tag";
+const ATTR_QUESTION_NAME: &str = "name";
+const ATTR_QUESTION_SESSION: &str = "session";
 
 fn question_to_attributes(value: QuestionItem) -> AttributeMap {
     let mut map = AttributeMap::new();
@@ -66,6 +68,15 @@ fn question_to_attributes(value: QuestionItem) -> AttributeMap {
             AttributeValue::N(tag.0.to_string()),
         );
     }
+    if let Some(name) = value.name {
+        map.insert(ATTR_QUESTION_NAME.into(), AttributeValue::S(name));
+    }
+    if let Some(session) = value.session {
+        map.insert(
+            ATTR_QUESTION_SESSION.into(),
+            AttributeValue::N(session.0.to_string()),
+        );
+    }
 
     map
 }
@@ -112,6 +123,17 @@ fn attributes_to_question(value: &AttributeMap) -> Result<QuestionItem, super::E
         .and_then(|v| v.parse::<usize>().ok())
         .map(TagId);
 
+    let name = value
+        .get(ATTR_QUESTION_NAME)
+        .and_then(|v| v.as_s().ok())
+        .cloned();
+
+    let session = value
+        .get(ATTR_QUESTION_SESSION)
+        .and_then(|v| v.as_n().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .map(SessionId);
+
     Ok(QuestionItem {
         id,
         likes,
@@ -121,5 +143,7 @@ fn attributes_to_question(value: &AttributeMap) -> Result<QuestionItem, super::E
         screening,
         create_time_unix,
         tag,
+        name,
+        session,
     })
 }
@@ -1,13 +1,21 @@
 mod conversion;
+mod organizer;
+mod password;
+mod poll;
+mod subscription;
+mod template;
+mod voucher;
+mod webhook;
 
 use crate::utils::timestamp_now;
 use aws_sdk_dynamodb::types::AttributeValue;
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_dynamo::from_item;
+use sha2::{Digest, Sha256};
 use shared::{
-    ContextItem, EventData, EventFlags, EventInfo, EventPassword, EventState, EventTags,
-    EventTokens, QuestionItem,
+    ContentFilter, ContextItem, EventData, EventFlags, EventInfo, EventSessions, EventState,
+    EventTags, EventTokens, ParticipantNames, PremiumTier, QuestionItem, QuestionLimits,
 };
 use std::collections::HashMap;
 use strum::IntoStaticStr;
@@ -16,12 +24,102 @@ use self::conversion::{attributes_to_event, event_to_attributes};
 
 use super::{Error, event_key};
 
+pub use self::{
+    organizer::StoredOrganizer, password::StoredPassword, poll::StoredPoll,
+    subscription::StoredSubscription, template::StoredTemplate, voucher::StoredVoucher,
+    webhook::StoredWebhookLog,
+};
+
+/// What an event was upgraded by.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, IntoStaticStr)]
+#[serde(from = "StoredPremiumOrder")]
 pub enum PremiumOrder {
+    StripeSessionId(String),
+    StripeSubscriptionId(String),
+    Admin(String),
+    /// invoice number of an invoice an admin confirmed as paid
+    Invoice(String),
+    /// order of any payment provider besides stripe
+    Provider {
+        provider: String,
+        order: String,
+    },
+    /// code of the redeemed [`StoredVoucher`]
+    Voucher(String),
+}
+
+/// [`PremiumOrder`] as stored, including variants of retired providers.
+#[derive(Deserialize)]
+enum StoredPremiumOrder {
     PaypalOrderId(String),
     StripeSessionId(String),
     StripeSubscriptionId(String),
     Admin(String),
+    Invoice(String),
+    Provider { provider: String, order: String },
+    Voucher(String),
+}
+
+impl From<StoredPremiumOrder> for PremiumOrder {
+    fn from(value: StoredPremiumOrder) -> Self {
+        match value {
+            StoredPremiumOrder::PaypalOrderId(order) => Self::paypal(order),
+            StoredPremiumOrder::StripeSessionId(id) => Self::StripeSessionId(id),
+            StoredPremiumOrder::StripeSubscriptionId(id) => Self::StripeSubscriptionId(id),
+            StoredPremiumOrder::Admin(admin) => Self::Admin(admin),
+            StoredPremiumOrder::Invoice(invoice) => Self::Invoice(invoice),
+            StoredPremiumOrder::Provider { provider, order } => Self::Provider { provider, order },
+            StoredPremiumOrder::Voucher(code) => Self::Voucher(code),
+        }
+    }
+}
+
+impl PremiumOrder {
+    /// Tier an event gets from this order unless it says otherwise.
+    pub const fn default_tier(&self) -> PremiumTier {
+        match self {
+            Self::StripeSubscriptionId(_) => PremiumTier::Organization,
+            _ => PremiumTier::SingleEvent,
+        }
+    }
+
+    /// events upgraded before stripe were paid with paypal
+    fn paypal(order: String) -> Self {
+        Self::Provider {
+            provider: String::from("paypal"),
+            order,
+        }
+    }
+}
+
+/// What premium entitles an event to, see [`ApiEventInfo::entitled`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PremiumFeature {
+    Tags,
+    Sessions,
+    ContextLinks,
+    QuestionLimits,
+    ViewerCount,
+    /// the event stays readable past the free retention period
+    Retention,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, IntoStaticStr)]
+pub enum PremiumIssueReason {
+    Refunded,
+    Disputed,
+    AsyncPaymentFailed,
+}
+
+/// Payment problem reported by the payment provider after an event was (about to be) upgraded.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PremiumIssue {
+    pub reason: PremiumIssueReason,
+    /// provider object reporting it, e.g. the refunded charge or the dispute
+    pub reference: String,
+    pub time_unix: i64,
+    /// the order the event lost premium by, `None` if it was only flagged
+    pub revoked: Option<PremiumOrder>,
 }
 
 /// old db entries had a untyped payment receipt that was only used in the paypal implmentation.
@@ -48,12 +146,39 @@ pub struct ApiEventInfo {
     pub do_screening: bool,
     pub state: EventState,
     #[serde(default)]
-    pub password: EventPassword,
+    pub password: StoredPassword,
     pub premium_id: Option<PremiumOrder>,
+    /// only meaningful with a `premium_id`
+    #[serde(default)]
+    pub premium_tier: PremiumTier,
+    /// premium ends at this time, `None` keeps it for good
+    #[serde(default)]
+    pub premium_until_unix: Option<i64>,
     #[serde(default)]
     pub context: Vec<ContextItem>,
     #[serde(default)]
     pub tags: EventTags,
+    #[serde(default)]
+    pub content_filter: ContentFilter,
+    #[serde(default)]
+    pub question_limits: QuestionLimits,
+    #[serde(default)]
+    pub participant_names: ParticipantNames,
+    #[serde(default)]
+    pub polls: Vec<StoredPoll>,
+    #[serde(default)]
+    pub sessions: EventSessions,
+    /// see [`ApiEventInfo::mod_email_hash`], the address itself is never stored
+    #[serde(default)]
+    pub mod_email_hash: Option<String>,
+    /// event id `mod_email_hash` is salted with if not this one, e.g. the event it was cloned from
+    #[serde(default)]
+    pub mod_email_salt: Option<String>,
+    #[serde(default)]
+    pub premium_issue: Option<PremiumIssue>,
+    /// chosen at creation, see [`shared::AddEvent::no_third_parties`]
+    #[serde(default)]
+    pub no_third_parties: bool,
 }
 
 const LOREM_IPSUM: &str = "Lorem ipsum dolor sit amet. Et adipisci repellendus id dolore molestiae sed quidem ratione! Aut itaque magnam eos corporis dolores ut repudiandae consequuntur et maiores accusantium. 33 quas illum vel cumque quisquam et possimus quaerat et nostrum galisum et similique dolorum quo earum earum et accusantium dignissimos!";
@@ -80,7 +205,7 @@ impl ApiEventInfo {
     }
 
     pub fn is_timed_out_and_free(&self) -> bool {
-        self.premium_id.is_none() && self.is_timed_out()
+        !self.entitled(PremiumFeature::Retention) && self.is_timed_out()
     }
 
     pub fn adapt_if_timedout(&mut self) -> bool {
@@ -95,6 +220,13 @@ impl ApiEventInfo {
     pub fn mask_data(&mut self) {
         for q in &mut self.questions {
             q.text = mask_string(&q.text).to_string();
+            q.name = q.name.as_deref().map(|name| mask_string(name).to_string());
+        }
+        for p in &mut self.polls {
+            p.poll.question = mask_string(&p.poll.question).to_string();
+            for o in &mut p.poll.options {
+                o.text = mask_string(&o.text).to_string();
+            }
         }
         self.data.description = mask_string(&self.data.description).to_string();
     }
@@ -103,8 +235,51 @@ impl ApiEventInfo {
         shared::EventInfo::age_in_seconds(self.create_time_unix)
     }
 
-    pub const fn premium(&self) -> bool {
+    /// Upgraded and not expired.
+    pub fn premium(&self) -> bool {
         self.premium_id.is_some()
+            && self
+                .premium_until_unix
+                .is_none_or(|until| until > timestamp_now())
+    }
+
+    /// The single place deciding whether the event's premium covers `feature`.
+    pub fn entitled(&self, feature: PremiumFeature) -> bool {
+        if !self.premium() {
+            return false;
+        }
+
+        self.premium_tier.has_features() || feature == PremiumFeature::Retention
+    }
+
+    /// Tier of an active premium.
+    pub fn active_premium_tier(&self) -> Option<PremiumTier> {
+        self.premium().then_some(self.premium_tier)
+    }
+
+    /// Upgrading to `tier` adds to what the event is entitled to.
+    pub fn upgradable_to(&self, tier: PremiumTier) -> bool {
+        self.active_premium_tier()
+            .is_none_or(|current| current.is_below(tier))
+    }
+
+    /// Hash of the moderator email given at creation, salted with the event id so the same
+    /// address cannot be linked across events.
+    pub fn mod_email_hash(id: &str, email: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(id.as_bytes());
+        hasher.update(b":");
+        hasher.update(email.trim().to_lowercase().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    pub fn is_mod_email(&self, email: &str) -> bool {
+        let salt = self
+            .mod_email_salt
+            .as_deref()
+            .unwrap_or(&self.tokens.public_token);
+
+        self.mod_email_hash.as_deref() == Some(Self::mod_email_hash(salt, email).as_str())
     }
 }
 
@@ -113,7 +288,12 @@ impl From<ApiEventInfo> for EventInfo {
         let mut flags = EventFlags::empty();
 
         flags.set(EventFlags::DELETED, val.deleted);
-        flags.set(EventFlags::PREMIUM, val.premium_id.is_some());
+        let premium_tier = val.active_premium_tier();
+
+        flags.set(
+            EventFlags::PREMIUM,
+            premium_tier.is_some_and(PremiumTier::has_features),
+        );
         flags.set(EventFlags::SCREENING, val.do_screening);
         flags.set(EventFlags::PASSWORD, val.password.is_enabled());
 
@@ -128,6 +308,11 @@ impl From<ApiEventInfo> for EventInfo {
             flags,
             context: val.context,
             tags: val.tags,
+            question_limits: val.question_limits,
+            participant_names: val.participant_names,
+            polls: val.polls.into_iter().map(|p| p.poll).collect(),
+            sessions: val.sessions,
+            premium_tier,
         }
     }
 }
@@ -188,7 +373,7 @@ impl TryFrom<&AttributeMap> for EventEntry {
             if let Some(legacy) = legacy {
                 tracing::info!("legacy: {:?}", legacy);
                 if let Some(paypal_order) = legacy.paypal_order {
-                    event.premium_id = Some(PremiumOrder::PaypalOrderId(paypal_order));
+                    event.premium_id = Some(PremiumOrder::paypal(paypal_order));
                 }
             }
         }
@@ -247,8 +432,10 @@ mod test_serialization {
                 create_time_unix: 1,
                 delete_time_unix: 0,
                 deleted: false,
-                password: shared::EventPassword::Disabled,
-                premium_id: Some(PremiumOrder::PaypalOrderId(String::from("order"))),
+                password: StoredPassword::Disabled,
+                premium_id: Some(PremiumOrder::Invoice(String::from("order"))),
+                premium_tier: PremiumTier::SingleEvent,
+                premium_until_unix: None,
                 last_edit_unix: 2,
                 questions: vec![QuestionItem {
                     id: 0,
@@ -259,6 +446,8 @@ mod test_serialization {
                     screening: false,
                     create_time_unix: 3,
                     tag: None,
+                    name: None,
+                    session: None,
                 }],
                 do_screening: true,
                 state: EventState {
@@ -266,6 +455,15 @@ mod test_serialization {
                 },
                 context: Vec::new(),
                 tags: EventTags::default(),
+                content_filter: ContentFilter::default(),
+                question_limits: QuestionLimits::default(),
+                participant_names: ParticipantNames::default(),
+                polls: Vec::new(),
+                sessions: EventSessions::default(),
+                mod_email_hash: None,
+                mod_email_salt: None,
+                premium_issue: None,
+                no_third_parties: false,
             },
             version: 2,
             ttl: None,
@@ -296,8 +494,10 @@ mod test_serialization {
                 create_time_unix: 1,
                 delete_time_unix: 0,
                 deleted: false,
-                password: shared::EventPassword::Enabled(String::from("pwd")),
+                password: StoredPassword::hash("pwd").unwrap(),
                 premium_id: Some(PremiumOrder::StripeSessionId(String::from("order"))),
+                premium_tier: PremiumTier::ExtendedRetention,
+                premium_until_unix: Some(4),
                 last_edit_unix: 2,
                 questions: vec![QuestionItem {
                     id: 0,
@@ -308,6 +508,8 @@ mod test_serialization {
                     screening: true,
                     create_time_unix: 3,
                     tag: Some(TagId(0)),
+                    name: None,
+                    session: None,
                 }],
                 do_screening: false,
                 state: EventState {
@@ -323,7 +525,22 @@ mod test_serialization {
                         id: TagId(0),
                     }],
                     current_tag: Some(TagId(0)),
+                    next_id: 1,
                 },
+                content_filter: ContentFilter::default(),
+                question_limits: QuestionLimits::default(),
+                participant_names: ParticipantNames::default(),
+                polls: Vec::new(),
+                sessions: EventSessions::default(),
+                mod_email_hash: Some(String::from("hash")),
+                mod_email_salt: Some(String::from("source")),
+                premium_issue: Some(PremiumIssue {
+                    reason: PremiumIssueReason::Refunded,
+                    reference: String::from("ch_1"),
+                    time_unix: 4,
+                    revoked: Some(PremiumOrder::StripeSessionId(String::from("cs_1"))),
+                }),
+                no_third_parties: true,
             },
             version: 2,
             ttl: Some(12345),
@@ -335,4 +552,81 @@ mod test_serialization {
 
         assert_eq!(entry, entry_deserialized);
     }
+
+    #[test]
+    fn test_paypal_order_reads_as_provider() {
+        let order: PremiumOrder = serde_json::from_str(r#"{"PaypalOrderId":"order"}"#).unwrap();
+
+        assert_eq!(
+            order,
+            PremiumOrder::Provider {
+                provider: String::from("paypal"),
+                order: String::from("order"),
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_premium {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const FEATURES: [PremiumFeature; 6] = [
+        PremiumFeature::Tags,
+        PremiumFeature::Sessions,
+        PremiumFeature::ContextLinks,
+        PremiumFeature::QuestionLimits,
+        PremiumFeature::ViewerCount,
+        PremiumFeature::Retention,
+    ];
+
+    fn event(tier: PremiumTier) -> ApiEventInfo {
+        ApiEventInfo {
+            premium_id: Some(PremiumOrder::Admin(String::from("admin"))),
+            premium_tier: tier,
+            ..ApiEventInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_entitled() {
+        // in the order of `FEATURES`
+        for (tier, entitled) in [
+            (PremiumTier::SingleEvent, [true; 6]),
+            (
+                PremiumTier::ExtendedRetention,
+                [false, false, false, false, false, true],
+            ),
+            (PremiumTier::Organization, [true; 6]),
+        ] {
+            let e = event(tier);
+
+            for (feature, entitled) in FEATURES.into_iter().zip(entitled) {
+                assert_eq!(e.entitled(feature), entitled);
+            }
+        }
+    }
+
+    #[test]
+    fn test_entitled_needs_active_premium() {
+        for tier in [
+            PremiumTier::SingleEvent,
+            PremiumTier::ExtendedRetention,
+            PremiumTier::Organization,
+        ] {
+            let mut expired = event(tier);
+            expired.premium_until_unix = Some(timestamp_now() - 1);
+
+            let free = ApiEventInfo {
+                premium_tier: tier,
+                ..ApiEventInfo::default()
+            };
+
+            for feature in FEATURES {
+                assert!(!expired.entitled(feature));
+                assert!(!free.entitled(feature));
+            }
+        }
+    }
 }
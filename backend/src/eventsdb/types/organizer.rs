@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Organizer account owning events. Accounts are keyed by a hash of the login email, the
+/// address itself is never stored.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredOrganizer {
+    pub id: String,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+    /// nonce of the last mailed login link, cleared once it is used
+    #[serde(default)]
+    pub login_nonce: Option<String>,
+    /// public ids of the owned events, oldest first
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// bumped on every write, concurrent logins and event changes retry instead of dropping
+    /// each other
+    #[serde(default)]
+    pub version: usize,
+}
+
+impl StoredOrganizer {
+    pub fn id_from_email(email: &str) -> String {
+        hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_id_from_email_is_normalized() {
+        assert_eq!(
+            StoredOrganizer::id_from_email(" Jo@Example.com "),
+            StoredOrganizer::id_from_email("jo@example.com")
+        );
+        assert_ne!(
+            StoredOrganizer::id_from_email("jo@example.com"),
+            StoredOrganizer::id_from_email("jo@example.org")
+        );
+    }
+}
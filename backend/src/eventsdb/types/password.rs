@@ -0,0 +1,141 @@
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use serde::{Deserialize, Serialize};
+use shared::EventPassword;
+
+use crate::utils::pwd_fingerprint;
+
+/// PHC id prefix of the hashes we write; anything else read from the db is a legacy plaintext.
+const HASH_PREFIX: &str = "$argon2";
+
+/// Event password as persisted. New passwords are only ever stored as a salted argon2 hash;
+/// `Legacy` is a plaintext value read from an entry written before hashing was introduced and
+/// is replaced by its hash when the entry is loaded (see `App::load_entry`).
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub enum StoredPassword {
+    #[default]
+    Disabled,
+    /// argon2 PHC string (algorithm, params, salt and hash)
+    Hashed(String),
+    /// pre-hashing entries serialized the plaintext as `EventPassword::Enabled`
+    #[serde(alias = "Enabled")]
+    Legacy(String),
+}
+
+impl StoredPassword {
+    /// Hash `password` with a fresh random salt.
+    pub fn hash(password: &str) -> Result<Self, argon2::password_hash::Error> {
+        let salt = SaltString::generate(&mut rand::rngs::OsRng);
+        let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+
+        Ok(Self::Hashed(hash.to_string()))
+    }
+
+    /// Classify a raw db value: our own PHC hashes vs. legacy plaintext.
+    pub fn from_stored(value: String) -> Self {
+        if value.starts_with(HASH_PREFIX) && PasswordHash::new(&value).is_ok() {
+            Self::Hashed(value)
+        } else {
+            Self::Legacy(value)
+        }
+    }
+
+    /// Replace a legacy plaintext with its hash; other variants are returned unchanged. If
+    /// hashing fails the plaintext is kept, so the password keeps working and is retried on the
+    /// next load. Slow like [`Self::verify`].
+    pub fn migrate(self) -> Self {
+        match self {
+            Self::Legacy(plain) => Self::hash(&plain).unwrap_or_else(|e| {
+                tracing::error!("legacy password migration failed: {e}");
+                Self::Legacy(plain)
+            }),
+            other => other,
+        }
+    }
+
+    pub const fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+
+    /// Slow: runs argon2 for hashed values, keep it off the async executor.
+    pub fn verify(&self, password: &str) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::Hashed(hash) => PasswordHash::new(hash).is_ok_and(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            }),
+            // compare digests rather than the plaintexts so timing reveals nothing useful
+            Self::Legacy(plain) => pwd_fingerprint(plain) == pwd_fingerprint(password),
+        }
+    }
+
+    /// Fingerprint bound into pwd grants. It covers the stored value, and every hash has a
+    /// fresh salt, so any password change (even to the same value) re-locks outstanding grants.
+    pub fn fingerprint(&self) -> Option<String> {
+        match self {
+            Self::Disabled => None,
+            Self::Hashed(value) | Self::Legacy(value) => Some(pwd_fingerprint(value)),
+        }
+    }
+
+    /// What moderators get to see: whether a password is set, never the value itself.
+    pub fn redacted(&self) -> EventPassword {
+        if self.is_enabled() {
+            EventPassword::Enabled(String::new())
+        } else {
+            EventPassword::Disabled
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hash_verifies_and_is_salted() {
+        let a = StoredPassword::hash("pwd").unwrap();
+        let b = StoredPassword::hash("pwd").unwrap();
+
+        assert!(a.verify("pwd"));
+        assert!(!a.verify("pw"));
+        // same password, different salt
+        assert_ne!(a, b);
+        assert_ne!(a.fingerprint(), b.fingerprint());
+    }
+
+    #[test]
+    fn stored_values_are_classified() {
+        let hashed = StoredPassword::hash("pwd").unwrap();
+
+        assert!(matches!(
+            &hashed,
+            StoredPassword::Hashed(raw) if StoredPassword::from_stored(raw.clone()) == hashed
+        ));
+        assert_eq!(
+            StoredPassword::from_stored(String::from("$plain")),
+            StoredPassword::Legacy(String::from("$plain"))
+        );
+    }
+
+    #[test]
+    fn legacy_plaintext_migrates_to_hash() {
+        let legacy = StoredPassword::Legacy(String::from("pwd"));
+        assert!(legacy.verify("pwd"));
+
+        let migrated = legacy.migrate();
+        assert!(matches!(migrated, StoredPassword::Hashed(_)));
+        assert!(migrated.verify("pwd"));
+        assert!(!migrated.verify("other"));
+    }
+
+    #[test]
+    fn redacted_never_leaks_the_value() {
+        assert_eq!(
+            StoredPassword::hash("pwd").unwrap().redacted(),
+            EventPassword::Enabled(String::new())
+        );
+        assert_eq!(StoredPassword::Disabled.redacted(), EventPassword::Disabled);
+    }
+}
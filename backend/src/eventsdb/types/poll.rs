@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use shared::Poll;
+
+/// Poll as persisted. Who voted is kept in separate records (see `EventsDB::claim_poll_vote`),
+/// so a large audience does not grow the event item.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredPoll {
+    pub poll: Poll,
+}
+
+impl StoredPoll {
+    pub const fn new(poll: Poll) -> Self {
+        Self { poll }
+    }
+
+    /// Count a ballot, the caller made sure `choices` are valid and claimed the vote.
+    pub fn add_vote(&mut self, choices: &[usize]) {
+        for choice in choices {
+            if let Some(option) = self.poll.options.get_mut(*choice) {
+                option.votes = option.votes.saturating_add(1);
+            }
+        }
+
+        self.poll.voters = self.poll.voters.saturating_add(1);
+    }
+}
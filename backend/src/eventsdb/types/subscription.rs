@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Stripe subscription that premium events were created under, kept in sync by its webhooks.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredSubscription {
+    pub id: String,
+    pub customer: String,
+    /// last status stripe reported, e.g. `active` or `canceled`
+    pub status: String,
+    #[serde(rename = "updateTimeUnix")]
+    pub update_time_unix: i64,
+    /// public ids of the events created under it, oldest first
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl StoredSubscription {
+    /// Only an active subscription creates new premium events. Events it created before stay
+    /// premium when it ends, they were paid for in that period.
+    pub fn is_active(&self) -> bool {
+        matches!(self.status.as_str(), "active" | "trialing")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_active() {
+        let mut subscription = StoredSubscription {
+            status: String::from("active"),
+            ..StoredSubscription::default()
+        };
+        assert!(subscription.is_active());
+
+        for status in ["past_due", "unpaid", "canceled", "incomplete_expired"] {
+            subscription.status = String::from(status);
+            assert!(!subscription.is_active());
+        }
+    }
+}
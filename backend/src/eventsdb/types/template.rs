@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use shared::{Color, ContentFilter, ContextItem, EventTags, ParticipantNames, QuestionLimits, Tag};
+
+use super::{ApiEventInfo, PremiumFeature};
+
+/// Settings of an event saved for reuse, see `App::mod_save_template`. Also used to carry the
+/// settings over when an event is cloned. The password is left out, a template is no way to
+/// hand the access to one event on to others.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+    pub color: Option<Color>,
+    #[serde(default)]
+    pub context: Vec<ContextItem>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub do_screening: bool,
+    #[serde(default)]
+    pub content_filter: ContentFilter,
+    #[serde(default)]
+    pub question_limits: QuestionLimits,
+    #[serde(default)]
+    pub participant_names: ParticipantNames,
+}
+
+impl StoredTemplate {
+    pub fn from_event(id: String, name: String, create_time_unix: i64, e: &ApiEventInfo) -> Self {
+        Self {
+            id,
+            name,
+            create_time_unix,
+            color: e.data.color.clone(),
+            context: e.context.clone(),
+            tags: e.tags.tags.clone(),
+            do_screening: e.do_screening,
+            content_filter: e.content_filter.clone(),
+            question_limits: e.question_limits,
+            participant_names: e.participant_names,
+        }
+    }
+
+    /// Copy the settings onto `e`. Premium only settings (tags, context links and question
+    /// limits) are only applied if `e` is entitled to them itself, whatever the source was.
+    pub fn apply(&self, e: &mut ApiEventInfo) {
+        e.data.color.clone_from(&self.color);
+        e.do_screening = self.do_screening;
+        e.content_filter = self.content_filter.clone();
+        e.participant_names = self.participant_names;
+
+        if e.entitled(PremiumFeature::ContextLinks) {
+            e.context.clone_from(&self.context);
+        }
+        if e.entitled(PremiumFeature::Tags) {
+            e.tags = EventTags {
+                current_tag: None,
+                tags: self.tags.clone(),
+                next_id: 0,
+            };
+        }
+        if e.entitled(PremiumFeature::QuestionLimits) {
+            e.question_limits = self.question_limits;
+        }
+    }
+}
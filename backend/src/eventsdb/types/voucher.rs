@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use shared::PremiumTier;
+
+/// Admin issued code upgrading events without a payment, e.g. handed out to a meetup. See
+/// `App::redeem_voucher`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredVoucher {
+    /// normalized, see [`StoredVoucher::normalize_code`]
+    pub code: String,
+    /// what it was issued for, only shown to admins
+    #[serde(default)]
+    pub note: String,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+    #[serde(rename = "maxRedemptions")]
+    pub max_redemptions: usize,
+    /// public ids of the events it upgraded, oldest first
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub tier: PremiumTier,
+    /// cannot be redeemed after this time
+    #[serde(default, rename = "validUntilUnix")]
+    pub valid_until_unix: Option<i64>,
+    /// bumped on every write, a conflicting redemption fails instead of going over the limit
+    #[serde(default)]
+    pub version: usize,
+}
+
+impl StoredVoucher {
+    /// Codes are entered by hand, so case and surrounding whitespace do not matter.
+    pub fn normalize_code(code: &str) -> String {
+        code.trim().to_uppercase()
+    }
+
+    /// Record `event` as upgraded by this voucher, `false` if it is used up, expired or was
+    /// redeemed for `event` before (its premium ran out or was revoked since).
+    pub fn redeem(&mut self, event: &str, now: i64) -> bool {
+        if self.events.len() >= self.max_redemptions
+            || self.events.iter().any(|e| e == event)
+            || self.valid_until_unix.is_some_and(|until| until <= now)
+        {
+            return false;
+        }
+
+        self.events.push(event.to_string());
+        self.version += 1;
+
+        true
+    }
+
+    /// Take back the redemption by `event` after its upgrade failed, `false` if there was none.
+    pub fn release(&mut self, event: &str) -> bool {
+        let redemptions = self.events.len();
+
+        self.events.retain(|e| e != event);
+
+        if self.events.len() == redemptions {
+            return false;
+        }
+
+        self.version += 1;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_redeem() {
+        let mut voucher = StoredVoucher {
+            code: StoredVoucher::normalize_code(" meetup-24 "),
+            max_redemptions: 2,
+            valid_until_unix: Some(10),
+            ..StoredVoucher::default()
+        };
+        assert_eq!(voucher.code, "MEETUP-24");
+
+        assert!(voucher.redeem("ev1", 1));
+        assert!(!voucher.redeem("ev1", 1));
+        assert!(voucher.redeem("ev2", 1));
+        assert!(!voucher.redeem("ev3", 1));
+        assert_eq!(
+            voucher.events,
+            vec![String::from("ev1"), String::from("ev2")]
+        );
+        assert_eq!(voucher.version, 2);
+
+        voucher.max_redemptions = 3;
+        assert!(!voucher.redeem("ev3", 10));
+        assert!(voucher.redeem("ev3", 9));
+
+        assert!(voucher.release("ev1"));
+        assert!(!voucher.release("ev1"));
+        assert_eq!(voucher.version, 4);
+        assert!(voucher.redeem("ev1", 1));
+    }
+}
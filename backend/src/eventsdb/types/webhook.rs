@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use shared::WebhookDelivery;
+
+/// How many deliveries the log keeps for support to look at.
+const MAX_LOGGED_DELIVERIES: usize = 200;
+
+/// The most recent webhook deliveries, newest first. Deliveries are deduplicated through their
+/// own items, this is only the list to look them up by.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct StoredWebhookLog {
+    #[serde(default)]
+    pub deliveries: Vec<WebhookDelivery>,
+    /// bumped on every write, concurrent deliveries retry instead of dropping each other
+    #[serde(default)]
+    pub version: usize,
+}
+
+impl StoredWebhookLog {
+    /// Put `delivery` on top, a retried delivery replaces its earlier attempt.
+    pub fn record(&mut self, delivery: WebhookDelivery) {
+        self.deliveries.retain(|d| d.id != delivery.id);
+        self.deliveries.insert(0, delivery);
+        self.deliveries.truncate(MAX_LOGGED_DELIVERIES);
+        self.version += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use shared::WebhookOutcome;
+
+    fn delivery(id: usize, outcome: WebhookOutcome) -> WebhookDelivery {
+        WebhookDelivery {
+            id: format!("evt_{id}"),
+            kind: String::from("charge.refunded"),
+            time_unix: 0,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut log = StoredWebhookLog::default();
+
+        for id in 0..=MAX_LOGGED_DELIVERIES {
+            log.record(delivery(id, WebhookOutcome::Failed(String::from("db"))));
+        }
+
+        assert_eq!(log.deliveries.len(), MAX_LOGGED_DELIVERIES);
+        assert_eq!(log.deliveries[0].id, format!("evt_{MAX_LOGGED_DELIVERIES}"));
+
+        log.record(delivery(5, WebhookOutcome::Processed));
+
+        assert_eq!(log.deliveries.len(), MAX_LOGGED_DELIVERIES);
+        assert_eq!(log.deliveries[0], delivery(5, WebhookOutcome::Processed));
+        assert_eq!(log.deliveries.iter().filter(|d| d.id == "evt_5").count(), 1);
+        assert_eq!(log.version, MAX_LOGGED_DELIVERIES + 2);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod legacy;
+
 use axum::{
     Extension, Json,
     extract::{Path, State, WebSocketUpgrade, ws::WebSocket},
@@ -6,6 +8,7 @@ use axum::{
 };
 use shared::{EventPasswordResponse, Semver, VersionInfo};
 use tracing::instrument;
+use ulid::Ulid;
 
 use crate::{
     GIT_HASH,
@@ -14,6 +17,21 @@ use crate::{
     error::InternalError,
 };
 
+/// Address of the requesting client for throttling. The load balancer appends the peer it saw
+/// to `X-Forwarded-For`, so only the last entry is trustworthy; anything before it is client
+/// supplied.
+fn client_address(headers: &HeaderMap) -> String {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .rfind(|addr| !addr.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
 async fn socket_handler(ws: WebSocket, id: String, app: SharedApp) {
     app.push_subscriber(ws, id).await;
 }
@@ -42,14 +60,31 @@ pub async fn editlike_handler(
 
 // skip(payload): AddEvent carries the moderator's email (PII); #[instrument] would
 // otherwise record it as a span field and ship it to logs/Sentry
-#[instrument(skip(app, payload))]
+#[instrument(skip(app, cfg, headers, payload))]
 pub async fn addevent_handler(
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
     Json(payload): Json<shared::AddEvent>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("create event");
 
-    Ok(Json(app.create_event(payload).await?))
+    let event = app.create_event(payload).await?;
+
+    // a logged in organizer owns the event right away, failing that must not fail the create
+    if let (Some(organizer), Some(secret)) = (
+        auth::organizer_id(&cfg, &headers),
+        event.tokens.moderator_token.as_deref(),
+    ) {
+        if let Err(e) = app
+            .organizer_add_event(&organizer, event.tokens.public_token.clone(), secret)
+            .await
+        {
+            tracing::error!("organizer add event error: {e}");
+        }
+    }
+
+    Ok(Json(event))
 }
 
 #[instrument(skip(app))]
@@ -82,39 +117,120 @@ pub async fn getevent_handler(
 
 // skip(payload): the request carries the cleartext event password; #[instrument] would
 // otherwise record it as a span field and ship it to logs/Sentry (cf. login_handler)
-#[instrument(skip(app, cfg, payload))]
+#[instrument(skip(app, cfg, headers, payload))]
 pub async fn set_event_password(
     Path(id): Path<String>,
     Extension(cfg): Extension<AuthConfig>,
     State(app): State<SharedApp>,
+    headers: HeaderMap,
     Json(payload): Json<shared::EventPasswordRequest>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("set_event_password");
 
+    let client = client_address(&headers);
+
+    let fingerprint = app
+        .check_event_password(id.clone(), &payload.pwd, &client)
+        .await?;
+
     let response = EventPasswordResponse {
-        ok: app.check_event_password(id.clone(), &payload.pwd).await?,
+        ok: fingerprint.is_some(),
     };
 
     // on success hand back a grant cookie the browser re-sends on the event fetch
     let mut cookies: Vec<(HeaderName, String)> = Vec::new();
-    if response.ok {
+    if let Some(fingerprint) = fingerprint {
         cookies.push((
             header::SET_COOKIE,
-            auth::pwd_grant_cookie(&cfg, &id, &payload.pwd)?,
+            auth::pwd_grant_cookie(&cfg, &id, &fingerprint)?,
         ));
     }
 
     Ok((AppendHeaders(cookies), Json(response)))
 }
 
-#[instrument(skip(app))]
+// skip(payload): the request carries the moderator's email (PII)
+#[instrument(skip(app, headers, payload))]
+pub async fn recover_mod_link_handler(
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModLinkRecovery>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("recover_mod_link");
+
+    let client = client_address(&headers);
+
+    app.recover_mod_link(id, &payload.email, &client).await?;
+
+    Ok(Json(()))
+}
+
+/// Vote on a poll. Voters are told apart by an anonymous id cookie, handed out on first vote.
+#[instrument(skip(app, cfg, headers))]
+pub async fn vote_poll_handler(
+    Path((id, poll_id)): Path<(String, i64)>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::PollVote>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("vote poll: {poll_id} in event: {id}");
+
+    let mut cookies: Vec<(HeaderName, String)> = Vec::new();
+    let voter = if let Some(voter) = auth::voter_id(&cfg, &headers) {
+        voter
+    } else {
+        let voter = Ulid::new().to_string();
+        cookies.push((header::SET_COOKIE, auth::voter_cookie(&cfg, &voter)?));
+        voter
+    };
+
+    let poll = app.vote_poll(id, poll_id, &voter, payload).await?;
+
+    Ok((AppendHeaders(cookies), Json(poll)))
+}
+
+/// Moderator secret of the request (see [`auth::mod_secret`]); the caller still checks it
+/// against the event.
+fn mod_secret(cfg: &AuthConfig, headers: &HeaderMap, id: &str) -> Result<String, InternalError> {
+    auth::mod_secret(cfg, headers, id)
+        .ok_or_else(|| InternalError::WrongModeratorToken(id.to_string()))
+}
+
+/// Exchange the secret of a mod link (as bearer token) for a grant cookie, so later moderator
+/// requests need neither the header nor the secret in the URL.
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_session(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_session");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    app.check_mod_token(id.clone(), &secret).await?;
+
+    Ok(AppendHeaders([(
+        header::SET_COOKIE,
+        auth::mod_grant_cookie(&cfg, &id, &secret)?,
+    )]))
+}
+
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_get_event(
-    Path((id, secret)): Path<(String, String)>,
+    Path(id): Path<String>,
     OptionalUser(user): OptionalUser,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_get_event");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     //TODO: special response type for mods to add more info
     Ok(Json(
         app.get_event(id, Some(secret), user.is_some(), None)
@@ -122,31 +238,59 @@ pub async fn mod_get_event(
     ))
 }
 
-#[instrument(skip(app))]
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_delete_event(
-    Path((id, secret)): Path<(String, String)>,
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_delete_event");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     Ok(Json(app.delete_event(id, secret).await?))
 }
 
-#[instrument(skip(app))]
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_premium_upgrade(
-    Path((id, secret)): Path<(String, String)>,
+    Path(id): Path<String>,
     OptionalUser(user): OptionalUser,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
     Json(payload): Json<shared::ModRequestPremium>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_premium_upgrade");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     Ok(Json(
         app.request_premium_upgrade(id, secret, user.is_some(), payload)
             .await?,
     ))
 }
 
+/// Upgrade with an admin issued voucher code instead of a payment.
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_redeem_voucher(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModRedeemVoucher>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_redeem_voucher");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+    let client = client_address(&headers);
+
+    app.redeem_voucher(id, secret, &payload.code, &client)
+        .await?;
+
+    Ok(Json(()))
+}
+
 #[instrument(skip(app))]
 pub async fn mod_premium_capture(
     Path((id, order)): Path<(String, String)>,
@@ -157,13 +301,17 @@ pub async fn mod_premium_capture(
     Ok(Json(app.premium_capture(id, order).await?))
 }
 
-#[instrument(skip(app))]
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_get_question(
-    Path((id, secret, question_id)): Path<(String, String, i64)>,
+    Path((id, question_id)): Path<(String, i64)>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_get_question");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     Ok(Json(app.get_question(id, Some(secret), question_id).await?))
 }
 
@@ -177,31 +325,217 @@ pub async fn get_question(
     Ok(Json(app.get_question(id, None, question_id).await?))
 }
 
-#[instrument(skip(app))]
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_edit_question(
-    Path((id, secret, question_id)): Path<(String, String, i64)>,
+    Path((id, question_id)): Path<(String, i64)>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
     Json(payload): Json<shared::ModQuestion>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_edit_question");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     Ok(Json(
         app.mod_edit_question(id, secret, question_id, payload)
             .await?,
     ))
 }
 
-#[instrument(skip(app))]
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_merge_question(
+    Path((id, question_id)): Path<(String, i64)>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::MergeQuestion>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_merge_question");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    Ok(Json(
+        app.mod_merge_question(id, secret, question_id, payload.into)
+            .await?,
+    ))
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_clone_event(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::CloneEvent>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_clone_event");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    Ok(Json(app.mod_clone_event(id, secret, payload).await?))
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_save_template(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::SaveTemplate>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_save_template");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    Ok(Json(app.mod_save_template(id, secret, payload).await?))
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_add_poll(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::AddPoll>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_add_poll");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    Ok(Json(app.mod_add_poll(id, secret, payload).await?))
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn mod_edit_poll(
+    Path((id, poll_id)): Path<(String, i64)>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModPoll>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_edit_poll");
+
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    Ok(Json(app.mod_edit_poll(id, secret, poll_id, payload).await?))
+}
+
+#[instrument(skip(app, cfg, headers))]
 pub async fn mod_edit_event(
-    Path((id, secret)): Path<(String, String)>,
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
     State(app): State<SharedApp>,
     Json(payload): Json<shared::ModEvent>,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
     tracing::info!("mod_edit_state");
 
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
     Ok(Json(app.mod_edit_event(id, secret, payload).await?))
 }
 
+// skip(payload): the request carries the organizer's email (PII)
+#[instrument(skip(app, cfg, headers, payload))]
+pub async fn organizer_login_handler(
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::OrganizerLogin>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("organizer_login");
+
+    let client = client_address(&headers);
+
+    let (organizer, nonce) = app.organizer_login(&payload.email, &client).await?;
+
+    let token = auth::organizer_login_token(&cfg, &organizer, &nonce)?;
+
+    app.send_login_mail(payload.email.trim().to_string(), &token);
+
+    Ok(Json(()))
+}
+
+/// Redeem a mailed login link for an organizer session cookie.
+#[instrument(skip(app, cfg, payload))]
+pub async fn organizer_verify_handler(
+    Extension(cfg): Extension<AuthConfig>,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::OrganizerLoginVerify>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("organizer_verify");
+
+    let (organizer, nonce) =
+        auth::verify_login_token(&cfg, &payload.token).ok_or(InternalError::InvalidLogin)?;
+
+    app.organizer_verify(&organizer, &nonce).await?;
+
+    Ok(AppendHeaders([(
+        header::SET_COOKIE,
+        auth::organizer_cookie(&cfg, &organizer)?,
+    )]))
+}
+
+#[instrument(skip_all)]
+#[allow(clippy::unused_async)]
+pub async fn organizer_logout_handler(Extension(cfg): Extension<AuthConfig>) -> impl IntoResponse {
+    tracing::info!("organizer_logout");
+
+    AppendHeaders([(header::SET_COOKIE, auth::clear_organizer_cookie(&cfg))])
+}
+
+fn organizer_id(cfg: &AuthConfig, headers: &HeaderMap) -> Result<String, InternalError> {
+    auth::organizer_id(cfg, headers).ok_or(InternalError::InvalidLogin)
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn organizer_events_handler(
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("organizer_events");
+
+    let organizer = organizer_id(&cfg, &headers)?;
+
+    Ok(Json(app.organizer_dashboard(&organizer).await?))
+}
+
+/// Add an existing event to the organizer's account, the moderator secret proves ownership.
+#[instrument(skip(app, cfg, headers))]
+pub async fn organizer_add_event_handler(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("organizer_add_event");
+
+    let organizer = organizer_id(&cfg, &headers)?;
+    let secret = mod_secret(&cfg, &headers, &id)?;
+
+    app.organizer_add_event(&organizer, id, &secret).await?;
+
+    Ok(Json(()))
+}
+
+#[instrument(skip(app, cfg, headers))]
+pub async fn organizer_remove_event_handler(
+    Path(id): Path<String>,
+    Extension(cfg): Extension<AuthConfig>,
+    headers: HeaderMap,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("organizer_remove_event");
+
+    let organizer = organizer_id(&cfg, &headers)?;
+
+    app.organizer_remove_event(&organizer, &id).await?;
+
+    Ok(Json(()))
+}
+
 // skip(payload): SubscriptionCheckout can carry the customer's email (PII); keep it off
 // the span so it never reaches logs/Sentry
 #[instrument(skip(app, payload))]
@@ -224,6 +558,70 @@ pub async fn subscription_url_handler(
     Ok(Json(response))
 }
 
+#[instrument(skip(app))]
+pub async fn admin_invoice_paid_handler(
+    Path(id): Path<String>,
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::AdminInvoicePaid>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("admin_invoice_paid");
+
+    if user.is_none() {
+        return Err(InternalError::InvalidLogin);
+    }
+
+    app.invoice_paid(id, &payload).await?;
+
+    Ok(Json(()))
+}
+
+#[instrument(skip(app))]
+pub async fn admin_create_voucher_handler(
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::AdminCreateVoucher>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("admin_create_voucher");
+
+    if user.is_none() {
+        return Err(InternalError::InvalidLogin);
+    }
+
+    Ok(Json(app.create_voucher(&payload).await?))
+}
+
+/// A voucher with the events that redeemed it.
+#[instrument(skip(app))]
+pub async fn admin_voucher_handler(
+    Path(code): Path<String>,
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("admin_voucher");
+
+    if user.is_none() {
+        return Err(InternalError::InvalidLogin);
+    }
+
+    Ok(Json(app.voucher(&code).await?))
+}
+
+/// Recent payment webhook deliveries and their outcome, for support.
+#[instrument(skip(app))]
+pub async fn admin_webhooks_handler(
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("admin_webhooks");
+
+    if user.is_none() {
+        return Err(InternalError::InvalidLogin);
+    }
+
+    Ok(Json(app.webhook_deliveries().await?))
+}
+
 #[instrument]
 pub async fn ping_handler() -> Html<&'static str> {
     Html("pong")
@@ -242,16 +640,107 @@ pub async fn version_handler() -> Json<VersionInfo> {
     })
 }
 
+#[cfg(test)]
+mod test_db_base {
+    use crate::eventsdb::{
+        EventEntry, EventsDB, InMemoryEventsDB, Result, StoredOrganizer, StoredSubscription,
+        StoredTemplate, StoredVoucher, StoredWebhookLog,
+    };
+    use async_trait::async_trait;
+    use shared::WebhookDelivery;
+
+    /// Hands every call to an `InMemoryEventsDB` unless the event `get`/`put` is overridden,
+    /// so the failing mocks below don't need to follow each new `EventsDB` method
+    #[derive(Default)]
+    pub struct DelegatingDB {
+        pub base: InMemoryEventsDB,
+        pub get: Option<fn(&str) -> Result<EventEntry>>,
+        pub put: Option<fn(&EventEntry) -> Result<()>>,
+    }
+
+    #[async_trait]
+    impl EventsDB for DelegatingDB {
+        async fn get(&self, key: &str) -> Result<EventEntry> {
+            match self.get {
+                Some(get) => get(key),
+                None => self.base.get(key).await,
+            }
+        }
+        async fn put(&self, event: EventEntry) -> Result<()> {
+            match self.put {
+                Some(put) => put(&event),
+                None => self.base.put(event).await,
+            }
+        }
+        async fn get_template(&self, key: &str) -> Result<StoredTemplate> {
+            self.base.get_template(key).await
+        }
+        async fn put_template(&self, template: StoredTemplate) -> Result<()> {
+            self.base.put_template(template).await
+        }
+        async fn get_organizer(&self, key: &str) -> Result<StoredOrganizer> {
+            self.base.get_organizer(key).await
+        }
+        async fn put_organizer(&self, organizer: StoredOrganizer) -> Result<()> {
+            self.base.put_organizer(organizer).await
+        }
+        async fn get_subscription(&self, key: &str) -> Result<StoredSubscription> {
+            self.base.get_subscription(key).await
+        }
+        async fn add_subscription_event(
+            &self,
+            subscription: &StoredSubscription,
+            event: &str,
+        ) -> Result<()> {
+            self.base.add_subscription_event(subscription, event).await
+        }
+        async fn update_subscription(
+            &self,
+            subscription: &StoredSubscription,
+        ) -> Result<Option<StoredSubscription>> {
+            self.base.update_subscription(subscription).await
+        }
+        async fn claim_webhook(
+            &self,
+            delivery: WebhookDelivery,
+            stale_before: i64,
+        ) -> Result<bool> {
+            self.base.claim_webhook(delivery, stale_before).await
+        }
+        async fn put_webhook(&self, delivery: WebhookDelivery) -> Result<()> {
+            self.base.put_webhook(delivery).await
+        }
+        async fn get_webhook_log(&self) -> Result<StoredWebhookLog> {
+            self.base.get_webhook_log().await
+        }
+        async fn put_webhook_log(&self, log: StoredWebhookLog) -> Result<()> {
+            self.base.put_webhook_log(log).await
+        }
+        async fn get_voucher(&self, code: &str) -> Result<StoredVoucher> {
+            self.base.get_voucher(code).await
+        }
+        async fn put_voucher(&self, voucher: StoredVoucher) -> Result<()> {
+            self.base.put_voucher(voucher).await
+        }
+        async fn claim_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<bool> {
+            self.base.claim_poll_vote(event, poll, voter).await
+        }
+        async fn release_poll_vote(&self, event: &str, poll: i64, voter: &str) -> Result<()> {
+            self.base.release_poll_vote(event, poll, voter).await
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_db_conflicts {
+    use super::test_db_base::DelegatingDB;
     use super::*;
-    use crate::eventsdb::{ApiEventInfo, EventEntry, EventsDB};
-    use crate::payment::Payment;
+    use crate::eventsdb::{ApiEventInfo, EventEntry};
+    use crate::payment::FakePayment;
     use crate::tracking::Tracking;
     use crate::utils::timestamp_now;
     use crate::viewers::MockViewers;
-    use crate::{app::App, pubsub::PubSubInMemory};
-    use async_trait::async_trait;
+    use crate::{app::App, pubsub::PubSubInMemory, throttle::InMemoryThrottle};
     use axum::{
         Router,
         body::Body,
@@ -264,39 +753,42 @@ mod test_db_conflicts {
     use tower::util::ServiceExt;
     use tower_http::trace::TraceLayer;
 
-    #[derive(Default)]
-    pub struct ConflictDB;
-    #[async_trait]
-    impl EventsDB for ConflictDB {
-        async fn get(&self, key: &str) -> crate::eventsdb::Result<EventEntry> {
-            tracing::info!("fake db get: {key}");
-            Ok(EventEntry {
-                event: ApiEventInfo {
-                    questions: vec![QuestionItem {
-                        id: 1,
+    /// events always load, every write of them conflicts
+    fn conflict_db() -> DelegatingDB {
+        DelegatingDB {
+            get: Some(|key| {
+                tracing::info!("fake db get: {key}");
+                Ok(EventEntry {
+                    event: ApiEventInfo {
+                        questions: vec![QuestionItem {
+                            id: 1,
+                            ..Default::default()
+                        }],
+                        create_time_unix: timestamp_now(),
                         ..Default::default()
-                    }],
-                    create_time_unix: timestamp_now(),
-                    ..Default::default()
-                },
-                version: 1,
-                ttl: None,
-            })
-        }
-        async fn put(&self, event: EventEntry) -> crate::eventsdb::Result<()> {
-            tracing::info!("fake db put: {}", event.event.tokens.public_token);
-            Err(crate::eventsdb::Error::Concurrency)
+                    },
+                    version: 1,
+                    ttl: None,
+                })
+            }),
+            put: Some(|event| {
+                tracing::info!("fake db put: {}", event.event.tokens.public_token);
+                Err(crate::eventsdb::Error::Concurrency)
+            }),
+            ..DelegatingDB::default()
         }
     }
 
     fn app() -> Router {
         let app = Arc::new(App::new(
-            Arc::new(ConflictDB::default()),
+            Arc::new(conflict_db()),
             Arc::new(PubSubInMemory::default()),
             Arc::new(MockViewers::new()),
-            Arc::new(Payment::default()),
+            Arc::new(InMemoryThrottle::default()),
+            Arc::new(FakePayment::default()),
             Tracking::default(),
             String::new(),
+            false,
         ));
 
         Router::new()
@@ -334,17 +826,12 @@ mod test_db_conflicts {
 
 #[cfg(test)]
 mod test_db_item_not_found {
+    use super::test_db_base::DelegatingDB;
     use super::*;
     use crate::{
-        app::App,
-        auth,
-        eventsdb::{EventEntry, EventsDB, InMemoryEventsDB},
-        payment::Payment,
-        pubsub::PubSubInMemory,
-        tracking::Tracking,
-        viewers::MockViewers,
+        app::App, auth, eventsdb::InMemoryEventsDB, payment::FakePayment, pubsub::PubSubInMemory,
+        throttle::InMemoryThrottle, tracking::Tracking, viewers::MockViewers,
     };
-    use async_trait::async_trait;
     use axum::{
         Extension, Router,
         body::Body,
@@ -359,15 +846,12 @@ mod test_db_item_not_found {
     use tower::util::ServiceExt;
     use tower_http::trace::TraceLayer;
 
-    #[derive(Default)]
-    pub struct ItemNotFoundDB;
-    #[async_trait]
-    impl EventsDB for ItemNotFoundDB {
-        async fn get(&self, _key: &str) -> crate::eventsdb::Result<EventEntry> {
-            Err(crate::eventsdb::Error::ItemNotFound)
-        }
-        async fn put(&self, _event: EventEntry) -> crate::eventsdb::Result<()> {
-            Ok(())
+    /// no event is found, every write of one succeeds
+    fn item_not_found_db() -> DelegatingDB {
+        DelegatingDB {
+            get: Some(|_| Err(crate::eventsdb::Error::ItemNotFound)),
+            put: Some(|_| Ok(())),
+            ..DelegatingDB::default()
         }
     }
 
@@ -376,12 +860,14 @@ mod test_db_item_not_found {
     async fn test_db_item_not_found() {
         let router = {
             let app = Arc::new(App::new(
-                Arc::new(ItemNotFoundDB::default()),
+                Arc::new(item_not_found_db()),
                 Arc::new(PubSubInMemory::default()),
                 Arc::new(MockViewers::new()),
-                Arc::new(Payment::default()),
+                Arc::new(InMemoryThrottle::default()),
+                Arc::new(FakePayment::default()),
                 Tracking::default(),
                 String::new(),
+                false,
             ));
 
             let auth = auth::setup_test();
@@ -417,9 +903,11 @@ mod test_db_item_not_found {
                 events.clone(),
                 Arc::new(PubSubInMemory::default()),
                 Arc::new(MockViewers::new()),
-                Arc::new(Payment::default()),
+                Arc::new(InMemoryThrottle::default()),
+                Arc::new(FakePayment::default()),
                 Tracking::default(),
                 String::new(),
+                false,
             ));
             let auth = auth::setup_test();
             let router = Router::new()
@@ -440,6 +928,8 @@ mod test_db_item_not_found {
                 moderator_email: None,
                 test: false,
                 customer: None,
+                template: None,
+                no_third_parties: false,
             })
             .await
             .unwrap();
@@ -468,9 +958,11 @@ mod test_db_item_not_found {
                 events.clone(),
                 Arc::new(PubSubInMemory::default()),
                 Arc::new(MockViewers::new()),
-                Arc::new(Payment::default()),
+                Arc::new(InMemoryThrottle::default()),
+                Arc::new(FakePayment::default()),
                 Tracking::default(),
                 String::new(),
+                false,
             ));
             let auth = auth::setup_test();
             let router = Router::new()
@@ -492,6 +984,8 @@ mod test_db_item_not_found {
                 moderator_email: None,
                 test: false,
                 customer: None,
+                template: None,
+                no_third_parties: false,
             })
             .await
             .unwrap();
@@ -549,4 +1043,105 @@ mod test_db_item_not_found {
 
         assert!(!response.flags.contains(EventResponseFlags::WRONG_PASSWORD));
     }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_mod_auth() {
+        let (app, router) = {
+            let app = Arc::new(App::new(
+                Arc::new(InMemoryEventsDB::default()),
+                Arc::new(PubSubInMemory::default()),
+                Arc::new(MockViewers::new()),
+                Arc::new(InMemoryThrottle::default()),
+                Arc::new(FakePayment::default()),
+                Tracking::default(),
+                String::new(),
+                false,
+            ));
+            let mod_routes = Router::new()
+                .route("/:id", get(mod_get_event))
+                .route("/:id/session", post(mod_session))
+                .merge(legacy::routes());
+            let router = Router::new()
+                .nest("/api/mod/event", mod_routes)
+                .layer(Extension(auth::setup_test()))
+                .layer(TraceLayer::new_for_http())
+                .with_state(app.clone());
+            (app, router)
+        };
+
+        let e = app
+            .create_event(shared::AddEvent {
+                data: shared::EventData {
+                    name: TEST_EVENT_NAME.into(),
+                    description: TEST_EVENT_DESC.into(),
+                    ..Default::default()
+                },
+                moderator_email: None,
+                test: false,
+                customer: None,
+                template: None,
+                no_third_parties: false,
+            })
+            .await
+            .unwrap();
+
+        let id = e.tokens.public_token.clone();
+        let secret = e.tokens.moderator_token.clone().unwrap();
+        let bearer = http::HeaderValue::from_str(&format!("Bearer {secret}")).unwrap();
+
+        let server = TestServer::new_with_config(
+            router,
+            TestServerConfig::builder()
+                .default_content_type("application/json")
+                .save_cookies()
+                .build(),
+        )
+        .unwrap();
+
+        // no secret at all
+        let response = server.get(&format!("/api/mod/event/{id}")).await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+        // wrong secret
+        let response = server
+            .post(&format!("/api/mod/event/{id}/session"))
+            .add_header(http::header::AUTHORIZATION, "Bearer wrong".parse().unwrap())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+        // secret as bearer token
+        let response = server
+            .get(&format!("/api/mod/event/{id}"))
+            .add_header(http::header::AUTHORIZATION, bearer.clone())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert!(
+            response
+                .json::<shared::GetEventResponse>()
+                .mod_info
+                .is_some()
+        );
+
+        // exchanged once for a grant cookie, later requests carry no secret at all
+        let response = server
+            .post(&format!("/api/mod/event/{id}/session"))
+            .add_header(http::header::AUTHORIZATION, bearer)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let response = server.get(&format!("/api/mod/event/{id}")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert!(
+            response
+                .json::<shared::GetEventResponse>()
+                .mod_info
+                .is_some()
+        );
+
+        // the secret in the path still works but is flagged as deprecated
+        let response = server.get(&format!("/api/mod/event/{id}/{secret}")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.header("deprecation"), "true");
+    }
 }
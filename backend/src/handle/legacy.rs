@@ -0,0 +1,113 @@
+//! Moderator routes carrying the moderator secret in the URL path. Deprecated: the path ends up
+//! in access logs, proxies and browser history. Kept until old clients have moved to the
+//! header / grant cookie based routes in [`super`].
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderValue, Request},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use tracing::instrument;
+
+use crate::{app::SharedApp, auth::OptionalUser, error::InternalError};
+
+#[rustfmt::skip]
+pub fn routes() -> Router<SharedApp> {
+    Router::new()
+        .route("/:id/:secret", get(mod_get_event))
+        .route("/upgrade/:id/:secret", post(mod_premium_upgrade))
+        .route("/delete/:id/:secret", get(mod_delete_event))
+        .route("/question/:id/:secret/:question_id", get(mod_get_question))
+        .route("/questionmod/:id/:secret/:question_id", post(mod_edit_question))
+        .route("/:id/:secret", post(mod_edit_event))
+        .route_layer(middleware::from_fn(deprecation))
+}
+
+/// Flags every response with the (RFC 9745) `Deprecation` header.
+async fn deprecation<B>(request: Request<B>, next: Next<B>) -> Response {
+    // the path holds the secret, so only note that a legacy route was hit
+    tracing::warn!("deprecated moderator route");
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert("deprecation", HeaderValue::from_static("true"));
+    response
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_get_event(
+    Path((id, secret)): Path<(String, String)>,
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_get_event");
+
+    Ok(Json(
+        app.get_event(id, Some(secret), user.is_some(), None)
+            .await?,
+    ))
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_delete_event(
+    Path((id, secret)): Path<(String, String)>,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_delete_event");
+
+    Ok(Json(app.delete_event(id, secret).await?))
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_premium_upgrade(
+    Path((id, secret)): Path<(String, String)>,
+    OptionalUser(user): OptionalUser,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModRequestPremium>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_premium_upgrade");
+
+    Ok(Json(
+        app.request_premium_upgrade(id, secret, user.is_some(), payload)
+            .await?,
+    ))
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_get_question(
+    Path((id, secret, question_id)): Path<(String, String, i64)>,
+    State(app): State<SharedApp>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_get_question");
+
+    Ok(Json(app.get_question(id, Some(secret), question_id).await?))
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_edit_question(
+    Path((id, secret, question_id)): Path<(String, String, i64)>,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModQuestion>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_edit_question");
+
+    Ok(Json(
+        app.mod_edit_question(id, secret, question_id, payload)
+            .await?,
+    ))
+}
+
+#[instrument(skip(app, secret))]
+async fn mod_edit_event(
+    Path((id, secret)): Path<(String, String)>,
+    State(app): State<SharedApp>,
+    Json(payload): Json<shared::ModEvent>,
+) -> std::result::Result<impl IntoResponse, InternalError> {
+    tracing::info!("mod_edit_state");
+
+    Ok(Json(app.mod_edit_event(id, secret, payload).await?))
+}
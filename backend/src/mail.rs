@@ -9,6 +9,7 @@ use crate::{aws_ses_client, ses};
 pub struct MailConfig;
 
 const MAIL_TEMPLATE: &str = include_str!("../mail_template.html.hbs");
+const LOGIN_MAIL_TEMPLATE: &str = include_str!("../login_mail_template.html.hbs");
 
 impl MailConfig {
     pub const fn new() -> Self {
@@ -40,16 +41,49 @@ impl MailConfig {
         event_name: String,
         public_link: String,
         mod_link: String,
+        subject: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         tracing::info!("mail::send_mail: {event_id}");
 
         let content = Self::create_mail(&event_name, &public_link, &mod_link)?;
 
+        let client = aws_ses_client().await?;
+        let response =
+            ses::send_message(&client, &[receiver], subject, &content, "mail@live-ask.com").await?;
+
+        tracing::info!("mail sent response: {:?}", response);
+
+        Ok(())
+    }
+
+    fn create_login_mail(
+        login_link: &str,
+    ) -> std::result::Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut hb = Handlebars::new();
+        hb.register_template_string("template", LOGIN_MAIL_TEMPLATE)?;
+
+        let mut data: HashMap<&str, &str> = HashMap::with_capacity(1);
+        data.insert("login_link", login_link);
+        let content = hb.render("template", &data)?;
+
+        Ok(content)
+    }
+
+    #[instrument(err, skip_all)]
+    pub async fn send_login_mail(
+        &self,
+        receiver: String,
+        login_link: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tracing::info!("mail::send_login_mail");
+
+        let content = Self::create_login_mail(&login_link)?;
+
         let client = aws_ses_client().await?;
         let response = ses::send_message(
             &client,
             &[receiver],
-            "New Event Created",
+            "Your Live-Ask Login",
             &content,
             "mail@live-ask.com",
         )
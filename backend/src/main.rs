@@ -2,6 +2,7 @@
 
 mod app;
 mod auth;
+mod content_filter;
 mod ecs_task_id;
 mod env;
 mod error;
@@ -9,11 +10,15 @@ mod eventsdb;
 mod handle;
 mod mail;
 mod payment;
+mod privacy;
 mod pubsub;
 mod redis_pool;
 mod ses;
+mod shortener;
 mod signals;
+mod similarity;
 mod stripe_webhooks;
+mod throttle;
 mod tracking;
 mod utils;
 mod viewers;
@@ -39,14 +44,15 @@ use crate::{
     app::App,
     auth::{admin_user_handler, login_handler, logout_handler},
     ecs_task_id::server_id,
-    env::session_secret,
+    env::{previous_session_secrets, session_secret},
     error::Result,
     eventsdb::DynamoEventsDB,
     handle::{push_handler, subscription_handler, subscription_url_handler},
-    payment::Payment,
+    payment::{FakePayment, FakeWebhook, PaymentProvider, StripePayment},
     pubsub::PubSubRedis,
     redis_pool::{create_pool, ping_test_redis},
-    tracking::Tracking,
+    throttle::RedisThrottle,
+    tracking::{SinkConfig, Tracking},
     viewers::RedisViewers,
 };
 
@@ -85,7 +91,7 @@ fn setup_cors() -> CorsLayer {
                 .allow_origin(origins)
                 .allow_credentials(true)
                 .allow_methods([Method::GET, Method::POST])
-                .allow_headers([header::CONTENT_TYPE])
+                .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
         }
     }
 }
@@ -118,6 +124,10 @@ fn use_local_db() -> bool {
     std::env::var(env::ENV_DB_LOCAL).is_ok()
 }
 
+fn use_fake_payment() -> bool {
+    std::env::var(env::ENV_PAYMENT_FAKE).is_ok_and(|var| var == "1")
+}
+
 fn production_env() -> String {
     std::env::var(env::ENV_ENV).unwrap_or_else(|_| String::from("local"))
 }
@@ -162,6 +172,39 @@ fn posthog_key() -> Option<String> {
     std::env::var(env::ENV_POSTHOG_KEY).ok()
 }
 
+/// Analytics sink from `LA_TRACKING_SINK`, unset keeps the old behaviour of sending to posthog
+/// if a key is configured (and not in privacy mode).
+fn tracking_sink() -> Result<SinkConfig> {
+    let var = |name: &str| std::env::var(name).ok().filter(|var| !var.is_empty());
+
+    Ok(match var(env::ENV_TRACKING_SINK).as_deref() {
+        None if env::privacy_mode() => SinkConfig::None,
+        None => posthog_key().map_or(SinkConfig::None, |key| SinkConfig::PostHog { key }),
+        Some("none") => SinkConfig::None,
+        Some("posthog") if env::privacy_mode() => bail!("posthog tracking in privacy mode"),
+        Some("posthog") => {
+            let Some(key) = posthog_key() else {
+                bail!("posthog tracking without {}", env::ENV_POSTHOG_KEY)
+            };
+            SinkConfig::PostHog { key }
+        }
+        Some("jsonl") => {
+            let Some(path) = var(env::ENV_TRACKING_FILE) else {
+                bail!("jsonl tracking without {}", env::ENV_TRACKING_FILE)
+            };
+            SinkConfig::Jsonl { path: path.into() }
+        }
+        Some("stdout") => SinkConfig::Stdout,
+        Some("webhook") => {
+            let Some(url) = var(env::ENV_TRACKING_WEBHOOK_URL) else {
+                bail!("webhook tracking without {}", env::ENV_TRACKING_WEBHOOK_URL)
+            };
+            SinkConfig::Webhook { url }
+        }
+        Some(sink) => bail!("unknown tracking sink: {sink}"),
+    })
+}
+
 fn stripe_secret() -> String {
     std::env::var(env::ENV_STRIPE_SECRET).unwrap_or_else(|_| String::new())
 }
@@ -197,10 +240,29 @@ async fn dynamo_client() -> Result<aws_sdk_dynamodb::Client> {
     Ok(Client::new(&config))
 }
 
-async fn payment() -> Result<Arc<Payment>> {
+async fn payment() -> Result<Arc<dyn PaymentProvider>> {
     let is_test = !is_prod();
+
+    if use_fake_payment() {
+        // the fake hands out premium for free, a stray env var must not open prod up
+        if !is_test {
+            bail!("fake payment in prod")
+        }
+
+        tracing::warn!("using fake payment provider");
+
+        // checkouts complete by a webhook to ourselves, as stripe would deliver it
+        let webhook = Some(FakeWebhook {
+            url: format!("http://localhost:{}/api/payment/stripe/webhook", get_port()),
+            secret: std::env::var(env::ENV_STRIPE_HOOK_SECRET).unwrap_or_default(),
+        })
+        .filter(|webhook| !webhook.secret.is_empty());
+
+        return Ok(Arc::new(FakePayment::new(webhook)));
+    }
+
     let secret = stripe_secret();
-    let mut payment = Payment::new(secret.clone());
+    let mut payment = StripePayment::new(secret.clone());
 
     match payment.authenticate(!is_test).await {
         Err(e) => {
@@ -233,13 +295,14 @@ async fn setup_app(
 
     let server_id = server_id().await.unwrap_or_else(|| "server".to_string());
 
-    let posthog_key = posthog_key();
+    let tracking_sink = tracking_sink()?;
 
     tracing::info!(
         git= %GIT_HASH,
         env= prod_env,
         is_prod= is_prod(),
-        posthog_key = posthog_key.is_some(),
+        tracking = tracking_sink.name(),
+        privacy_mode = env::privacy_mode(),
         log_level,
         redis_url,
         base_url,
@@ -247,9 +310,14 @@ async fn setup_app(
         "server-starting",
     );
 
-    let tracking = Tracking::new(posthog_key, server_id.clone(), prod_env.to_string());
+    let tracking = Tracking::new(
+        tracking_sink,
+        server_id.clone(),
+        prod_env.to_string(),
+        env::privacy_mode(),
+    )?;
 
-    tracking.track_server_start().await?;
+    tracking.track_server_start();
 
     let redis_pool = create_pool(redis_url)?;
     ping_test_redis(&redis_pool).await?;
@@ -257,6 +325,7 @@ async fn setup_app(
     let payment = payment().await?;
 
     let pubsub = Arc::new(PubSubRedis::new(redis_pool.clone(), redis_url.to_string()));
+    let throttle = Arc::new(RedisThrottle::new(redis_pool.clone()));
     let viewers = Arc::new(RedisViewers::new(redis_pool));
 
     let eventsdb = Arc::new(DynamoEventsDB::new(dynamo_client().await?, use_local_db()).await?);
@@ -264,9 +333,11 @@ async fn setup_app(
         eventsdb,
         Arc::<PubSubRedis>::clone(&pubsub),
         viewers,
+        throttle,
         payment,
         tracking,
         base_url,
+        env::privacy_mode(),
     ));
 
     pubsub.set_receiver(Arc::<App>::clone(&app)).await;
@@ -282,15 +353,23 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     let prod_env = production_env();
 
+    let mut sentry_options = sentry::ClientOptions {
+        release: Some(GIT_HASH.into()),
+        attach_stacktrace: true,
+        traces_sample_rate: if is_debug() { 1.0 } else { 0.0 },
+        environment: Some(prod_env.clone().into()),
+        ..Default::default()
+    };
+
+    // breadcrumbs may quote event content
+    if env::privacy_mode() {
+        sentry_options.max_breadcrumbs = 0;
+        sentry_options.before_send = Some(Arc::new(privacy::scrub_breadcrumbs));
+    }
+
     let _guard = sentry::init((
         std::env::var(env::ENV_SENTRY_DSN).unwrap_or_default(),
-        sentry::ClientOptions {
-            release: Some(GIT_HASH.into()),
-            attach_stacktrace: true,
-            traces_sample_rate: if is_debug() { 1.0 } else { 0.0 },
-            environment: Some(prod_env.clone().into()),
-            ..Default::default()
-        },
+        sentry_options,
     ));
 
     let sentry_layer = if is_prod() {
@@ -318,39 +397,78 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let secret = session_secret()
         .ok_or_else(|| error::InternalError::General(String::from("invalid session secret")))?;
 
-    // auth is now a stateless JWT: knowing the signing key is enough to forge an admin token,
+    let previous_secrets = previous_session_secrets().ok_or_else(|| {
+        error::InternalError::General(String::from("invalid previous session secret"))
+    })?;
+
+    // auth is now a stateless JWT: knowing any accepted key is enough to forge an admin token,
     // so refuse to start on any public (non-local) env with the well-known dev fallback.
-    if !is_local() && env::is_default_session_secret(&secret) {
+    if !is_local()
+        && once(&secret)
+            .chain(&previous_secrets)
+            .any(|secret| env::is_default_session_secret(secret))
+    {
         return Err(
             "LA_SESSION_SECRET must be set to a non-default value outside local dev".into(),
         );
     }
 
+    tracing::info!(
+        previous_keys = previous_secrets.len(),
+        "session keys loaded"
+    );
+
     // harden cookies (SameSite=Strict) on every public env, not just prod
-    let auth_config = auth::setup(secret, !is_local());
+    let auth_config = auth::setup(secret, previous_secrets, !is_local());
 
     let admin_routes = Router::new()
         .route("/user", get(admin_user_handler))
         .route("/login", post(login_handler))
-        .route("/logout", get(logout_handler));
+        .route("/logout", get(logout_handler))
+        .route("/webhooks", get(handle::admin_webhooks_handler))
+        .route(
+            "/event/:id/invoice",
+            post(handle::admin_invoice_paid_handler),
+        )
+        .route("/voucher", post(handle::admin_create_voucher_handler))
+        .route("/voucher/:code", get(handle::admin_voucher_handler));
 
     let event_routes = Router::new()
         .route("/:id", get(handle::getevent_handler))
         .route("/:id/pwd", post(handle::set_event_password))
+        .route("/:id/recover", post(handle::recover_mod_link_handler))
         .route("/add", post(handle::addevent_handler))
         .route("/editlike/:id", post(handle::editlike_handler))
         .route("/addquestion/:id", post(handle::addquestion_handler))
-        .route("/question/:id/:question_id", get(handle::get_question));
+        .route("/question/:id/:question_id", get(handle::get_question))
+        .route("/:id/poll/:poll_id/vote", post(handle::vote_poll_handler));
 
     #[rustfmt::skip]
     let mod_routes = Router::new()
-        .route("/:id/:secret", get(handle::mod_get_event))
-        .route("/upgrade/:id/:secret", post(handle::mod_premium_upgrade))
+        .route("/:id", get(handle::mod_get_event))
+        .route("/:id", post(handle::mod_edit_event))
+        .route("/:id/session", post(handle::mod_session))
+        .route("/:id/upgrade", post(handle::mod_premium_upgrade))
+        .route("/:id/voucher", post(handle::mod_redeem_voucher))
+        .route("/:id/delete", get(handle::mod_delete_event))
+        .route("/:id/question/:question_id", get(handle::mod_get_question))
+        .route("/:id/question/:question_id", post(handle::mod_edit_question))
+        .route("/:id/question/:question_id/merge", post(handle::mod_merge_question))
+        .route("/:id/clone", post(handle::mod_clone_event))
+        .route("/:id/template", post(handle::mod_save_template))
+        .route("/:id/poll", post(handle::mod_add_poll))
+        .route("/:id/poll/:poll_id", post(handle::mod_edit_poll))
         .route("/capture/:id/:order", get(handle::mod_premium_capture))
-        .route("/delete/:id/:secret", get(handle::mod_delete_event))
-        .route("/question/:id/:secret/:question_id", get(handle::mod_get_question))
-        .route("/questionmod/:id/:secret/:question_id", post(handle::mod_edit_question))
-        .route("/:id/:secret", post(handle::mod_edit_event));
+        .merge(handle::legacy::routes());
+
+    #[rustfmt::skip]
+    let organizer_routes = Router::new()
+        .route("/login", post(handle::organizer_login_handler))
+        .route("/verify", post(handle::organizer_verify_handler))
+        .route("/logout", get(handle::organizer_logout_handler))
+        .route("/events", get(handle::organizer_events_handler))
+        .route("/events/:id", post(handle::organizer_add_event_handler))
+        .route("/events/:id/remove", post(handle::organizer_remove_event_handler));
 
     let (prometheus_layer, metrics_handler) = axum_prometheus::PrometheusMetricLayer::pair();
 
@@ -365,9 +483,10 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         .nest("/api/event", event_routes)
         .nest("/api/mod/event", mod_routes)
         .nest("/api/admin", admin_routes)
+        .nest("/api/organizer", organizer_routes)
         .route("/metrics", get(async move || metrics_handler.render()))
         .layer(Extension(auth_config))
-        .layer(SetSensitiveRequestHeadersLayer::new(once(header::COOKIE)))
+        .layer(SetSensitiveRequestHeadersLayer::new([header::COOKIE, header::AUTHORIZATION]))
         .layer(SentryHttpLayer::with_transaction())
         .layer(NewSentryLayer::new_from_top())
         .layer(TraceLayer::new_for_http())
@@ -0,0 +1,279 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+use tracing::instrument;
+use ulid::Ulid;
+
+use crate::{eventsdb::PremiumOrder, utils::timestamp_now};
+
+use super::{ORDER_ID_PLACEHOLDER, OrderState, PaymentError, PaymentProvider, PaymentResult};
+
+/// Recorded stripe payload the fake checkout completion is derived from.
+const CHECKOUT_COMPLETED: &str =
+    include_str!("../../test-data/stripe/checkout_session_completed.json");
+
+/// Where [`FakePayment`] delivers the webhooks stripe would send, signed with `secret` like
+/// stripe signs them.
+#[derive(Clone, Debug)]
+pub struct FakeWebhook {
+    pub url: String,
+    pub secret: String,
+}
+
+struct FakeCustomer {
+    email: String,
+    subscription: String,
+}
+
+/// [`PaymentProvider`] that never leaves the process: every checkout is paid right away and,
+/// with a [`FakeWebhook`], reported by a signed `checkout.session.completed` webhook.
+#[derive(Default)]
+pub struct FakePayment {
+    webhook: Option<FakeWebhook>,
+    /// checkout session -> event
+    sessions: Mutex<HashMap<String, String>>,
+    customers: Mutex<HashMap<String, FakeCustomer>>,
+}
+
+impl FakePayment {
+    pub fn new(webhook: Option<FakeWebhook>) -> Self {
+        Self {
+            webhook,
+            ..Self::default()
+        }
+    }
+
+    /// Add a customer with an active subscription, returns the customer id.
+    pub async fn add_subscriber(&self, email: &str) -> String {
+        let customer = format!("cus_fake{}", Ulid::new());
+
+        self.customers.lock().await.insert(
+            customer.clone(),
+            FakeCustomer {
+                email: email.to_string(),
+                subscription: format!("sub_fake{}", Ulid::new()),
+            },
+        );
+
+        customer
+    }
+
+    fn checkout_completed(session: &str, event: &str) -> PaymentResult<String> {
+        let mut payload: serde_json::Value = serde_json::from_str(CHECKOUT_COMPLETED)
+            .map_err(|e| PaymentError::Generic(e.to_string()))?;
+
+        payload["id"] = format!("evt_fake{}", Ulid::new()).into();
+        payload["created"] = timestamp_now().into();
+        payload["data"]["object"]["id"] = session.into();
+        payload["data"]["object"]["client_reference_id"] = event.into();
+
+        Ok(payload.to_string())
+    }
+
+    #[instrument(skip(webhook, payload))]
+    async fn deliver(webhook: FakeWebhook, payload: String) {
+        let signature = match sign_webhook(&webhook.secret, &payload, timestamp_now()) {
+            Ok(signature) => signature,
+            Err(e) => {
+                tracing::error!("[fake-payment] signing webhook failed: {e}");
+                return;
+            }
+        };
+
+        let res = reqwest::Client::new()
+            .post(&webhook.url)
+            .header("stripe-signature", signature)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(res) if res.status().is_success() => {
+                tracing::info!("[fake-payment] webhook delivered");
+            }
+            Ok(res) => tracing::warn!("[fake-payment] webhook refused: {}", res.status()),
+            Err(e) => tracing::error!("[fake-payment] webhook delivery failed: {e}"),
+        }
+    }
+}
+
+/// `stripe-signature` header value for `payload`, see
+/// <https://docs.stripe.com/webhooks#verify-manually>.
+pub fn sign_webhook(secret: &str, payload: &str, timestamp: i64) -> PaymentResult<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| PaymentError::Generic(e.to_string()))?;
+
+    mac.update(format!("{timestamp}.{payload}").as_bytes());
+
+    Ok(format!(
+        "t={timestamp},v1={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+#[async_trait]
+impl PaymentProvider for FakePayment {
+    async fn customer_portal_url(
+        &self,
+        _customer_id: &str,
+        return_url: &str,
+    ) -> PaymentResult<String> {
+        Ok(return_url.to_string())
+    }
+
+    async fn subscription_customer_by_email(&self, email: &str) -> PaymentResult<String> {
+        self.customers
+            .lock()
+            .await
+            .iter()
+            .find(|(_, customer)| customer.email == email)
+            .map(|(id, _)| id.clone())
+            .ok_or_else(|| {
+                PaymentError::Generic(String::from(
+                    "no active subscription customer found for email",
+                ))
+            })
+    }
+
+    async fn customer_email(&self, customer: &str) -> PaymentResult<Option<String>> {
+        Ok(self
+            .customers
+            .lock()
+            .await
+            .get(customer)
+            .map(|customer| customer.email.clone()))
+    }
+
+    async fn payment_intent_event(&self, payment_intent: &str) -> PaymentResult<Option<String>> {
+        let Some(session) = payment_intent.strip_prefix("pi_") else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .sessions
+            .lock()
+            .await
+            .get(&format!("cs_{session}"))
+            .cloned())
+    }
+
+    async fn checkout_payment_intent(&self, session_id: &str) -> PaymentResult<Option<String>> {
+        // fake payment intents are named after the session they paid
+        Ok(session_id
+            .strip_prefix("cs_")
+            .map(|session| format!("pi_{session}")))
+    }
+
+    async fn verify_customer(&self, customer_id: &str) -> PaymentResult<String> {
+        self.customers
+            .lock()
+            .await
+            .get(customer_id)
+            .map(|customer| customer.subscription.clone())
+            .ok_or_else(|| PaymentError::Generic(String::from("no active subscription found")))
+    }
+
+    #[instrument(skip(self))]
+    async fn create_order(
+        &self,
+        event: &str,
+        _mod_url: &str,
+        return_url: &str,
+    ) -> PaymentResult<String> {
+        let session = format!("cs_test_fake{}", Ulid::new());
+
+        self.sessions
+            .lock()
+            .await
+            .insert(session.clone(), event.to_string());
+
+        if let Some(webhook) = &self.webhook {
+            let payload = Self::checkout_completed(&session, event)?;
+
+            tokio::spawn(Self::deliver(webhook.clone(), payload));
+        }
+
+        Ok(return_url.replace(ORDER_ID_PLACEHOLDER, &session))
+    }
+
+    async fn verify_order(&self, order: &str) -> PaymentResult<OrderState> {
+        self.sessions
+            .lock()
+            .await
+            .get(order)
+            .map(|event| OrderState {
+                event: event.clone(),
+                paid: true,
+            })
+            .ok_or_else(|| PaymentError::Generic(String::from("unknown checkout session")))
+    }
+
+    fn premium_order(&self, session_id: String) -> PremiumOrder {
+        // stands in for stripe, so everything downstream treats it the same
+        PremiumOrder::StripeSessionId(session_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use stripe::{EventObject, EventType, Webhook};
+
+    #[tokio::test]
+    async fn test_checkout() {
+        let payment = FakePayment::default();
+
+        let url = payment
+            .create_order("ev1", "https://mod", "https://mod?token={ORDER_ID}")
+            .await
+            .unwrap();
+
+        let session = url.strip_prefix("https://mod?token=").unwrap().to_string();
+
+        assert_eq!(
+            payment.verify_order(&session).await.unwrap(),
+            OrderState {
+                event: String::from("ev1"),
+                paid: true,
+            }
+        );
+
+        let payment_intent = payment
+            .checkout_payment_intent(&session)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            payment.payment_intent_event(&payment_intent).await.unwrap(),
+            Some(String::from("ev1"))
+        );
+        assert_eq!(
+            payment.payment_intent_event("pi_other").await.unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_signed_webhook() {
+        let secret = "whsec_test";
+        let payload = FakePayment::checkout_completed("cs_test_fake1", "ev1").unwrap();
+        let signature = sign_webhook(secret, &payload, timestamp_now()).unwrap();
+
+        let event = Webhook::construct_event(&payload, &signature, secret).unwrap();
+
+        assert_eq!(event.type_, EventType::CheckoutSessionCompleted);
+        let session = match event.data.object {
+            EventObject::CheckoutSession(session) => Some(session),
+            _ => None,
+        }
+        .unwrap();
+        assert_eq!(session.id.as_str(), "cs_test_fake1");
+        assert_eq!(session.client_reference_id.as_deref(), Some("ev1"));
+
+        assert!(Webhook::construct_event(&payload, &signature, "whsec_other").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use sentry::{Hub, protocol::Event};
+
+/// Drop the breadcrumbs of an error reported to sentry, they may quote event content.
+#[allow(clippy::unnecessary_wraps)]
+pub fn scrub_breadcrumbs(mut event: Event<'static>) -> Option<Event<'static>> {
+    event.breadcrumbs.values.clear();
+    Some(event)
+}
+
+/// Scrub the breadcrumbs of whatever the current request reports to sentry, for events that
+/// opted out of third parties. Outside of a request (with its own hub) this does nothing, the
+/// process wide scope is left alone.
+pub fn scrub_request_scope() {
+    if Arc::ptr_eq(&Hub::current(), &Hub::main()) {
+        return;
+    }
+
+    sentry::configure_scope(|scope| {
+        scope.clear_breadcrumbs();
+        scope.add_event_processor(scrub_breadcrumbs);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sentry::protocol::Breadcrumb;
+
+    #[test]
+    fn test_scrub_breadcrumbs() {
+        let mut event = Event::new();
+        event.breadcrumbs.values.push(Breadcrumb {
+            message: Some(String::from("event 'secret meetup' created")),
+            ..Breadcrumb::default()
+        });
+
+        let event = scrub_breadcrumbs(event).unwrap();
+
+        assert!(event.breadcrumbs.values.is_empty());
+    }
+}
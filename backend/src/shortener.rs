@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use std::time::Instant;
+
+use crate::env;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UrlShortener: Send + Sync {
+    /// Short link to `url`, `None` if there is none.
+    async fn shorten(&self, url: &str) -> Option<String>;
+}
+
+/// Shortens links with ezlime, nothing happens without an api key.
+pub struct Ezlime {
+    key: Option<String>,
+}
+
+impl Ezlime {
+    pub fn from_env() -> Self {
+        let key = std::env::var(env::ENV_WEEME_KEY)
+            .ok()
+            .filter(|key| !key.trim().is_empty());
+
+        match &key {
+            Some(key) => tracing::info!("link shortener token set (len: {})", key.trim().len()),
+            None => tracing::warn!("no url shorten token set"),
+        }
+
+        Self { key }
+    }
+}
+
+#[async_trait]
+impl UrlShortener for Ezlime {
+    async fn shorten(&self, url: &str) -> Option<String> {
+        let Some(key) = &self.key else {
+            tracing::info!("no weeme key");
+            return None;
+        };
+
+        let now = Instant::now();
+
+        let ezlime = ezlime_rs::EzlimeApi::new(key.clone());
+
+        match ezlime.create_short_url(url).await {
+            Ok(shortened_url) => {
+                tracing::info!(
+                    "short url: '{}' (in {}ms)",
+                    shortened_url,
+                    now.elapsed().as_millis()
+                );
+                Some(shortened_url)
+            }
+            Err(e) => {
+                tracing::error!("failed to create short url: {}", e);
+                None
+            }
+        }
+    }
+}
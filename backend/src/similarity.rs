@@ -0,0 +1,119 @@
+//! Near-duplicate detection for questions. Texts are compared on their significant words (case,
+//! punctuation and filler words ignored), either as a largely overlapping word set or, to catch
+//! typos, as strings only a few edits apart.
+
+use std::collections::HashSet;
+
+/// Words carrying no meaning of their own for telling questions apart.
+const FILLER_WORDS: &[&str] = &[
+    "a", "about", "an", "and", "any", "are", "can", "could", "do", "does", "for", "how", "i", "in",
+    "is", "it", "of", "on", "or", "please", "the", "there", "this", "to", "what", "when", "where",
+    "which", "who", "why", "will", "would", "you", "your",
+];
+
+/// Share of words two questions have in common (Jaccard) to count as similar, in percent.
+const MIN_WORD_OVERLAP_PERCENT: usize = 75;
+/// Edits allowed between two normalized texts, in percent of the longer one.
+const MAX_EDIT_PERCENT: usize = 10;
+
+pub fn is_similar(a: &str, b: &str) -> bool {
+    let a = significant_words(a);
+    let b = significant_words(b);
+
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    let a_text = a.join(" ");
+    let b_text = b.join(" ");
+
+    if a_text == b_text {
+        return true;
+    }
+
+    let a_set: HashSet<&String> = a.iter().collect();
+    let b_set: HashSet<&String> = b.iter().collect();
+    let common = a_set.intersection(&b_set).count();
+    let total = a_set.union(&b_set).count();
+
+    // a single shared word says nothing on short questions
+    if common >= 2 && common * 100 >= total * MIN_WORD_OVERLAP_PERCENT {
+        return true;
+    }
+
+    let (a_len, b_len) = (a_text.chars().count(), b_text.chars().count());
+    let allowed = a_len.max(b_len) * MAX_EDIT_PERCENT;
+
+    // it takes at least as many edits as the lengths differ, skip the quadratic part then
+    if a_len.abs_diff(b_len) * 100 > allowed {
+        return false;
+    }
+
+    edit_distance(&a_text, &b_text) * 100 <= allowed
+}
+
+fn significant_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !FILLER_WORDS.contains(word))
+        .map(String::from)
+        .collect()
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rewordings_are_similar() {
+        assert!(is_similar(
+            "What about pricing?",
+            "what about the pricing??"
+        ));
+        assert!(is_similar(
+            "When will the new API be released?",
+            "When is the new API released"
+        ));
+        // typo
+        assert!(is_similar(
+            "How do you handle authentication?",
+            "How do you handle authentification?"
+        ));
+    }
+
+    #[test]
+    fn test_different_questions_are_not_similar() {
+        assert!(!is_similar(
+            "What about pricing in Europe?",
+            "What about pricing in Asia?"
+        ));
+        assert!(!is_similar("Why is the sky blue?", "Why is the sea blue?"));
+        assert!(!is_similar("what is this?", "what is this?"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
@@ -4,9 +4,15 @@ use axum::{
     response::{Html, IntoResponse, Response},
 };
 use reqwest::StatusCode;
-use stripe::{CheckoutSessionPaymentStatus, Event, EventObject, EventType};
+use shared::WebhookOutcome;
+use stripe::{CheckoutSession, CheckoutSessionPaymentStatus, Event, EventObject, EventType};
 
-use crate::{app::SharedApp, env, error::InternalError};
+use crate::{
+    app::{App, SharedApp},
+    env,
+    error::InternalError,
+    eventsdb::PremiumIssueReason,
+};
 
 pub struct StripeEvent(Event);
 
@@ -51,46 +57,370 @@ where
     }
 }
 
-// TODO: cleanup
-#[allow(clippy::cognitive_complexity)]
+/// What a stripe event means for the premium status of an event.
+#[derive(Debug, Eq, PartialEq)]
+enum WebhookAction {
+    /// the checkout of `event` is paid, upgrade it
+    Fulfil {
+        session: String,
+        event: String,
+    },
+    /// something went wrong with a payment, see [`App::premium_issue`](crate::app::App::premium_issue)
+    Issue {
+        target: PaymentTarget,
+        reason: PremiumIssueReason,
+        reference: String,
+    },
+    /// a subscription changed status, see [`App::subscription_changed`](crate::app::App::subscription_changed)
+    Subscription {
+        subscription: String,
+        customer: String,
+        status: String,
+        time_unix: i64,
+    },
+    Ignore,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum PaymentTarget {
+    Event(String),
+    /// the charge was not expanded in the payload, its metadata names the event
+    Charge(String),
+    /// the charge carries no event, the checkout session it paid names it
+    PaymentIntent(String),
+}
+
+fn checkout_fulfilment(session: CheckoutSession) -> WebhookAction {
+    // a completed session can still be `Unpaid` for async/delayed payment
+    // methods; only fulfil once Stripe reports the money actually cleared
+    if !matches!(
+        session.payment_status,
+        CheckoutSessionPaymentStatus::Paid | CheckoutSessionPaymentStatus::NoPaymentRequired
+    ) {
+        tracing::warn!(
+            "[hooks] checkout completed but not paid ({:?}); skipping upgrade",
+            session.payment_status
+        );
+        return WebhookAction::Ignore;
+    }
+
+    session
+        .client_reference_id
+        .map_or(WebhookAction::Ignore, |event| WebhookAction::Fulfil {
+            session: session.id.to_string(),
+            event,
+        })
+}
+
+fn webhook_action(event: Event) -> WebhookAction {
+    let created = event.created;
+
+    match (event.type_, event.data.object) {
+        (
+            type_ @ (EventType::CheckoutSessionCompleted
+            | EventType::CheckoutSessionAsyncPaymentSucceeded),
+            EventObject::CheckoutSession(session),
+        ) => {
+            tracing::info!(
+                "[hooks] {:?}: {:?} (payment_status: {:?})",
+                type_,
+                session.id,
+                session.payment_status
+            );
+
+            checkout_fulfilment(session)
+        }
+        (EventType::CheckoutSessionAsyncPaymentFailed, EventObject::CheckoutSession(session)) => {
+            tracing::info!(
+                "[hooks] CheckoutSessionAsyncPaymentFailed: {:?}",
+                session.id
+            );
+
+            session
+                .client_reference_id
+                .map_or(WebhookAction::Ignore, |event| WebhookAction::Issue {
+                    target: PaymentTarget::Event(event),
+                    reason: PremiumIssueReason::AsyncPaymentFailed,
+                    reference: session.id.to_string(),
+                })
+        }
+        (EventType::ChargeRefunded, EventObject::Charge(charge)) => {
+            tracing::info!(
+                "[hooks] ChargeRefunded: {:?} ({} of {})",
+                charge.id,
+                charge.amount_refunded,
+                charge.amount
+            );
+
+            // partial refunds are goodwill, the event keeps what it paid for
+            if !charge.refunded {
+                return WebhookAction::Ignore;
+            }
+
+            // checkout payments always have one, anything else is not about an event
+            let Some(payment_intent) = charge.payment_intent.map(|pi| pi.id().to_string()) else {
+                return WebhookAction::Ignore;
+            };
+
+            let target = charge.metadata.get("event").cloned().map_or_else(
+                || PaymentTarget::PaymentIntent(payment_intent.clone()),
+                PaymentTarget::Event,
+            );
+
+            WebhookAction::Issue {
+                target,
+                reason: PremiumIssueReason::Refunded,
+                reference: payment_intent,
+            }
+        }
+        (EventType::ChargeDisputeCreated, EventObject::Dispute(dispute)) => {
+            tracing::info!(
+                "[hooks] ChargeDisputeCreated: {:?} ({})",
+                dispute.id,
+                dispute.reason
+            );
+
+            let target = dispute
+                .charge
+                .as_object()
+                .and_then(|charge| charge.metadata.get("event").cloned())
+                .map_or_else(
+                    || PaymentTarget::Charge(dispute.charge.id().to_string()),
+                    PaymentTarget::Event,
+                );
+
+            WebhookAction::Issue {
+                target,
+                reason: PremiumIssueReason::Disputed,
+                reference: dispute.id.to_string(),
+            }
+        }
+        (
+            type_ @ (EventType::CustomerSubscriptionUpdated
+            | EventType::CustomerSubscriptionDeleted),
+            EventObject::Subscription(subscription),
+        ) => {
+            tracing::info!(
+                "[hooks] {:?}: {:?} ({:?})",
+                type_,
+                subscription.id,
+                subscription.status
+            );
+
+            WebhookAction::Subscription {
+                subscription: subscription.id.to_string(),
+                customer: subscription.customer.id().to_string(),
+                status: subscription.status.as_str().to_string(),
+                time_unix: created,
+            }
+        }
+        (type_, _) => {
+            tracing::warn!("[hooks] unknown stripe hook: {:?}", type_);
+            WebhookAction::Ignore
+        }
+    }
+}
+
+/// Stripe name of the event type, e.g. `charge.refunded`.
+fn event_kind(type_: EventType) -> String {
+    serde_json::to_value(type_)
+        .ok()
+        .and_then(|kind| kind.as_str().map(ToOwned::to_owned))
+        .unwrap_or_default()
+}
+
 pub async fn handle_webhook(
     State(app): State<SharedApp>,
     StripeEvent(event): StripeEvent,
 ) -> std::result::Result<impl IntoResponse, InternalError> {
-    match event.type_ {
-        EventType::CheckoutSessionCompleted => {
-            if let EventObject::CheckoutSession(session) = event.data.object {
-                tracing::info!(
-                    "[hooks] CheckoutSessionCompleted: {:?} (payment_status: {:?})",
-                    session.id,
-                    session.payment_status
-                );
+    let (id, kind) = (event.id.to_string(), event_kind(event.type_));
+
+    if !app.webhook_claim(&id, &kind).await? {
+        tracing::info!("[hooks] {kind} {id} already processed or in progress");
+        return Ok(Html(""));
+    }
 
-                // a completed session can still be `Unpaid` for async/delayed payment
-                // methods; only fulfil once Stripe reports the money actually cleared
-                if matches!(
-                    session.payment_status,
-                    CheckoutSessionPaymentStatus::Paid
-                        | CheckoutSessionPaymentStatus::NoPaymentRequired
-                ) {
-                    if let Some(event) = session.client_reference_id {
-                        // propagate so a transient failure returns 5xx and Stripe retries,
-                        // instead of silently dropping an upgrade the customer paid for
-                        app.payment_webhook(session.id.to_string(), event).await?;
-                    }
-                } else {
-                    tracing::warn!(
-                        "[hooks] checkout completed but not paid ({:?}); skipping upgrade",
-                        session.payment_status
-                    );
+    let result = process_action(&app, webhook_action(event)).await;
+
+    let outcome = match &result {
+        Ok(outcome) => outcome.clone(),
+        Err(e) => WebhookOutcome::Failed(e.to_string()),
+    };
+
+    // the work is done at this point, a failed record only risks a retry processing it again
+    // once the claim went stale
+    if let Err(e) = app.webhook_processed(id, kind, outcome).await {
+        tracing::error!("[hooks] failed to record delivery: {e}");
+    }
+
+    // errors propagate so a transient failure returns 5xx and Stripe retries, instead of
+    // silently dropping an upgrade the customer paid for (or a refund they got back)
+    result?;
+
+    Ok(Html(""))
+}
+
+async fn process_action(
+    app: &App,
+    action: WebhookAction,
+) -> std::result::Result<WebhookOutcome, InternalError> {
+    match action {
+        WebhookAction::Fulfil { session, event } => {
+            app.payment_webhook(session, event).await?;
+        }
+        WebhookAction::Issue {
+            target,
+            reason,
+            reference,
+        } => {
+            let event = match target {
+                PaymentTarget::Event(event) => Some(event),
+                PaymentTarget::Charge(charge) => app.charge_event(&charge).await?,
+                PaymentTarget::PaymentIntent(payment_intent) => {
+                    app.payment_intent_event(&payment_intent).await?
                 }
+            };
+
+            if let Some(event) = event {
+                app.premium_issue(event, reason, reference).await?;
+            } else {
+                tracing::warn!("[hooks] {reason:?} without event: {reference}");
+                return Ok(WebhookOutcome::Ignored);
             }
         }
-        //TODO: handle refunds
-        _ => {
-            tracing::warn!("[hooks] unknown stripe hook: {:?}", event.type_);
+        WebhookAction::Subscription {
+            subscription,
+            customer,
+            status,
+            time_unix,
+        } => {
+            app.subscription_changed(subscription, customer, status, time_unix)
+                .await?;
         }
+        WebhookAction::Ignore => return Ok(WebhookOutcome::Ignored),
     }
 
-    Ok(Html(""))
+    Ok(WebhookOutcome::Processed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn action(payload: &str) -> WebhookAction {
+        webhook_action(serde_json::from_str(payload).unwrap())
+    }
+
+    #[test]
+    fn test_event_kind() {
+        assert_eq!(
+            event_kind(EventType::CustomerSubscriptionDeleted),
+            "customer.subscription.deleted"
+        );
+    }
+
+    #[test]
+    fn test_checkout_completed() {
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/checkout_session_completed.json"
+            )),
+            WebhookAction::Fulfil {
+                session: String::from("cs_test_a1Yx7PqLmN2oR3sT4uV5wX6yZ7aB8cD9eF0gH1iJ2kL3mN4oP5"),
+                event: String::from("01HQ3Z8K5V7N2W4X6Y8A0B2C4D"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_async_payment() {
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/checkout_session_async_payment_succeeded.json"
+            )),
+            WebhookAction::Fulfil {
+                session: String::from("cs_test_b2Zy8QrMnO3pS4tU5vW6xY7zA8bC9dE0fG1hI2jK3lM4nO5pQ6"),
+                event: String::from("01HQ3Z8K5V7N2W4X6Y8A0B2C4D"),
+            }
+        );
+
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/checkout_session_async_payment_failed.json"
+            )),
+            WebhookAction::Issue {
+                target: PaymentTarget::Event(String::from("01HQ3Z8K5V7N2W4X6Y8A0B2C4D")),
+                reason: PremiumIssueReason::AsyncPaymentFailed,
+                reference: String::from(
+                    "cs_test_b2Zy8QrMnO3pS4tU5vW6xY7zA8bC9dE0fG1hI2jK3lM4nO5pQ6"
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn test_charge_refunded() {
+        assert_eq!(
+            action(include_str!("../test-data/stripe/charge_refunded.json")),
+            WebhookAction::Issue {
+                target: PaymentTarget::Event(String::from("01HQ3Z8K5V7N2W4X6Y8A0B2C4D")),
+                reason: PremiumIssueReason::Refunded,
+                reference: String::from("pi_3OnB7xK2eZvKYlo21zR8vNcE"),
+            }
+        );
+
+        // charges of older checkouts have no metadata
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/charge_refunded_without_metadata.json"
+            )),
+            WebhookAction::Issue {
+                target: PaymentTarget::PaymentIntent(String::from("pi_3OnB7xK2eZvKYlo21zR8vNcE")),
+                reason: PremiumIssueReason::Refunded,
+                reference: String::from("pi_3OnB7xK2eZvKYlo21zR8vNcE"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_dispute_created() {
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/charge_dispute_created.json"
+            )),
+            WebhookAction::Issue {
+                target: PaymentTarget::Charge(String::from("ch_3OnB7xK2eZvKYlo21Xq9WmTr")),
+                reason: PremiumIssueReason::Disputed,
+                reference: String::from("dp_1OnC2aK2eZvKYlo2Hs8TzQpL"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_subscription_lifecycle() {
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/customer_subscription_updated.json"
+            )),
+            WebhookAction::Subscription {
+                subscription: String::from("sub_1OnD4eK2eZvKYlo2tR5yUi7O"),
+                customer: String::from("cus_PcSx9QwErTy2Ui"),
+                status: String::from("past_due"),
+                time_unix: 1_711_128_321,
+            }
+        );
+
+        assert_eq!(
+            action(include_str!(
+                "../test-data/stripe/customer_subscription_deleted.json"
+            )),
+            WebhookAction::Subscription {
+                subscription: String::from("sub_1OnD4eK2eZvKYlo2tR5yUi7O"),
+                customer: String::from("cus_PcSx9QwErTy2Ui"),
+                status: String::from("canceled"),
+                time_unix: 1_712_942_401,
+            }
+        );
+    }
 }
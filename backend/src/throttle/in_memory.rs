@@ -0,0 +1,44 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::Throttle;
+
+#[derive(Clone, Default)]
+pub struct InMemoryThrottle {
+    failures: Arc<Mutex<HashMap<String, u64>>>,
+    locks: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+#[async_trait]
+impl Throttle for InMemoryThrottle {
+    async fn locked_for(&self, key: &str) -> Option<Duration> {
+        let until = *self.locks.lock().await.get(key)?;
+
+        Some(until.saturating_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+
+    async fn fail(&self, key: &str) -> u64 {
+        let mut failures = self.failures.lock().await;
+        let count = failures.entry(key.to_string()).or_default();
+        *count += 1;
+        *count
+    }
+
+    async fn lock(&self, key: &str, duration: Duration) {
+        self.locks
+            .lock()
+            .await
+            .insert(key.to_string(), Instant::now() + duration);
+    }
+
+    async fn reset(&self, key: &str) {
+        self.failures.lock().await.remove(key);
+        self.locks.lock().await.remove(key);
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod in_memory;
+mod redis;
+
+#[cfg(test)]
+pub use self::in_memory::InMemoryThrottle;
+pub use self::redis::RedisThrottle;
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Failed-attempt counters with lockouts, keyed by whatever is being protected
+/// (e.g. an event or a client address).
+#[async_trait]
+pub trait Throttle: Send + Sync {
+    /// Remaining lockout of `key`, `None` if it is not locked.
+    async fn locked_for(&self, key: &str) -> Option<Duration>;
+    /// Count a failed attempt on `key`, returns the failures seen within the window.
+    async fn fail(&self, key: &str) -> u64;
+    /// Lock `key` for `duration`.
+    async fn lock(&self, key: &str, duration: Duration);
+    /// Forget failures and lockouts of `key`.
+    async fn reset(&self, key: &str);
+}
+
+/// Exponential lockout: the first `free_attempts` failures are tolerated, every further one
+/// doubles the lockout starting at `base`, capped at `max`.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    pub free_attempts: u64,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Policy {
+    pub fn lockout(&self, failures: u64) -> Option<Duration> {
+        let over = failures
+            .checked_sub(self.free_attempts)
+            .filter(|over| *over > 0)?;
+
+        let exponent = u32::try_from(over.saturating_sub(1))
+            .unwrap_or(u32::MAX)
+            .min(31);
+
+        Some(self.base.saturating_mul(1_u32 << exponent).min(self.max))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const POLICY: Policy = Policy {
+        free_attempts: 3,
+        base: Duration::from_secs(2),
+        max: Duration::from_secs(60),
+    };
+
+    #[test]
+    fn free_attempts_are_not_locked() {
+        assert_eq!(POLICY.lockout(0), None);
+        assert_eq!(POLICY.lockout(3), None);
+    }
+
+    #[test]
+    fn lockout_doubles_and_caps() {
+        assert_eq!(POLICY.lockout(4), Some(Duration::from_secs(2)));
+        assert_eq!(POLICY.lockout(5), Some(Duration::from_secs(4)));
+        assert_eq!(POLICY.lockout(8), Some(Duration::from_secs(32)));
+        assert_eq!(POLICY.lockout(9), Some(Duration::from_secs(60)));
+        assert_eq!(POLICY.lockout(u64::MAX), Some(Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,83 @@
+use super::Throttle;
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use std::time::Duration;
+use tracing::instrument;
+
+pub struct RedisThrottle {
+    redis: deadpool_redis::Pool,
+}
+
+impl RedisThrottle {
+    pub const fn new(pool: deadpool_redis::Pool) -> Self {
+        Self { redis: pool }
+    }
+}
+
+/// failures are forgotten once a key saw none for this long
+const FAILURE_WINDOW: usize = 60 * 60;
+
+// Note: all operations fail open — an unreachable redis must not lock everyone out
+#[async_trait]
+impl Throttle for RedisThrottle {
+    #[instrument(skip(self))]
+    async fn locked_for(&self, key: &str) -> Option<Duration> {
+        let mut db = self.redis.get().await.ok()?;
+
+        let ttl_ms = db.pttl::<_, i64>(lock_key(key)).await.ok()?;
+
+        // negative ttl: no lock (-2) or no expiry (-1, never set by us)
+        u64::try_from(ttl_ms)
+            .ok()
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    #[instrument(skip(self))]
+    async fn fail(&self, key: &str) -> u64 {
+        let Ok(mut db) = self.redis.get().await else {
+            return 0;
+        };
+
+        let key = count_key(key);
+        // INCR + refresh window in a single round-trip
+        redis::pipe()
+            .incr(&key, 1_u64)
+            .expire(&key, FAILURE_WINDOW)
+            .ignore()
+            .query_async::<_, (u64,)>(&mut db)
+            .await
+            .map(|(count,)| count)
+            .unwrap_or_default()
+    }
+
+    #[instrument(skip(self))]
+    async fn lock(&self, key: &str, duration: Duration) {
+        if let Ok(mut db) = self.redis.get().await {
+            let seconds = usize::try_from(duration.as_secs())
+                .unwrap_or(usize::MAX)
+                .max(1);
+
+            db.set_ex::<_, _, ()>(lock_key(key), 1_u8, seconds)
+                .await
+                .ok();
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn reset(&self, key: &str) {
+        if let Ok(mut db) = self.redis.get().await {
+            db.del::<_, ()>(vec![count_key(key), lock_key(key)])
+                .await
+                .ok();
+        }
+    }
+}
+
+fn count_key(key: &str) -> String {
+    format!("throttle/fail/{key}")
+}
+
+fn lock_key(key: &str) -> String {
+    format!("throttle/lock/{key}")
+}
@@ -6,6 +6,14 @@ pub enum TrackingError {
     PosthogAsync(#[from] async_posthog::Error),
     #[error("PH Core Error: {0}")]
     PosthogCore(#[from] posthog_core::error::Error),
+    #[error("Serde Json Error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Http Error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Event has no properties: {0}")]
+    NotAnObject(&'static str),
 }
 
 pub type TrackingResult<T> = std::result::Result<T, TrackingError>;
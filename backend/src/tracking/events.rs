@@ -0,0 +1,207 @@
+use serde::Serialize;
+
+/// Typed analytics event, its fields become the event properties.
+pub trait AnalyticsEvent: Serialize {
+    const NAME: &'static str;
+    /// properties quoting what users entered (names, urls, ..), left out for events kept away
+    /// from third parties
+    const USER_CONTENT: &'static [&'static str] = &[];
+}
+
+macro_rules! analytics_event {
+    ($name:literal, $event:ty) => {
+        impl AnalyticsEvent for $event {
+            const NAME: &'static str = $name;
+        }
+    };
+    ($name:literal, $event:ty, user_content: $($prop:literal),+) => {
+        impl AnalyticsEvent for $event {
+            const NAME: &'static str = $name;
+            const USER_CONTENT: &'static [&'static str] = &[$($prop),+];
+        }
+    };
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+pub enum EditEvent {
+    Enabled,
+    Changed,
+    Disabled,
+}
+
+#[derive(Serialize)]
+pub struct ServerStart {}
+analytics_event!("event-start", ServerStart);
+
+#[derive(Serialize)]
+pub struct PasswordSet {
+    pub event: String,
+    pub edit: EditEvent,
+}
+analytics_event!("event-pwd", PasswordSet);
+
+#[derive(Serialize)]
+pub struct PasswordLockout<'a> {
+    pub event: String,
+    pub scope: &'a str,
+    pub failures: u64,
+    pub lockout_secs: u64,
+}
+analytics_event!("event-pwd-lockout", PasswordLockout<'_>);
+
+#[derive(Serialize)]
+pub struct TagSet {
+    pub event: String,
+    pub edit: EditEvent,
+    pub age: i64,
+}
+analytics_event!("event-tag", TagSet);
+
+#[derive(Serialize)]
+pub struct TagEdit<'a> {
+    pub event: String,
+    pub action: &'a str,
+}
+analytics_event!("event-tag-edit", TagEdit<'_>);
+
+#[derive(Serialize)]
+pub struct SessionEdit<'a> {
+    pub event: String,
+    pub action: &'a str,
+    pub sessions: usize,
+}
+analytics_event!("event-session-edit", SessionEdit<'_>);
+
+#[derive(Serialize)]
+pub struct OrganizerLogin {
+    pub events: usize,
+}
+analytics_event!("organizer-login", OrganizerLogin);
+
+#[derive(Serialize)]
+pub struct OrganizerEventAdded {
+    pub event: String,
+}
+analytics_event!("organizer-event-add", OrganizerEventAdded);
+
+#[derive(Serialize)]
+pub struct EventCreated {
+    pub event: String,
+    pub name: String,
+    pub url: String,
+}
+analytics_event!("event-created", EventCreated, user_content: "name", "url");
+
+#[derive(Serialize)]
+pub struct EventCloned {
+    pub source: String,
+    pub event: String,
+    pub questions: usize,
+}
+analytics_event!("event-cloned", EventCloned);
+
+#[derive(Serialize)]
+pub struct TemplateSaved {
+    pub event: String,
+    pub template: String,
+}
+analytics_event!("event-template-saved", TemplateSaved);
+
+#[derive(Serialize)]
+pub struct EventUpgraded<'a> {
+    pub event: String,
+    pub name: String,
+    pub url: String,
+    pub age: i64,
+    pub order_type: &'a str,
+}
+analytics_event!("event-upgraded", EventUpgraded<'_>, user_content: "name", "url");
+
+#[derive(Serialize)]
+pub struct PremiumIssue<'a> {
+    pub event: String,
+    pub reason: &'a str,
+    pub revoked: bool,
+}
+analytics_event!("event-premium-issue", PremiumIssue<'_>);
+
+#[derive(Serialize)]
+pub struct SubscriptionChanged {
+    pub subscription: String,
+    pub status: String,
+    pub events: usize,
+}
+analytics_event!("subscription-changed", SubscriptionChanged);
+
+#[derive(Serialize)]
+pub struct ContextSet<'a> {
+    pub event: String,
+    #[serde(rename = "context-label")]
+    pub label: &'a str,
+    #[serde(rename = "context-url")]
+    pub url: &'a str,
+}
+analytics_event!(
+    "event-context",
+    ContextSet<'_>,
+    user_content: "context-label",
+    "context-url"
+);
+
+#[derive(Serialize)]
+pub struct MetaChanged<'a> {
+    pub event: String,
+    pub title: &'a str,
+    pub desc: &'a str,
+}
+analytics_event!("event-meta-changed", MetaChanged<'_>, user_content: "title", "desc");
+
+#[derive(Serialize)]
+pub struct ContentFilterChanged {
+    pub event: String,
+    /// `ContentFilterAction` variant name
+    pub action: String,
+    pub blocklist: usize,
+}
+analytics_event!("event-filter", ContentFilterChanged);
+
+#[derive(Serialize)]
+pub struct QuestionLimitsChanged {
+    pub event: String,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub min_words: usize,
+    pub max_questions: usize,
+}
+analytics_event!("event-question-limits", QuestionLimitsChanged);
+
+#[derive(Serialize)]
+pub struct PollAdded {
+    pub event: String,
+    /// `PollKind` variant name
+    pub kind: String,
+    pub options: usize,
+}
+analytics_event!("event-poll-added", PollAdded);
+
+#[derive(Serialize)]
+pub struct ColorChanged<'a> {
+    pub event: String,
+    pub color: &'a str,
+    pub premium: bool,
+}
+analytics_event!("event-color-changed", ColorChanged<'_>);
+
+#[derive(Serialize)]
+pub struct RequestUpgrade {
+    pub event: String,
+    pub context: String,
+}
+analytics_event!("event-request-upgrade", RequestUpgrade);
+
+#[derive(Serialize)]
+pub struct QuestionAdded {
+    pub event: String,
+    pub count: i64,
+}
+analytics_event!("event-question-added", QuestionAdded);
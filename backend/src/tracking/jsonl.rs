@@ -0,0 +1,89 @@
+use std::{fs::OpenOptions, path::Path};
+
+use async_trait::async_trait;
+use tokio::{
+    fs::File,
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::Mutex,
+};
+
+use super::{TrackedEvent, TrackingResult, TrackingSink};
+
+/// One json object per event and line.
+fn lines(batch: &[TrackedEvent]) -> TrackingResult<Vec<u8>> {
+    let mut out = Vec::new();
+
+    for event in batch {
+        serde_json::to_writer(&mut out, event)?;
+        out.push(b'\n');
+    }
+
+    Ok(out)
+}
+
+async fn write_lines(
+    out: &mut (impl AsyncWrite + Unpin),
+    batch: &[TrackedEvent],
+) -> TrackingResult<()> {
+    out.write_all(&lines(batch)?).await?;
+    out.flush().await?;
+
+    Ok(())
+}
+
+/// Appends events to a file as json lines.
+pub struct JsonlSink {
+    file: Mutex<File>,
+}
+
+impl JsonlSink {
+    /// Creates the file if it does not exist yet.
+    pub fn open(path: &Path) -> TrackingResult<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(File::from_std(file)),
+        })
+    }
+}
+
+#[async_trait]
+impl TrackingSink for JsonlSink {
+    async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()> {
+        write_lines(&mut *self.file.lock().await, batch).await
+    }
+}
+
+/// Writes events to stdout as json lines, for deployments collecting the process output.
+pub struct StdoutSink;
+
+#[async_trait]
+impl TrackingSink for StdoutSink {
+    async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()> {
+        write_lines(&mut tokio::io::stdout(), batch).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_lines() {
+        let mut event = TrackedEvent::new("event-created", "server");
+        event.insert_prop("event", "ev1").unwrap();
+
+        let out = lines(&[event, TrackedEvent::new("event-start", "server")]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"event":"event-created","distinct_id":"server","properties":{"event":"ev1"}}"#,
+                "\n",
+                r#"{"event":"event-start","distinct_id":"server","properties":{}}"#,
+                "\n"
+            )
+        );
+    }
+}
@@ -1,203 +1,410 @@
 mod error;
+mod events;
+mod jsonl;
+mod posthog;
+mod queue;
+mod webhook;
 
 pub use error::TrackingError;
+pub use events::{AnalyticsEvent, EditEvent};
+pub use jsonl::{JsonlSink, StdoutSink};
+pub use posthog::PostHogSink;
+pub use queue::{QueueConfig, TrackingQueue};
+pub use webhook::WebhookSink;
 
 use error::TrackingResult;
 
-use async_posthog::{ClientOptions, Event};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use shared::{ContentFilterAction, PollKind, QuestionLimits};
+use tokio::time::timeout;
 
 use crate::GIT_HASH;
 
+/// How long shutdown waits for queued events to be sent.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An analytics event as queued, before a [`TrackingSink`] sends it wherever it goes.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrackedEvent {
+    pub event: String,
+    pub distinct_id: String,
+    pub properties: Map<String, Value>,
+}
+
+impl TrackedEvent {
+    pub fn new(event: &str, distinct_id: &str) -> Self {
+        Self {
+            event: event.to_string(),
+            distinct_id: distinct_id.to_string(),
+            properties: Map::new(),
+        }
+    }
+
+    pub fn from_event<E: AnalyticsEvent>(event: &E, distinct_id: &str) -> TrackingResult<Self> {
+        match serde_json::to_value(event)? {
+            Value::Object(properties) => Ok(Self {
+                properties,
+                ..Self::new(E::NAME, distinct_id)
+            }),
+            _ => Err(TrackingError::NotAnObject(E::NAME)),
+        }
+    }
+
+    pub fn insert_prop<V: Serialize>(&mut self, key: &str, value: V) -> TrackingResult<()> {
+        self.properties
+            .insert(key.to_string(), serde_json::to_value(value)?);
+
+        Ok(())
+    }
+}
+
+/// Where the [`TrackingQueue`] sends its batches.
+#[async_trait]
+pub trait TrackingSink: Send + Sync {
+    async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()>;
+}
+
+/// Where tracked events are sent, chosen by `LA_TRACKING_SINK`.
+#[derive(Clone, Default)]
+pub enum SinkConfig {
+    /// events are discarded
+    #[default]
+    None,
+    PostHog {
+        key: String,
+    },
+    Jsonl {
+        path: PathBuf,
+    },
+    Stdout,
+    Webhook {
+        url: String,
+    },
+}
+
+impl SinkConfig {
+    /// For logging, without the secrets the config may contain.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::PostHog { .. } => "posthog",
+            Self::Jsonl { .. } => "jsonl",
+            Self::Stdout => "stdout",
+            Self::Webhook { .. } => "webhook",
+        }
+    }
+
+    fn sink(self) -> TrackingResult<Option<Arc<dyn TrackingSink>>> {
+        Ok(match self {
+            Self::None => None,
+            Self::PostHog { key } => Some(Arc::new(PostHogSink::new(&key))),
+            Self::Jsonl { path } => Some(Arc::new(JsonlSink::open(&path)?)),
+            Self::Stdout => Some(Arc::new(StdoutSink)),
+            Self::Webhook { url } => Some(Arc::new(WebhookSink::new(url)?)),
+        })
+    }
+}
+
+/// Records analytics events. Recording never waits or fails, events are queued and sent in the
+/// background; without a queue (no sink configured) they are discarded.
 #[derive(Clone, Default)]
 pub struct Tracking {
-    key: Option<String>,
+    queue: Option<Arc<TrackingQueue>>,
     server: String,
     env: String,
-}
-
-#[derive(Clone, Debug)]
-pub enum EditEvent {
-    Enabled,
-    Changed,
-    Disabled,
+    /// leave out [`AnalyticsEvent::USER_CONTENT`]
+    redact: bool,
 }
 
 impl Tracking {
-    pub const fn new(key: Option<String>, server: String, env: String) -> Self {
-        Self { key, server, env }
+    /// Spawns the queue worker sending to `sink`, fails if the sink cannot be set up (e.g. the
+    /// jsonl file cannot be opened). In `privacy_mode` no event carries user content.
+    pub fn new(
+        sink: SinkConfig,
+        server: String,
+        env: String,
+        privacy_mode: bool,
+    ) -> TrackingResult<Self> {
+        let queue = sink
+            .sink()?
+            .map(|sink| Arc::new(TrackingQueue::spawn(sink, QueueConfig::default())));
+
+        Ok(Self {
+            queue,
+            server,
+            env,
+            redact: privacy_mode,
+        })
     }
 
-    pub async fn track_server_start(&self) -> TrackingResult<()> {
-        self.log(Event::new("event-start", &self.server)).await?;
-        Ok(())
+    /// Tracking for an event, without user content if it opted out of third parties.
+    pub fn redacted(&self, no_third_parties: bool) -> Self {
+        Self {
+            redact: self.redact || no_third_parties,
+            ..self.clone()
+        }
     }
 
-    pub async fn track_event_password_set(
-        &self,
-        event: String,
-        edit: EditEvent,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-pwd", &self.server);
+    /// Send what is queued, giving up after [`FLUSH_TIMEOUT`].
+    pub async fn flush(&self) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("edit", format!("{edit:?}"))?;
+        if timeout(FLUSH_TIMEOUT, queue.flush()).await.is_err() {
+            tracing::warn!("tracking flush timed out");
+        }
 
-        self.log(e).await?;
+        tracing::info!(stats = ?queue.stats(), "tracking flushed");
+    }
 
-        Ok(())
+    pub fn track_server_start(&self) {
+        self.log(&events::ServerStart {});
     }
 
-    pub async fn track_event_tag_set(
+    pub fn track_event_password_set(&self, event: String, edit: EditEvent) {
+        self.log(&events::PasswordSet { event, edit });
+    }
+
+    pub fn track_event_password_lockout(
         &self,
         event: String,
-        edit: EditEvent,
-        age: i64,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-tag", &self.server);
+        scope: &str,
+        failures: u64,
+        lockout: Duration,
+    ) {
+        self.log(&events::PasswordLockout {
+            event,
+            scope,
+            failures,
+            lockout_secs: lockout.as_secs(),
+        });
+    }
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("edit", format!("{edit:?}"))?;
-        e.insert_prop("age", age)?;
+    pub fn track_event_tag_set(&self, event: String, edit: EditEvent, age: i64) {
+        self.log(&events::TagSet { event, edit, age });
+    }
 
-        self.log(e).await?;
+    pub fn track_event_tag_edit(&self, event: String, action: &str) {
+        self.log(&events::TagEdit { event, action });
+    }
 
-        Ok(())
+    pub fn track_event_session_edit(&self, event: String, action: &str, sessions: usize) {
+        self.log(&events::SessionEdit {
+            event,
+            action,
+            sessions,
+        });
     }
 
-    pub async fn track_event_create(
-        &self,
-        event: String,
-        url: String,
-        name: String,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-created", &self.server);
+    pub fn track_organizer_login(&self, events: usize) {
+        self.log(&events::OrganizerLogin { events });
+    }
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("name", name)?;
-        e.insert_prop("url", url)?;
+    pub fn track_organizer_event_added(&self, event: String) {
+        self.log(&events::OrganizerEventAdded { event });
+    }
 
-        self.log(e).await?;
+    pub fn track_event_create(&self, event: String, url: String, name: String) {
+        self.log(&events::EventCreated { event, name, url });
+    }
 
-        Ok(())
+    pub fn track_event_clone(&self, source: String, event: String, questions: usize) {
+        self.log(&events::EventCloned {
+            source,
+            event,
+            questions,
+        });
+    }
+
+    pub fn track_event_template_saved(&self, event: String, template: String) {
+        self.log(&events::TemplateSaved { event, template });
     }
 
-    pub async fn track_event_upgrade(
+    pub fn track_event_upgrade(
         &self,
         event: String,
         name: String,
         long_url: String,
         age: i64,
         order_type: &str,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-upgraded", &self.server);
-
-        e.insert_prop("event", event)?;
-        e.insert_prop("name", name)?;
-        e.insert_prop("url", long_url)?;
-        e.insert_prop("age", age)?;
-        e.insert_prop("order_type", order_type)?;
-
-        self.log(e).await?;
-
-        Ok(())
+    ) {
+        self.log(&events::EventUpgraded {
+            event,
+            name,
+            url: long_url,
+            age,
+            order_type,
+        });
     }
 
-    pub async fn track_event_context_set(
-        &self,
-        event: String,
-        label: &str,
-        url: &str,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-context", &self.server);
+    pub fn track_event_premium_issue(&self, event: String, reason: &str, revoked: bool) {
+        self.log(&events::PremiumIssue {
+            event,
+            reason,
+            revoked,
+        });
+    }
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("context-label", label)?;
-        e.insert_prop("context-url", url)?;
+    pub fn track_subscription_changed(&self, subscription: String, status: String, events: usize) {
+        self.log(&events::SubscriptionChanged {
+            subscription,
+            status,
+            events,
+        });
+    }
 
-        self.log(e).await?;
+    pub fn track_event_context_set(&self, event: String, label: &str, url: &str) {
+        self.log(&events::ContextSet { event, label, url });
+    }
 
-        Ok(())
+    pub fn track_event_meta_change(&self, event: String, meta: &shared::EditMetaData) {
+        self.log(&events::MetaChanged {
+            event,
+            title: &meta.title,
+            desc: &meta.description,
+        });
     }
 
-    pub async fn track_event_meta_change(
+    pub fn track_event_content_filter_change(
         &self,
         event: String,
-        meta: &shared::EditMetaData,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-meta-changed", &self.server);
-
-        e.insert_prop("event", event)?;
-        e.insert_prop("title", &meta.title)?;
-        e.insert_prop("desc", &meta.description)?;
+        action: ContentFilterAction,
+        blocklist_len: usize,
+    ) {
+        self.log(&events::ContentFilterChanged {
+            event,
+            action: format!("{action:?}"),
+            blocklist: blocklist_len,
+        });
+    }
 
-        self.log(e).await?;
+    pub fn track_event_question_limits_change(&self, event: String, limits: &QuestionLimits) {
+        self.log(&events::QuestionLimitsChanged {
+            event,
+            min_len: limits.min_len,
+            max_len: limits.max_len,
+            min_words: limits.min_words,
+            max_questions: limits.max_questions,
+        });
+    }
 
-        Ok(())
+    pub fn track_event_poll_added(&self, event: String, kind: PollKind, options: usize) {
+        self.log(&events::PollAdded {
+            event,
+            kind: format!("{kind:?}"),
+            options,
+        });
     }
 
-    pub async fn track_event_color_change(
+    pub fn track_event_color_change(
         &self,
         event: String,
         value: &shared::EditColor,
         premium: bool,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-color-changed", &self.server);
-
-        e.insert_prop("event", event)?;
-        e.insert_prop("color", &value.0)?;
-        e.insert_prop("premium", premium)?;
-
-        self.log(e).await?;
-
-        Ok(())
+    ) {
+        self.log(&events::ColorChanged {
+            event,
+            color: &value.0,
+            premium,
+        });
     }
 
-    pub async fn track_event_request_upgrade(
-        &self,
-        event: String,
-        context: String,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-request-upgrade", &self.server);
+    pub fn track_event_request_upgrade(&self, event: String, context: String) {
+        self.log(&events::RequestUpgrade { event, context });
+    }
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("context", context)?;
+    pub fn track_event_question_added(&self, event: String, count: i64) {
+        self.log(&events::QuestionAdded { event, count });
+    }
 
-        self.log(e).await?;
+    fn log<E: AnalyticsEvent>(&self, event: &E) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
 
-        Ok(())
+        match self.tracked(event) {
+            Ok(e) => queue.push(e),
+            Err(err) => tracing::error!("tracking event {} dropped: {err}", E::NAME),
+        }
     }
 
-    pub async fn track_event_question_added(
-        &self,
-        event: String,
-        count: i64,
-    ) -> TrackingResult<()> {
-        let mut e = Event::new("event-question-added", &self.server);
+    fn tracked<E: AnalyticsEvent>(&self, event: &E) -> TrackingResult<TrackedEvent> {
+        let mut e = TrackedEvent::from_event(event, &self.server)?;
 
-        e.insert_prop("event", event)?;
-        e.insert_prop("count", count)?;
+        if self.redact {
+            for prop in E::USER_CONTENT {
+                e.properties.remove(*prop);
+            }
+        }
 
-        self.log(e).await?;
+        e.insert_prop("env", &self.env)?;
+        e.insert_prop("git", GIT_HASH)?;
 
-        Ok(())
+        Ok(e)
     }
+}
 
-    async fn log(&self, event: Event) -> TrackingResult<()> {
-        if let Some(key) = &self.key {
-            let mut client = ClientOptions::new(key);
-            client.api_endpoint("https://eu.posthog.com");
-            let client = client.build();
-
-            let mut event = event;
-
-            event.insert_prop("env", &self.env)?;
-            event.insert_prop("git", GIT_HASH)?;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_event() {
+        let event = TrackedEvent::from_event(
+            &events::ContextSet {
+                event: String::from("ev1"),
+                label: "slides",
+                url: "https://example.com",
+            },
+            "server",
+        )
+        .unwrap();
+
+        assert_eq!(event.event, "event-context");
+        assert_eq!(
+            Value::Object(event.properties),
+            json!({
+                "event": "ev1",
+                "context-label": "slides",
+                "context-url": "https://example.com",
+            })
+        );
+
+        let event = TrackedEvent::from_event(&events::ServerStart {}, "server").unwrap();
+        assert!(event.properties.is_empty());
+    }
 
-            if let Err(e) = client.capture(event).await {
-                tracing::error!("posthog error: {e}");
+    #[test]
+    fn test_redacted() {
+        let event = events::EventCreated {
+            event: String::from("ev1"),
+            name: String::from("secret meetup"),
+            url: String::from("https://example.com/event/ev1"),
+        };
+
+        let tracking = Tracking::default();
+        let props = tracking.tracked(&event).unwrap().properties;
+        assert_eq!(props["name"], "secret meetup");
+
+        for tracking in [
+            tracking.redacted(true),
+            Tracking {
+                redact: true,
+                ..Tracking::default()
             }
+            .redacted(false),
+        ] {
+            let props = tracking.tracked(&event).unwrap().properties;
+            assert_eq!(props["event"], "ev1");
+            assert!(!props.contains_key("name"));
+            assert!(!props.contains_key("url"));
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,45 @@
+use async_posthog::{Client, ClientOptions, Event};
+use async_trait::async_trait;
+
+use super::{TrackedEvent, TrackingResult, TrackingSink};
+
+const API_ENDPOINT: &str = "https://eu.posthog.com";
+
+pub struct PostHogSink {
+    client: Client,
+}
+
+impl PostHogSink {
+    pub fn new(key: &str) -> Self {
+        let mut client = ClientOptions::new(key);
+        client.api_endpoint(API_ENDPOINT);
+
+        Self {
+            client: client.build(),
+        }
+    }
+
+    fn event(tracked: &TrackedEvent) -> TrackingResult<Event> {
+        let mut event = Event::new(&tracked.event, &tracked.distinct_id);
+
+        for (key, value) in &tracked.properties {
+            event.insert_prop(key, value)?;
+        }
+
+        Ok(event)
+    }
+}
+
+#[async_trait]
+impl TrackingSink for PostHogSink {
+    async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()> {
+        let events = batch
+            .iter()
+            .map(Self::event)
+            .collect::<TrackingResult<Vec<_>>>()?;
+
+        self.client.capture_batch(events).await?;
+
+        Ok(())
+    }
+}
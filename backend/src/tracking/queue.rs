@@ -0,0 +1,288 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{MissedTickBehavior, interval, sleep},
+};
+
+use super::{TrackedEvent, TrackingSink};
+
+#[derive(Clone, Copy, Debug)]
+pub struct QueueConfig {
+    /// events waiting for the worker, more are dropped
+    pub capacity: usize,
+    pub batch_size: usize,
+    /// a partial batch is sent after this long
+    pub interval: Duration,
+    pub attempts: u32,
+    /// grows linearly with each attempt
+    pub retry_delay: Duration,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            batch_size: 100,
+            interval: Duration::from_secs(5),
+            attempts: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Counts of what happened to queued events, also exported as prometheus counters.
+#[derive(Default, Debug)]
+pub struct TrackingStats {
+    pub sent: AtomicU64,
+    /// queue was full
+    pub dropped: AtomicU64,
+    /// the sink kept failing
+    pub failed: AtomicU64,
+}
+
+impl TrackingStats {
+    fn add(counter: &AtomicU64, name: &'static str, count: usize) {
+        let count = u64::try_from(count).unwrap_or(u64::MAX);
+        counter.fetch_add(count, Ordering::Relaxed);
+        metrics::counter!(name, count);
+    }
+}
+
+enum Command {
+    Event(TrackedEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Hands events to a background worker that sends them to a [`TrackingSink`] in batches, so
+/// tracking never blocks or fails the request it happens in.
+pub struct TrackingQueue {
+    tx: mpsc::Sender<Command>,
+    stats: Arc<TrackingStats>,
+}
+
+impl TrackingQueue {
+    pub fn spawn(sink: Arc<dyn TrackingSink>, config: QueueConfig) -> Self {
+        let (tx, rx) = mpsc::channel(config.capacity);
+        let stats = Arc::new(TrackingStats::default());
+
+        tokio::spawn(Worker::new(sink, Arc::clone(&stats), config).run(rx));
+
+        Self { tx, stats }
+    }
+
+    pub fn stats(&self) -> &TrackingStats {
+        &self.stats
+    }
+
+    /// Never waits, drops the event if the worker is too far behind.
+    pub fn push(&self, event: TrackedEvent) {
+        if let Err(e) = self.tx.try_send(Command::Event(event)) {
+            tracing::warn!("tracking event dropped: {e}");
+            TrackingStats::add(&self.stats.dropped, "liveask_tracking_dropped_total", 1);
+        }
+    }
+
+    /// Send everything queued so far.
+    pub async fn flush(&self) {
+        let (ack, done) = oneshot::channel();
+
+        if self.tx.send(Command::Flush(ack)).await.is_ok() {
+            let _ = done.await;
+        }
+    }
+}
+
+struct Worker {
+    sink: Arc<dyn TrackingSink>,
+    stats: Arc<TrackingStats>,
+    config: QueueConfig,
+    batch: Vec<TrackedEvent>,
+}
+
+impl Worker {
+    fn new(sink: Arc<dyn TrackingSink>, stats: Arc<TrackingStats>, config: QueueConfig) -> Self {
+        Self {
+            sink,
+            stats,
+            config,
+            batch: Vec::with_capacity(config.batch_size),
+        }
+    }
+
+    async fn run(mut self, mut rx: mpsc::Receiver<Command>) {
+        let mut ticks = interval(self.config.interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                command = rx.recv() => match command {
+                    Some(Command::Event(event)) => {
+                        self.batch.push(event);
+
+                        if self.batch.len() >= self.config.batch_size {
+                            self.send().await;
+                        }
+                    }
+                    Some(Command::Flush(ack)) => {
+                        self.send().await;
+                        let _ = ack.send(());
+                    }
+                    None => {
+                        self.send().await;
+                        break;
+                    }
+                },
+                _ = ticks.tick() => self.send().await,
+            }
+        }
+    }
+
+    async fn send(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+
+        let len = self.batch.len();
+
+        for attempt in 1..=self.config.attempts {
+            match self.sink.send(&self.batch).await {
+                Ok(()) => {
+                    TrackingStats::add(&self.stats.sent, "liveask_tracking_sent_total", len);
+                    self.batch.clear();
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(attempt, "tracking batch of {len} failed: {e}");
+
+                    if attempt < self.config.attempts {
+                        sleep(self.config.retry_delay * attempt).await;
+                    }
+                }
+            }
+        }
+
+        tracing::error!("tracking batch of {len} given up");
+        TrackingStats::add(&self.stats.failed, "liveask_tracking_failed_total", len);
+        self.batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracking::{TrackingResult, error::TrackingError};
+    use async_trait::async_trait;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::AtomicU32;
+    use tokio::sync::Mutex;
+
+    /// records the batches, failing the first `failures` sends
+    #[derive(Default)]
+    struct RecordingSink {
+        batches: Mutex<Vec<Vec<String>>>,
+        failures: AtomicU32,
+    }
+
+    #[async_trait]
+    impl TrackingSink for RecordingSink {
+        async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()> {
+            if self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+                .is_ok()
+            {
+                return Err(TrackingError::SerdeJson(
+                    serde_json::from_str::<serde_json::Value>("unavailable").unwrap_err(),
+                ));
+            }
+
+            self.batches
+                .lock()
+                .await
+                .push(batch.iter().map(|e| e.event.clone()).collect());
+
+            Ok(())
+        }
+    }
+
+    fn config() -> QueueConfig {
+        QueueConfig {
+            capacity: 10,
+            batch_size: 2,
+            interval: Duration::from_secs(60),
+            attempts: 2,
+            retry_delay: Duration::ZERO,
+        }
+    }
+
+    fn event(name: &str) -> TrackedEvent {
+        TrackedEvent::new(name, "server")
+    }
+
+    #[tokio::test]
+    async fn test_batches() {
+        let sink = Arc::new(RecordingSink::default());
+        let queue = TrackingQueue::spawn(Arc::clone(&sink), config());
+
+        for name in ["a", "b", "c"] {
+            queue.push(event(name));
+        }
+        queue.flush().await;
+
+        assert_eq!(
+            *sink.batches.lock().await,
+            vec![
+                vec![String::from("a"), String::from("b")],
+                vec![String::from("c")]
+            ]
+        );
+        assert_eq!(queue.stats().sent.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let sink = Arc::new(RecordingSink {
+            failures: AtomicU32::new(1),
+            ..RecordingSink::default()
+        });
+        let queue = TrackingQueue::spawn(Arc::clone(&sink), config());
+
+        queue.push(event("a"));
+        queue.flush().await;
+        assert_eq!(sink.batches.lock().await.len(), 1);
+
+        sink.failures.store(2, Ordering::Relaxed);
+        queue.push(event("b"));
+        queue.flush().await;
+        assert_eq!(sink.batches.lock().await.len(), 1);
+        assert_eq!(queue.stats().failed.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_drop_on_overflow() {
+        let sink = Arc::new(RecordingSink::default());
+        let queue = TrackingQueue::spawn(
+            Arc::clone(&sink),
+            QueueConfig {
+                capacity: 2,
+                ..config()
+            },
+        );
+
+        // the worker only runs once this test yields
+        for name in ["a", "b", "c"] {
+            queue.push(event(name));
+        }
+        queue.flush().await;
+
+        assert_eq!(queue.stats().dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(queue.stats().sent.load(Ordering::Relaxed), 2);
+    }
+}
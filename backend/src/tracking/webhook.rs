@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+
+use super::{TrackedEvent, TrackingResult, TrackingSink};
+
+/// a hanging endpoint fails the batch instead of stalling the queue worker
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    batch: &'a [TrackedEvent],
+}
+
+/// Posts each batch as `{"batch": [..]}` json to a url, any non-success status fails the batch
+/// so the queue retries it.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> TrackingResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
+        Ok(Self { client, url })
+    }
+}
+
+#[async_trait]
+impl TrackingSink for WebhookSink {
+    async fn send(&self, batch: &[TrackedEvent]) -> TrackingResult<()> {
+        self.client
+            .post(&self.url)
+            .json(&Payload { batch })
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
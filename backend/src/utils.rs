@@ -6,9 +6,9 @@ pub fn timestamp_now() -> i64 {
     now.timestamp_millis().saturating_div(1000)
 }
 
-/// Stable, non-reversible fingerprint of an event password. Embedded in a pwd grant so the
-/// grant is bound to the password value: rotating the password changes the fingerprint and
-/// re-locks outstanding grants (matching the old re-check-every-request behaviour).
+/// Stable, non-reversible fingerprint of a stored event password (its salted hash). Embedded in
+/// a pwd grant so the grant is bound to the password: rotating the password changes the
+/// fingerprint and re-locks outstanding grants (matching the old re-check-every-request behaviour).
 pub fn pwd_fingerprint(pwd: &str) -> String {
     hex::encode(Sha256::digest(pwd.as_bytes()))
 }
@@ -1,6 +1,7 @@
 use crate::{components::Popup, fetch, pages::BASE_API};
 use shared::{
-    ContextItem, ContextLabelError, ContextUrlError, ContextValidation, ModEvent, ValidationState,
+    ContextItem, ContextLabelError, ContextUrlError, ContextValidation, EditContextLink,
+    MAX_CONTEXT_LINKS, ModEvent, ValidationState,
 };
 use wasm_bindgen::UnwrapThrowExt;
 use web_sys::HtmlInputElement;
@@ -13,24 +14,22 @@ pub enum Input {
 }
 
 pub enum Msg {
-    ConfirmedDelete,
+    Select(Option<usize>),
+    Remove(usize),
+    MoveUp(usize),
     ConfirmEdit,
     ServerResponed,
     Close,
     InputChange(Input, InputEvent),
 }
 
-enum State {
-    Create,
-    Edit,
-}
-
 pub struct ContextPopup {
     label: String,
     url: String,
     send_pending: bool,
     errors: ContextValidation,
-    state: State,
+    /// index of the link being edited, `None` while adding a new one
+    editing: Option<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Properties)]
@@ -52,23 +51,15 @@ impl Component for ContextPopup {
             url: String::new(),
             send_pending: false,
             errors: ContextValidation::default(),
-            state: State::Create,
+            editing: None,
         }
     }
 
-    fn changed(&mut self, ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
-        if ctx.props().show {
-            self.state = State::Create;
-            if let Some(item) = ctx.props().context.first() {
-                self.label.clone_from(&item.label);
-                self.url.clone_from(&item.url);
-                self.errors.check(&self.label, &self.url);
-                self.state = State::Edit;
-            }
-        } else {
-            self.label = String::new();
-            self.url = String::new();
-            self.state = State::Create;
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let links = ctx.props().context.len();
+
+        if ctx.props().show != old_props.show || self.editing.is_some_and(|idx| idx >= links) {
+            self.select(ctx, None);
         }
 
         true
@@ -84,59 +75,37 @@ impl Component for ContextPopup {
                     true
                 }
             }
-            Msg::ConfirmedDelete => {
-                self.send_pending = true;
-
-                let tokens = ctx.props().tokens.clone();
-                ctx.link().send_future(async move {
-                    fetch::mod_edit_event(
-                        BASE_API,
-                        tokens.public_token.clone(),
-                        tokens.moderator_token.unwrap_throw(),
-                        ModEvent {
-                            context: Some(shared::EditContextLink::Disabled),
-                            ..Default::default()
-                        },
-                    )
-                    .await
-                    .map_or_else(
-                        |e| {
-                            log::error!("mod_edit_event error: {e}");
-                            Msg::ServerResponed
-                        },
-                        |_| Msg::ServerResponed,
-                    )
-                });
+            Msg::Select(idx) => {
+                self.select(ctx, idx);
+                true
+            }
+            Msg::Remove(idx) => {
+                self.send(ctx, EditContextLink::Remove(idx));
+                true
+            }
+            Msg::MoveUp(idx) => {
+                self.send(
+                    ctx,
+                    EditContextLink::Move {
+                        from: idx,
+                        to: idx.saturating_sub(1),
+                    },
+                );
                 true
             }
             Msg::ConfirmEdit => {
-                self.send_pending = true;
-
-                let tokens = ctx.props().tokens.clone();
                 let item = ContextItem {
                     label: self.label.clone(),
                     url: self.url.clone(),
                 };
 
-                ctx.link().send_future(async move {
-                    fetch::mod_edit_event(
-                        BASE_API,
-                        tokens.public_token.clone(),
-                        tokens.moderator_token.unwrap_throw(),
-                        ModEvent {
-                            context: Some(shared::EditContextLink::Enabled(item)),
-                            ..Default::default()
-                        },
-                    )
-                    .await
-                    .map_or_else(
-                        |e| {
-                            log::error!("mod_edit_event error: {e}");
-                            Msg::ServerResponed
-                        },
-                        |_| Msg::ServerResponed,
-                    )
-                });
+                let change = self.editing.map_or_else(
+                    || EditContextLink::Add(item.clone()),
+                    |idx| EditContextLink::Edit(idx, item.clone()),
+                );
+
+                self.send(ctx, change);
+                self.select(ctx, None);
                 true
             }
             Msg::InputChange(input, c) => {
@@ -156,7 +125,6 @@ impl Component for ContextPopup {
             }
             Msg::ServerResponed => {
                 self.send_pending = false;
-                ctx.props().on_close.emit(());
                 true
             }
         }
@@ -164,18 +132,24 @@ impl Component for ContextPopup {
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let show = ctx.props().show;
-        let is_create = matches!(self.state, State::Create);
 
         if show {
             let on_close = ctx.link().callback(|()| Msg::Close);
-            let on_click_delete = ctx.link().callback(|_| Msg::ConfirmedDelete);
+            let on_click_close = ctx.link().callback(|_| Msg::Close);
             let on_click_ok = ctx.link().callback(|_| Msg::ConfirmEdit);
 
-            let has_errors = self.errors.has_any();
+            let is_create = self.editing.is_none();
+            let is_full = ctx.props().context.len() >= MAX_CONTEXT_LINKS;
+            let has_errors =
+                self.errors.has_any() || self.label.is_empty() || (is_create && is_full);
 
             html! {
                 <Popup class="context-popup" {on_close}>
-                    <div class="title">{ if is_create { "Add context link" } else { "Edit context link" } }</div>
+                    <div class="title">{ "Context links" }</div>
+                    <div class="links">
+                        { for ctx.props().context.iter().enumerate().map(|(idx, item)| self.view_link(ctx, idx, item)) }
+                    </div>
+                    <div class="subtitle">{ if is_create { "Add context link" } else { "Edit context link" } }</div>
                     <div class="input-box">
                         <input
                             type="text"
@@ -204,16 +178,19 @@ impl Component for ContextPopup {
                     <div hidden={self.errors.url.is_valid()} class="invalid">
                         { self.url_error().unwrap_or_default() }
                     </div>
+                    <div hidden={!(is_create && is_full)} class="invalid">
+                        { format!("At most {MAX_CONTEXT_LINKS} links.") }
+                    </div>
                     <div class="buttons">
                         <button class="button-white"
                             disabled={self.send_pending}
-                            onclick={on_click_delete}>
-                            { if is_create { "cancel" } else { "remove" } }
+                            onclick={on_click_close}>
+                            { "close" }
                         </button>
                         <button class="button-red"
                             disabled={self.send_pending || has_errors}
                             onclick={on_click_ok}>
-                            { if is_create { "create" } else { "change" } }
+                            { if is_create { "add" } else { "change" } }
                         </button>
                     </div>
                 </Popup>
@@ -225,6 +202,67 @@ impl Component for ContextPopup {
 }
 
 impl ContextPopup {
+    fn view_link(&self, ctx: &Context<Self>, idx: usize, item: &ContextItem) -> Html {
+        let selected = self.editing == Some(idx);
+
+        html! {
+            <div class={classes!("link", selected.then_some("selected"))}>
+                <div class="label" onclick={ctx.link().callback(move |_| Msg::Select(if selected { None } else { Some(idx) }))}>
+                    { item.label.clone() }
+                </div>
+                <button class="button-white"
+                    hidden={idx == 0}
+                    disabled={self.send_pending}
+                    onclick={ctx.link().callback(move |_| Msg::MoveUp(idx))}>
+                    { "\u{2191}" }
+                </button>
+                <button class="button-white"
+                    disabled={self.send_pending}
+                    onclick={ctx.link().callback(move |_| Msg::Remove(idx))}>
+                    { "remove" }
+                </button>
+            </div>
+        }
+    }
+
+    fn select(&mut self, ctx: &Context<Self>, idx: Option<usize>) {
+        let item = idx.and_then(|idx| ctx.props().context.get(idx));
+
+        self.editing = item.and(idx);
+        self.label = item.map(|item| item.label.clone()).unwrap_or_default();
+        self.url = item.map(|item| item.url.clone()).unwrap_or_default();
+        self.errors = ContextValidation::default();
+        if item.is_some() {
+            self.errors.check(&self.label, &self.url);
+        }
+    }
+
+    fn send(&mut self, ctx: &Context<Self>, change: EditContextLink) {
+        self.send_pending = true;
+
+        let tokens = ctx.props().tokens.clone();
+
+        ctx.link().send_future(async move {
+            fetch::mod_edit_event(
+                BASE_API,
+                tokens.public_token.clone(),
+                tokens.moderator_token.unwrap_throw(),
+                ModEvent {
+                    context: Some(change),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_or_else(
+                |e| {
+                    log::error!("mod_edit_event error: {e}");
+                    Msg::ServerResponed
+                },
+                |_| Msg::ServerResponed,
+            )
+        });
+    }
+
     fn label_err(&self) -> Option<String> {
         match self.errors.label {
             ValidationState::Invalid(ContextLabelError::MinLength(len, max)) => Some(format!(
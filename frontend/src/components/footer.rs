@@ -9,6 +9,7 @@ pub struct Footer {
 }
 pub enum Msg {
     Privacy,
+    Organizer,
     Admin,
     VersionReceived(Option<String>),
 }
@@ -28,6 +29,13 @@ impl Component for Footer {
                 ctx.link().navigator().unwrap_throw().push(&Route::Privacy);
                 false
             }
+            Msg::Organizer => {
+                ctx.link()
+                    .navigator()
+                    .unwrap_throw()
+                    .push(&Route::Organizer);
+                false
+            }
             Msg::Admin => {
                 ctx.link().navigator().unwrap_throw().push(&Route::Login);
                 false
@@ -71,6 +79,9 @@ impl Component for Footer {
                 <div class="link about" onclick={ctx.link().callback(|_| Msg::Privacy)}>
                     { "Privacy Policy" }
                 </div>
+                <div class="link about" onclick={ctx.link().callback(|_| Msg::Organizer)}>
+                    { "My Events" }
+                </div>
                 <a class="status" href="https://liveask.instatus.com" target="_blank">
                     { "Status" }
                 </a>
@@ -7,13 +7,18 @@ mod footer;
 mod iconbar;
 mod meta_popup;
 mod mod_color;
+mod mod_organizer;
 mod mod_password;
+mod mod_sessions;
 mod mod_tags;
+mod mod_templates;
 mod password_popup;
+mod polls;
 mod popup;
 mod qr;
 mod question;
 mod question_popup;
+mod sessions;
 mod share_popup;
 mod socket;
 mod spinner;
@@ -33,13 +38,18 @@ pub use footer::Footer;
 pub use iconbar::IconBar;
 pub use meta_popup::MetaPopup;
 pub use mod_color::ColorPopup;
+pub use mod_organizer::ModOrganizer;
 pub use mod_password::ModPassword;
+pub use mod_sessions::ModSessions;
 pub use mod_tags::{ModTags, SharableTags};
+pub use mod_templates::ModTemplates;
 pub use password_popup::PasswordPopup;
+pub use polls::Polls;
 pub use popup::Popup;
 pub use qr::Qr;
 pub use question::{Question, QuestionClickType, QuestionFlags};
 pub use question_popup::QuestionPopup;
+pub use sessions::{Sessions, SharableSessions};
 pub use share_popup::SharePopup;
 pub use socket::{EventSocket, SocketResponse};
 pub use spinner::Spinner;
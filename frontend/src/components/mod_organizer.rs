@@ -0,0 +1,72 @@
+use crate::{components::DarkButton, fetch, pages::BASE_API, routes::Route};
+use shared::EventTokens;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::{prelude::*, suspense::use_future_with};
+use yew_router::prelude::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AddState {
+    Idle,
+    Requested,
+    Added,
+    Failed,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ModOrganizerProps {
+    pub tokens: EventTokens,
+}
+
+/// Put this event on the account of the logged in organizer, so its mod link can be recovered.
+#[function_component]
+pub fn ModOrganizer(props: &ModOrganizerProps) -> Html {
+    let state = use_state(|| AddState::Idle);
+
+    let _ = use_future_with(state.clone(), {
+        let tokens = props.tokens.clone();
+
+        |state| async move {
+            if **state != AddState::Requested {
+                return;
+            }
+
+            let res = fetch::organizer_add_event(
+                BASE_API,
+                tokens.public_token.clone(),
+                tokens.moderator_token.clone().unwrap_throw(),
+            )
+            .await;
+
+            match res {
+                Ok(()) => state.set(AddState::Added),
+                Err(e) => {
+                    log::error!("organizer_add_event error: {e}");
+                    state.set(AddState::Failed);
+                }
+            }
+        }
+    });
+
+    let click_add = Callback::from({
+        let state = state.clone();
+        move |()| state.set(AddState::Requested)
+    });
+
+    let note = match *state {
+        AddState::Idle | AddState::Requested => html! {},
+        AddState::Added => html! { <div class="note">{ "added to your events" }</div> },
+        AddState::Failed => html! {
+            <div class="note">
+                <Link<Route> to={Route::Organizer}>{ "log in" }</Link<Route>>
+                { " first to keep this event in your account" }
+            </div>
+        },
+    };
+
+    html! {
+        <div class="mod-organizer">
+            <DarkButton label="add to my events" on_click={click_add} />
+            { note }
+        </div>
+    }
+}
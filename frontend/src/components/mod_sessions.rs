@@ -0,0 +1,164 @@
+use crate::{
+    components::{
+        DarkButton, SharableSessions,
+        sessions::{state_label, time_window},
+    },
+    fetch,
+    pages::BASE_API,
+};
+use shared::{EditSession, EventState, EventTokens, ModEvent, SessionData, SessionValidation};
+use std::str::FromStr;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{prelude::*, suspense::use_future_with};
+
+#[derive(PartialEq, Properties)]
+pub struct ModSessionsProps {
+    pub tokens: EventTokens,
+    pub sessions: SharableSessions,
+}
+
+/// `datetime-local` inputs report milliseconds, `NaN` if empty
+#[allow(clippy::cast_possible_truncation)]
+fn input_unix(input: &NodeRef) -> Option<i64> {
+    input
+        .cast::<HtmlInputElement>()
+        .map(|input| input.value_as_number())
+        .filter(|ms| ms.is_finite())
+        .map(|ms| (ms / 1000.0) as i64)
+}
+
+/// Moderator agenda: add sessions, pick the active one and open/close each on its own.
+#[function_component]
+pub fn ModSessions(props: &ModSessionsProps) -> Html {
+    let name_ref = use_node_ref();
+    let start_ref = use_node_ref();
+    let end_ref = use_node_ref();
+    let edit = use_state(|| None::<EditSession>);
+    let error = use_state(|| false);
+
+    let _ = use_future_with(edit, {
+        let tokens = props.tokens.clone();
+
+        |edit| async move {
+            if let Some(session) = &**edit {
+                edit.set(None);
+
+                if let Err(e) = fetch::mod_edit_event(
+                    BASE_API,
+                    tokens.public_token.clone(),
+                    tokens.moderator_token.clone().unwrap_throw(),
+                    ModEvent {
+                        session: Some(session.clone()),
+                        ..Default::default()
+                    },
+                )
+                .await
+                {
+                    log::error!("mod_edit_event error: {e}");
+                }
+            }
+        }
+    });
+
+    let click_add = Callback::from({
+        let edit = edit.clone();
+        let error = error.clone();
+        let name_ref = name_ref.clone();
+        let start_ref = start_ref.clone();
+        let end_ref = end_ref.clone();
+        move |()| {
+            let Some(name) = name_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+
+            let data = SessionData {
+                name: name.value(),
+                start_unix: input_unix(&start_ref),
+                end_unix: input_unix(&end_ref),
+            };
+
+            let mut validation = SessionValidation::default();
+            validation.check(&data);
+            error.set(validation.has_any());
+
+            if !validation.has_any() {
+                name.set_value("");
+                edit.set(Some(EditSession::Add(data)));
+            }
+        }
+    });
+
+    let rows = props
+        .sessions
+        .sessions
+        .iter()
+        .map(|session| {
+            let id = session.id;
+            let is_current = props.sessions.current == Some(id);
+
+            let state_change = Callback::from({
+                let edit = edit.clone();
+                move |e: Event| {
+                    let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
+                        return;
+                    };
+                    if let Ok(state) = shared::States::from_str(&select.value()) {
+                        edit.set(Some(EditSession::State(id, EventState { state })));
+                    }
+                }
+            });
+            let pin = Callback::from({
+                let edit = edit.clone();
+                move |()| {
+                    edit.set(Some(EditSession::Current(
+                        (!is_current).then_some(id),
+                    )));
+                }
+            });
+            let delete = Callback::from({
+                let edit = edit.clone();
+                move |()| {
+                    edit.set(Some(EditSession::Delete(id)));
+                }
+            });
+
+            html! {
+                <div class="mod-session" key={id.0}>
+                    <div class="name">
+                        {session.data.name.clone()}
+                        <div class="time">{time_window(session)}</div>
+                    </div>
+                    <select onchange={state_change}>
+                        { for [shared::States::Open, shared::States::VotingOnly, shared::States::Closed].into_iter().map(|state| {
+                            let value = EventState { state };
+                            html! {
+                                <option value={value.to_value().to_string()} selected={session.state == value}>
+                                    {state_label(value)}
+                                </option>
+                            }
+                        }) }
+                    </select>
+                    <DarkButton label={if is_current {"unpin"} else {"set active"}} on_click={pin} />
+                    <DarkButton label="delete" on_click={delete} />
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="mod-sessions">
+            <div class="questions-seperator">{"SESSIONS"}</div>
+            {rows}
+            <div class="add-session">
+                <input ref={name_ref} type="text" placeholder="session name" maxlength="40" />
+                <input ref={start_ref} type="datetime-local" title="start" />
+                <input ref={end_ref} type="datetime-local" title="end" />
+                <DarkButton label="add session" on_click={click_add} />
+                <div class="error" hidden={!*error}>
+                    {"names need 3 to 40 characters and the end has to be after the start"}
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -3,13 +3,13 @@ use crate::{
     fetch,
     pages::BASE_API,
 };
-use shared::{EventTokens, ModEvent, TagId, TagValidation};
-use std::{collections::HashMap, rc::Rc};
+use shared::{EditTag, EventTags, EventTokens, ModEvent, TagId, TagValidation};
+use std::rc::Rc;
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
-use web_sys::{HtmlElement, HtmlInputElement};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
 use yew::{prelude::*, suspense::use_future_with};
 
-pub type SharableTags = Rc<HashMap<TagId, String>>;
+pub type SharableTags = Rc<EventTags>;
 
 #[derive(Eq, PartialEq, Properties)]
 pub struct ModTagsProps {
@@ -21,6 +21,14 @@ pub struct ModTagsProps {
 #[function_component]
 pub fn ModTags(props: &ModTagsProps) -> Html {
     let add_popup_open = use_state(|| false);
+    let manage_popup_open = use_state(|| false);
+
+    let on_click_manage: Callback<()> = Callback::from({
+        let manage_popup_open = manage_popup_open.clone();
+        move |()| {
+            manage_popup_open.set(true);
+        }
+    });
 
     let on_click: Callback<()> = Callback::from({
         let add_popup_open = add_popup_open.clone();
@@ -36,7 +44,7 @@ pub fn ModTags(props: &ModTagsProps) -> Html {
         let tags = SharableTags::clone(&props.tags);
         move |tag: Option<TagId>| {
             if let Some(tag) = tag {
-                let tag = tags.get(&tag).unwrap_throw().clone();
+                let tag = tags.get(tag).unwrap_throw().name.clone();
                 set_current_tag.set(Some(shared::CurrentTag::Enabled(tag)));
             } else {
                 set_current_tag.set(Some(shared::CurrentTag::Disabled));
@@ -77,7 +85,7 @@ pub fn ModTags(props: &ModTagsProps) -> Html {
             <Tags tags={SharableTags::clone(&props.tags)} tag={props.tag.clone()} {tag_click} />
 
             {
-                if props.tags.len() < shared::MAX_TAGS {
+                if props.tags.tags.len() < shared::MAX_TAGS {
                     html! {
                         <>
                         <AddTag open={add_popup_open.clone()} tokens={props.tokens.clone()} />
@@ -88,10 +96,161 @@ pub fn ModTags(props: &ModTagsProps) -> Html {
                     html! {}
                 }
             }
+            {
+                if props.tags.tags.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <>
+                        <ManageTags open={manage_popup_open.clone()} tokens={props.tokens.clone()} tags={SharableTags::clone(&props.tags)} />
+                        <DarkButton label="manage tags" on_click={on_click_manage}/>
+                        </>
+                    }
+                }
+            }
         </div>
     }
 }
 
+#[derive(PartialEq, Properties)]
+struct ManageTagsProps {
+    pub open: UseStateHandle<bool>,
+    pub tokens: EventTokens,
+    pub tags: SharableTags,
+}
+
+/// Rename, reorder and delete tags. Questions of a deleted tag move to the tag picked in the
+/// `reassign` select (or lose their tag).
+#[function_component]
+fn ManageTags(props: &ManageTagsProps) -> Html {
+    let bg_ref = use_node_ref();
+    let edit = use_state(|| None::<EditTag>);
+    let reassign = use_state(|| None::<TagId>);
+
+    let click_bg = Callback::from({
+        let open = props.open.clone();
+        let bg_ref = bg_ref.clone();
+        move |e: MouseEvent| {
+            let div = bg_ref
+                .cast::<HtmlElement>()
+                .expect_throw("div_ref not attached to div element");
+
+            let target = e.target().unwrap_throw();
+            let target: HtmlElement = target.dyn_into().unwrap_throw();
+
+            if div == target {
+                open.set(false);
+            }
+        }
+    });
+
+    let _ = use_future_with(edit, {
+        let tokens = props.tokens.clone();
+
+        |edit| async move {
+            if let Some(tag) = &**edit {
+                edit.set(None);
+
+                if let Err(e) = fetch::mod_edit_event(
+                    BASE_API,
+                    tokens.public_token.clone(),
+                    tokens.moderator_token.clone().unwrap_throw(),
+                    ModEvent {
+                        edit_tag: Some(tag.clone()),
+                        ..Default::default()
+                    },
+                )
+                .await
+                {
+                    log::error!("mod_edit_event error: {e}");
+                }
+            }
+        }
+    });
+
+    let reassign_change = Callback::from({
+        let reassign = reassign.clone();
+        move |e: Event| {
+            let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
+                return;
+            };
+            reassign.set(select.value().parse::<usize>().ok().map(TagId));
+        }
+    });
+
+    let rows = props
+        .tags
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(idx, tag)| {
+            let id = tag.id;
+
+            let rename = Callback::from({
+                let edit = edit.clone();
+                move |e: Event| {
+                    let Some(input) = e.target_dyn_into::<HtmlInputElement>() else {
+                        return;
+                    };
+                    let mut valid = TagValidation::default();
+                    valid.check(&input.value());
+                    if !valid.has_any() {
+                        edit.set(Some(EditTag::Rename(id, input.value())));
+                    }
+                }
+            });
+            let move_up = Callback::from({
+                let edit = edit.clone();
+                move |()| {
+                    edit.set(Some(EditTag::Move {
+                        tag: id,
+                        to: idx.saturating_sub(1),
+                    }));
+                }
+            });
+            let delete = Callback::from({
+                let edit = edit.clone();
+                let reassign = reassign.clone();
+                move |()| {
+                    edit.set(Some(EditTag::Delete {
+                        tag: id,
+                        reassign: (*reassign).filter(|other| *other != id),
+                    }));
+                }
+            });
+
+            html! {
+                <div class="manage-tag" key={id.0}>
+                    <input type="text" value={tag.name.clone()} maxlength="15" onchange={rename} />
+                    { if idx > 0 { html! { <DarkButton label="\u{2191}" on_click={move_up} /> } } else { html! {} } }
+                    <DarkButton label="delete" on_click={delete} />
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    if *props.open {
+        html! {
+            <div class="popup-bg" ref={bg_ref} onclick={click_bg}>
+                <div class="add-tag-popup">
+                    {rows}
+                    <div class="reassign">
+                        {"questions of deleted tags move to "}
+                        <select onchange={reassign_change}>
+                            <option value="" selected={reassign.is_none()}>{"no tag"}</option>
+                            { for props.tags.tags.iter().map(|tag| html! {
+                                <option value={tag.id.0.to_string()} selected={*reassign == Some(tag.id)}>{tag.name.clone()}</option>
+                            }) }
+                        </select>
+                    </div>
+                </div>
+            </div>
+        }
+    } else {
+        html! {}
+    }
+}
+
 #[derive(PartialEq, Properties)]
 pub struct AddTagProps {
     pub open: UseStateHandle<bool>,
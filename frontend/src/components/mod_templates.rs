@@ -0,0 +1,111 @@
+use crate::{components::DarkButton, fetch, pages::BASE_API};
+use shared::{EventTemplate, EventTokens};
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, suspense::use_future_with};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Request {
+    Clone { questions: bool },
+    SaveTemplate,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ModTemplatesProps {
+    pub tokens: EventTokens,
+    pub name: String,
+}
+
+/// Moderator actions to start the next event from this one: clone it right away or save its
+/// settings as a template that `/newevent?template=<id>` picks up.
+#[function_component]
+pub fn ModTemplates(props: &ModTemplatesProps) -> Html {
+    let questions_ref = use_node_ref();
+    let request = use_state(|| None::<Request>);
+    let template = use_state(|| None::<EventTemplate>);
+
+    let _ = use_future_with(request, {
+        let tokens = props.tokens.clone();
+        // template names are capped at 30 chars, event names can be longer
+        let name: String = props.name.chars().take(30).collect();
+        let template = template.clone();
+
+        |request| async move {
+            let Some(req) = **request else {
+                return;
+            };
+            request.set(None);
+
+            let id = tokens.public_token.clone();
+            let secret = tokens.moderator_token.clone().unwrap_throw();
+
+            match req {
+                Request::Clone { questions } => {
+                    match fetch::mod_clone_event(BASE_API, id, secret, questions).await {
+                        Ok(e) => {
+                            let url = format!(
+                                "/eventmod/{}/{}",
+                                e.tokens.public_token,
+                                e.tokens.moderator_token.unwrap_throw()
+                            );
+                            gloo_utils::window().location().assign(&url).unwrap_throw();
+                        }
+                        Err(e) => log::error!("mod_clone_event error: {e}"),
+                    }
+                }
+                Request::SaveTemplate => {
+                    match fetch::mod_save_template(BASE_API, id, secret, name).await {
+                        Ok(saved) => template.set(Some(saved)),
+                        Err(e) => log::error!("mod_save_template error: {e}"),
+                    }
+                }
+            }
+        }
+    });
+
+    let click_clone = Callback::from({
+        let request = request.clone();
+        let questions_ref = questions_ref.clone();
+        move |()| {
+            let questions = questions_ref
+                .cast::<HtmlInputElement>()
+                .is_some_and(|input| input.checked());
+            request.set(Some(Request::Clone { questions }));
+        }
+    });
+
+    let click_template = Callback::from({
+        let request = request.clone();
+        move |()| {
+            request.set(Some(Request::SaveTemplate));
+        }
+    });
+
+    let template_link = (*template).as_ref().map_or_else(
+        || html! {},
+        |template| {
+            let origin = gloo_utils::window().location().origin().unwrap_or_default();
+            let url = format!("{origin}/newevent?template={}", template.id);
+
+            html! {
+                <div class="template-link">
+                    {"New events from this template: "}
+                    <a href={url.clone()}>{url}</a>
+                </div>
+            }
+        },
+    );
+
+    html! {
+        <div class="mod-templates">
+            <div class="questions-seperator">{"NEXT EVENT"}</div>
+            <label>
+                <input ref={questions_ref} type="checkbox" />
+                {"keep open questions"}
+            </label>
+            <DarkButton label="clone event" on_click={click_clone} />
+            <DarkButton label="save as template" on_click={click_template} />
+            {template_link}
+        </div>
+    }
+}
@@ -0,0 +1,378 @@
+use crate::{
+    components::{DarkButton, RedButton},
+    fetch,
+    local_cache::LocalCache,
+    pages::BASE_API,
+};
+use shared::{AddPoll, EventTokens, ModPoll, Poll, PollKind, PollValidation};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlTextAreaElement};
+use yew::{prelude::*, suspense::use_future_with};
+
+#[derive(PartialEq, Properties)]
+pub struct PollsProps {
+    pub tokens: EventTokens,
+    pub polls: Vec<Poll>,
+    pub is_mod: bool,
+    pub can_vote: bool,
+}
+
+#[function_component]
+pub fn Polls(props: &PollsProps) -> Html {
+    let add_popup_open = use_state(|| false);
+
+    let on_click: Callback<()> = Callback::from({
+        let add_popup_open = add_popup_open.clone();
+        move |()| {
+            add_popup_open.set(true);
+        }
+    });
+
+    let polls = props
+        .polls
+        .iter()
+        .rev()
+        .filter(|poll| props.is_mod || !poll.hidden)
+        .map(|poll| {
+            html! {
+                <PollCard
+                    key={poll.id}
+                    poll={poll.clone()}
+                    tokens={props.tokens.clone()}
+                    is_mod={props.is_mod}
+                    can_vote={props.can_vote}
+                />
+            }
+        })
+        .collect::<Html>();
+
+    let add_poll = if props.is_mod && props.polls.len() < shared::MAX_POLLS {
+        html! {
+            <>
+            <AddPollPopup open={add_popup_open.clone()} tokens={props.tokens.clone()} />
+            <DarkButton label="add poll" {on_click}/>
+            </>
+        }
+    } else {
+        html! {}
+    };
+
+    if props.polls.is_empty() && !props.is_mod {
+        return html! {};
+    }
+
+    html! {
+        <div class="polls">
+            <div class="questions-seperator">{"POLLS"}</div>
+            {polls}
+            {add_poll}
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct PollCardProps {
+    pub tokens: EventTokens,
+    pub poll: Poll,
+    pub is_mod: bool,
+    pub can_vote: bool,
+}
+
+#[function_component]
+fn PollCard(props: &PollCardProps) -> Html {
+    let event_id = props.tokens.public_token.clone();
+    let poll_id = props.poll.id;
+
+    let voted = use_state(|| LocalCache::is_poll_voted(&event_id, poll_id));
+    let selected = use_state(Vec::<usize>::new);
+    let vote = use_state(|| None::<Vec<usize>>);
+    let edit = use_state(|| None::<ModPoll>);
+
+    let _ = use_future_with(vote, {
+        let voted = voted.clone();
+        let event_id = event_id.clone();
+
+        move |vote| async move {
+            if let Some(choices) = &**vote {
+                vote.set(None);
+
+                match fetch::vote_poll(BASE_API, event_id.clone(), poll_id, choices.clone()).await {
+                    Ok(_) => {
+                        LocalCache::set_poll_voted(&event_id, poll_id);
+                        voted.set(true);
+                    }
+                    Err(e) => log::error!("vote_poll error: {e}"),
+                }
+            }
+        }
+    });
+
+    let _ = use_future_with(edit, {
+        let tokens = props.tokens.clone();
+
+        |edit| async move {
+            if let Some(state) = &**edit {
+                edit.set(None);
+
+                if let Err(e) = fetch::mod_edit_poll(
+                    BASE_API,
+                    tokens.public_token.clone(),
+                    tokens.moderator_token.clone().unwrap_throw(),
+                    poll_id,
+                    *state,
+                )
+                .await
+                {
+                    log::error!("mod_edit_poll error: {e}");
+                }
+            }
+        }
+    });
+
+    let poll = &props.poll;
+    let show_results = props.is_mod || *voted || !poll.is_open() || !props.can_vote;
+
+    let options = poll
+        .options
+        .iter()
+        .enumerate()
+        .map(|(idx, option)| {
+            if show_results {
+                let percent = if poll.voters == 0 {
+                    0
+                } else {
+                    option.votes * 100 / poll.voters
+                };
+
+                html! {
+                    <div class="option result">
+                        <div class="bar" style={format!("width: {percent}%")} />
+                        <div class="text">{option.text.clone()}</div>
+                        <div class="votes">{format!("{percent}%")}</div>
+                    </div>
+                }
+            } else {
+                let onclick = Callback::from({
+                    let selected = selected.clone();
+                    let kind = poll.kind;
+                    move |_: MouseEvent| {
+                        let mut choices = (*selected).clone();
+                        if choices.contains(&idx) {
+                            choices.retain(|choice| *choice != idx);
+                        } else if kind == PollKind::SingleChoice {
+                            choices = vec![idx];
+                        } else {
+                            choices.push(idx);
+                        }
+                        selected.set(choices);
+                    }
+                });
+
+                html! {
+                    <div
+                        class={classes!("option", selected.contains(&idx).then_some("selected"))}
+                        {onclick}
+                    >
+                        <div class="text">{option.text.clone()}</div>
+                    </div>
+                }
+            }
+        })
+        .collect::<Html>();
+
+    let vote_button = if show_results {
+        html! {
+            <div class="voters">{format!("{} votes", poll.voters)}</div>
+        }
+    } else {
+        let on_click = Callback::from({
+            let selected = selected.clone();
+            let vote = vote.clone();
+            let poll = poll.clone();
+            move |()| {
+                if poll.valid_choices(&*selected) {
+                    vote.set(Some((*selected).clone()));
+                }
+            }
+        });
+
+        html! {
+            <RedButton label="Vote" {on_click} />
+        }
+    };
+
+    let mod_buttons = if props.is_mod {
+        let toggle_closed = Callback::from({
+            let edit = edit.clone();
+            let state = ModPoll {
+                closed: !poll.closed,
+                hidden: poll.hidden,
+            };
+            move |()| edit.set(Some(state))
+        });
+        let toggle_hidden = Callback::from({
+            let edit = edit.clone();
+            let state = ModPoll {
+                closed: poll.closed,
+                hidden: !poll.hidden,
+            };
+            move |()| edit.set(Some(state))
+        });
+
+        html! {
+            <div class="mod-buttons">
+                <DarkButton label={if poll.closed {"reopen"} else {"close"}} on_click={toggle_closed} />
+                <DarkButton label={if poll.hidden {"show"} else {"hide"}} on_click={toggle_hidden} />
+            </div>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class={classes!("poll", poll.closed.then_some("closed"), poll.hidden.then_some("hidden"))}>
+            <div class="question">{poll.question.clone()}</div>
+            {
+                if poll.kind == PollKind::MultipleChoice && !show_results {
+                    html! { <div class="hint">{"choose one or more"}</div> }
+                } else {
+                    html! {}
+                }
+            }
+            {options}
+            {vote_button}
+            {mod_buttons}
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct AddPollPopupProps {
+    pub open: UseStateHandle<bool>,
+    pub tokens: EventTokens,
+}
+
+#[function_component]
+fn AddPollPopup(props: &AddPollPopupProps) -> Html {
+    let bg_ref = use_node_ref();
+    let question_ref = use_node_ref();
+    let options_ref = use_node_ref();
+    let multiple_ref = use_node_ref();
+
+    let click_bg = Callback::from({
+        let open = props.open.clone();
+        let bg_ref = bg_ref.clone();
+        move |e: MouseEvent| {
+            let div = bg_ref
+                .cast::<HtmlElement>()
+                .expect_throw("div_ref not attached to div element");
+
+            let target = e.target().unwrap_throw();
+            let target: HtmlElement = target.dyn_into().unwrap_throw();
+
+            if div == target {
+                open.set(false);
+            }
+        }
+    });
+
+    let poll_to_add = use_state(|| None::<AddPoll>);
+    let invalid = use_state(|| false);
+
+    let on_click: Callback<()> = Callback::from({
+        let open = props.open.clone();
+        let question_ref = question_ref.clone();
+        let options_ref = options_ref.clone();
+        let multiple_ref = multiple_ref.clone();
+        let poll_to_add = poll_to_add.clone();
+        let invalid = invalid.clone();
+        move |()| {
+            let question = question_ref
+                .cast::<HtmlInputElement>()
+                .expect_throw("question_ref not attached to input element")
+                .value();
+            let options = options_ref
+                .cast::<HtmlTextAreaElement>()
+                .expect_throw("options_ref not attached to textarea element")
+                .value()
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let kind = if multiple_ref
+                .cast::<HtmlInputElement>()
+                .expect_throw("multiple_ref not attached to input element")
+                .checked()
+            {
+                PollKind::MultipleChoice
+            } else {
+                PollKind::SingleChoice
+            };
+
+            let mut valid = PollValidation::default();
+            valid.check(&question, &options);
+
+            invalid.set(valid.has_any());
+
+            if !valid.has_any() {
+                poll_to_add.set(Some(AddPoll {
+                    question,
+                    kind,
+                    options,
+                }));
+                open.set(false);
+            }
+        }
+    });
+
+    let _ = use_future_with(poll_to_add, {
+        let tokens = props.tokens.clone();
+
+        |poll_to_add| async move {
+            if let Some(poll) = &**poll_to_add {
+                poll_to_add.set(None);
+
+                if let Err(e) = fetch::mod_add_poll(
+                    BASE_API,
+                    tokens.public_token.clone(),
+                    tokens.moderator_token.clone().unwrap_throw(),
+                    poll.clone(),
+                )
+                .await
+                {
+                    log::error!("mod_add_poll error: {e}");
+                }
+            }
+        }
+    });
+
+    if *props.open {
+        html! {
+            <div class="popup-bg" ref={bg_ref} onclick={click_bg}>
+                <div class="add-poll-popup">
+                    <input
+                        ref={question_ref}
+                        type="text"
+                        placeholder="question"
+                        maxlength="100"
+                    />
+                    <textarea
+                        ref={options_ref}
+                        placeholder="one option per line"
+                        rows="5"
+                    />
+                    <label>
+                        <input ref={multiple_ref} type="checkbox" />
+                        {"allow multiple choices"}
+                    </label>
+                    <div class="error" hidden={!*invalid}>
+                        {"Needs a question and 2 to 10 options."}
+                    </div>
+                    <RedButton label="Add" {on_click} />
+                </div>
+            </div>
+        }
+    } else {
+        html! {}
+    }
+}
@@ -276,6 +276,17 @@ impl Component for Question {
             },
         );
 
+        let name = self.data.item.name.as_ref().map_or_else(
+            || html! {},
+            |name| {
+                html! {
+                    <div class={classes!("name",blurred.then_some("blurr"))} data-testid="question-name">
+                        { name.clone() }
+                    </div>
+                }
+            },
+        );
+
         html! {
             <div class={main_classes} ref={self.node_ref.clone()} data-testid="question-item" data-qid={self.data.item.id.to_string()}>
                 <div
@@ -284,6 +295,7 @@ impl Component for Question {
                     onclick={ctx.link().callback(|_| Msg::QuestionClick(QuestionClickType::Like))}
                 >
                     <div class="time-since">{ self.get_age() }</div>
+                    { name }
                     { tag }
                     { if screened {
                             if liked {
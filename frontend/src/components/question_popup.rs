@@ -2,23 +2,30 @@ use super::SharableTags;
 use crate::{
     GlobalEvent,
     components::{Popup, TagSelect, TextArea},
-    fetch,
+    fetch::{self, AddQuestionResult},
     local_cache::LocalCache,
     pages::BASE_API,
     tracking,
 };
 use events::{EventBridge, event_context};
-use shared::{AddQuestionError, AddQuestionValidation, TagId, ValidationState};
+use shared::{
+    AddQuestionError, AddQuestionValidation, ParticipantNameError, ParticipantNameValidation,
+    ParticipantNames, QuestionItem, QuestionLimits, TagId, ValidationState,
+};
 use wasm_bindgen::UnwrapThrowExt;
-use web_sys::{HtmlTextAreaElement, KeyboardEvent};
+use web_sys::{HtmlInputElement, HtmlTextAreaElement, KeyboardEvent};
 use yew::prelude::*;
 
 pub enum Msg {
     GlobalEvent(GlobalEvent),
     Send,
     QuestionCreated(Option<i64>),
+    Similar(String, QuestionItem),
+    LikeSimilar,
+    AskAnyway,
     Close,
     InputChanged(InputEvent),
+    NameChanged(InputEvent),
     KeyEvent(KeyboardEvent),
     Tag(TagId),
 }
@@ -28,6 +35,9 @@ pub struct QuestionPopup {
     text: String,
     tag: Option<TagId>,
     errors: AddQuestionValidation,
+    name: String,
+    name_errors: ParticipantNameValidation,
+    similar: Option<QuestionItem>,
     events: EventBridge<GlobalEvent>,
 }
 
@@ -36,6 +46,8 @@ pub struct AddQuestionProps {
     pub event_id: AttrValue,
     pub current_tag: Option<TagId>,
     pub tags: SharableTags,
+    pub limits: QuestionLimits,
+    pub names: ParticipantNames,
 }
 
 impl Component for QuestionPopup {
@@ -52,6 +64,9 @@ impl Component for QuestionPopup {
             events,
             tag: None,
             errors: AddQuestionValidation::default(),
+            name: String::new(),
+            name_errors: ParticipantNameValidation::default(),
+            similar: None,
             text: String::new(),
         }
     }
@@ -64,6 +79,8 @@ impl Component for QuestionPopup {
                     self.tag = ctx.props().current_tag;
                     self.show = true;
                     self.errors = AddQuestionValidation::default();
+                    self.name_errors.check(Some(&self.name), ctx.props().names);
+                    self.similar = None;
                     return true;
                 }
                 false
@@ -73,29 +90,42 @@ impl Component for QuestionPopup {
                 true
             }
             Msg::Send => {
-                let event_id: String = ctx.props().event_id.to_string();
-                let text = self.text.clone();
-                let tag = self.tag;
-
                 tracking::track_event(tracking::EVNT_ASK_SENT);
+                self.send(ctx, false);
+                true
+            }
+            Msg::AskAnyway => {
+                self.send(ctx, true);
+                true
+            }
+            Msg::Similar(text, item) => {
+                self.text = text;
+                self.similar = Some(item);
+                self.show = true;
+                true
+            }
+            Msg::LikeSimilar => {
+                if let Some(item) = self.similar.take() {
+                    let event_id: String = ctx.props().event_id.to_string();
+
+                    let already_liked = LocalCache::is_liked(&event_id, item.id);
+                    LocalCache::set_like_state(&event_id, item.id, true);
 
-                ctx.link().send_future(async move {
-                    if let Ok(item) =
-                        fetch::add_question(BASE_API, event_id.clone(), text, tag).await
-                    {
-                        LocalCache::set_like_state(&event_id, item.id, true);
-                        if item.screening {
-                            LocalCache::add_unscreened_question(&event_id, &item);
+                    ctx.link().send_future(async move {
+                        if !already_liked {
+                            if let Err(e) =
+                                fetch::like_question(BASE_API, event_id, item.id, true).await
+                            {
+                                log::error!("like question error: {e}");
+                            }
                         }
+
                         Msg::QuestionCreated(Some(item.id))
-                    } else {
-                        Msg::QuestionCreated(None)
-                    }
-                });
+                    });
+                }
 
                 self.show = false;
                 self.text.clear();
-
                 true
             }
             Msg::QuestionCreated(id) => {
@@ -107,11 +137,18 @@ impl Component for QuestionPopup {
             Msg::InputChanged(ev) => {
                 let target: HtmlTextAreaElement = ev.target_dyn_into().unwrap_throw();
                 self.text = target.value();
-                self.errors.check(&self.text);
+                self.similar = None;
+                self.errors.check(&self.text, &ctx.props().limits);
+                true
+            }
+            Msg::NameChanged(ev) => {
+                let target: HtmlInputElement = ev.target_dyn_into().unwrap_throw();
+                self.name = target.value();
+                self.name_errors.check(Some(&self.name), ctx.props().names);
                 true
             }
             Msg::KeyEvent(e) => {
-                if &e.key() == "Enter" && e.meta_key() && !self.errors.has_any() {
+                if &e.key() == "Enter" && e.meta_key() && !self.has_errors() {
                     ctx.link().callback(|()| Msg::Send).emit(());
                 }
                 true
@@ -127,6 +164,7 @@ impl Component for QuestionPopup {
     fn view(&self, ctx: &Context<Self>) -> Html {
         if self.show {
             let tags = SharableTags::clone(&ctx.props().tags);
+            let max_len = ctx.props().limits.max_len;
 
             let on_close = ctx.link().callback(|()| Msg::Close);
             let on_click_ask = ctx.link().callback(|_| Msg::Send);
@@ -141,7 +179,7 @@ impl Component for QuestionPopup {
                                 id="questiontext"
                                 testid="question-input"
                                 name="questiontext"
-                                maxlength="200"
+                                maxlength={max_len.to_string()}
                                 value={self.text.clone()}
                                 placeholder="What’s your question?"
                                 required=true
@@ -150,7 +188,7 @@ impl Component for QuestionPopup {
                             />
                             <div class="more-info">
                                 <div class="chars-info">
-                                    <code>{ format!("{}",200 - self.text.len()) }</code>
+                                    <code>{ format!("{}", max_len.saturating_sub(self.text.len())) }</code>
                                 </div>
                                 { html!{
                                 <div hidden={!self.errors.has_any()} class="invalid">
@@ -160,16 +198,25 @@ impl Component for QuestionPopup {
                                 </div>
                             } }
                             </div>
+                            { self.view_name(ctx) }
                             <TagSelect {tags} tag_selected={on_tag} tag={self.tag}/>
                         </div>
-                        <button
-                            class="dlg-button"
-                            data-testid="question-submit"
-                            onclick={on_click_ask}
-                            disabled={self.errors.has_any()}
-                        >
-                            { "Ask!" }
-                        </button>
+                        {
+                            if let Some(similar) = &self.similar {
+                                Self::view_similar(ctx, similar)
+                            } else {
+                                html! {
+                                    <button
+                                        class="dlg-button"
+                                        data-testid="question-submit"
+                                        onclick={on_click_ask}
+                                        disabled={self.has_errors()}
+                                    >
+                                        { "Ask!" }
+                                    </button>
+                                }
+                            }
+                        }
                     </div>
                 </Popup>
             }
@@ -180,6 +227,111 @@ impl Component for QuestionPopup {
 }
 
 impl QuestionPopup {
+    fn send(&mut self, ctx: &Context<Self>, allow_similar: bool) {
+        let event_id: String = ctx.props().event_id.to_string();
+        let text = self.text.clone();
+        let tag = self.tag;
+        let name = (ctx.props().names != ParticipantNames::Anonymous
+            && !self.name.trim().is_empty())
+        .then(|| self.name.clone());
+
+        ctx.link().send_future(async move {
+            match fetch::add_question(
+                BASE_API,
+                event_id.clone(),
+                text.clone(),
+                tag,
+                name,
+                allow_similar,
+            )
+            .await
+            {
+                Ok(AddQuestionResult::Added(item)) => {
+                    LocalCache::set_like_state(&event_id, item.id, true);
+                    if item.screening {
+                        LocalCache::add_unscreened_question(&event_id, &item);
+                    }
+                    Msg::QuestionCreated(Some(item.id))
+                }
+                Ok(AddQuestionResult::Similar(item)) => Msg::Similar(text, item),
+                Err(_) => Msg::QuestionCreated(None),
+            }
+        });
+
+        self.show = false;
+        self.similar = None;
+        self.text.clear();
+    }
+
+    fn view_similar(ctx: &Context<Self>, similar: &QuestionItem) -> Html {
+        let on_like = ctx.link().callback(|_| Msg::LikeSimilar);
+        let on_ask = ctx.link().callback(|_| Msg::AskAnyway);
+
+        html! {
+            <div class="similar" data-testid="question-similar">
+                <div class="similar-info">{ "A similar question was already asked:" }</div>
+                <div class="similar-text">{ similar.text.clone() }</div>
+                <button class="dlg-button" data-testid="similar-like" onclick={on_like}>
+                    { "Like it instead" }
+                </button>
+                <button class="dlg-button" data-testid="similar-ask" onclick={on_ask}>
+                    { "Ask anyway" }
+                </button>
+            </div>
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        self.errors.has_any() || self.name_errors.has_any()
+    }
+
+    fn view_name(&self, ctx: &Context<Self>) -> Html {
+        let names = ctx.props().names;
+
+        if names == ParticipantNames::Anonymous {
+            return html! {};
+        }
+
+        let placeholder = if names == ParticipantNames::Required {
+            "Your name"
+        } else {
+            "Your name (optional)"
+        };
+
+        html! {
+            <div class="name-input">
+                <input
+                    type="text"
+                    name="participantname"
+                    data-testid="question-name-input"
+                    {placeholder}
+                    value={self.name.clone()}
+                    maxlength="30"
+                    oninput={ctx.link().callback(Msg::NameChanged)}
+                />
+                <div hidden={!self.name_errors.has_any()} class="invalid">
+                    { self.name_error_text().unwrap_or_default() }
+                </div>
+            </div>
+        }
+    }
+
+    const fn name_error_text(&self) -> Option<&'static str> {
+        match self.name_errors.name {
+            ValidationState::Invalid(ParticipantNameError::Required) => Some("Name required."),
+            ValidationState::Invalid(ParticipantNameError::MinLength(_, _)) => {
+                Some("Name too short.")
+            }
+            ValidationState::Invalid(ParticipantNameError::MaxLength(_, _)) => {
+                Some("Name too long.")
+            }
+            ValidationState::Invalid(ParticipantNameError::NotAllowed) => {
+                Some("Questions are anonymous.")
+            }
+            _ => None,
+        }
+    }
+
     fn error_text(&self) -> Option<String> {
         match self.errors.content {
             ValidationState::Invalid(AddQuestionError::MinLength(_, _)) => {
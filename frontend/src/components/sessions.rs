@@ -0,0 +1,91 @@
+use chrono::{DateTime, Local, Utc};
+use shared::{EventSessions, EventState, Session, SessionId};
+use std::rc::Rc;
+use yew::prelude::*;
+
+pub type SharableSessions = Rc<EventSessions>;
+
+#[derive(PartialEq, Properties)]
+pub struct SessionsProps {
+    pub sessions: SharableSessions,
+    /// only questions of this session are shown
+    pub filter: Option<SessionId>,
+    pub on_filter: Callback<Option<SessionId>>,
+}
+
+pub fn format_time(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|t| t.with_timezone(&Local).format("%a %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+pub fn time_window(session: &Session) -> String {
+    match (session.data.start_unix, session.data.end_unix) {
+        (Some(start), Some(end)) => format!("{} - {}", format_time(start), format_time(end)),
+        (Some(start), None) => format_time(start),
+        (None, Some(end)) => format!("until {}", format_time(end)),
+        (None, None) => String::new(),
+    }
+}
+
+pub const fn state_label(state: EventState) -> &'static str {
+    match state.state {
+        shared::States::Open => "open",
+        shared::States::VotingOnly => "vote only",
+        shared::States::Closed => "closed",
+    }
+}
+
+/// Agenda of a multi-session event: the active session on top and all sessions to filter the
+/// questions by.
+#[function_component]
+pub fn Sessions(props: &SessionsProps) -> Html {
+    if props.sessions.is_empty() {
+        return html! {};
+    }
+
+    let active = props.sessions.active(Utc::now().timestamp());
+
+    let now = active.map_or_else(
+        || html! { <div class="now">{"no session running right now"}</div> },
+        |session| {
+            html! {
+                <div class="now">
+                    {"NOW: "}
+                    <strong>{session.data.name.clone()}</strong>
+                    {format!(" ({})", state_label(session.state))}
+                </div>
+            }
+        },
+    );
+
+    html! {
+        <div class="sessions">
+            {now}
+            <div class="agenda">
+                {for props.sessions.sessions.iter().map(|session| {
+                    let id = session.id;
+                    let is_filter = props.filter == Some(id);
+                    let onclick = Callback::from({
+                        let on_filter = props.on_filter.clone();
+                        move |_| on_filter.emit(if is_filter { None } else { Some(id) })
+                    });
+
+                    let classes = classes!(
+                        "session",
+                        is_filter.then_some("current"),
+                        active.is_some_and(|active| active.id == id).then_some("active"),
+                        session.state.is_closed().then_some("closed"),
+                    );
+
+                    html! {
+                        <div class={classes} {onclick} key={id.0}>
+                            <div class="name">{session.data.name.clone()}</div>
+                            <div class="time">{time_window(session)}</div>
+                        </div>
+                    }
+                })}
+            </div>
+        </div>
+    }
+}
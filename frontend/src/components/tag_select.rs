@@ -22,10 +22,13 @@ pub fn TagSelect(props: &TagSelectProps) -> Html {
         }
     });
 
-    let tag = props.tag.and_then(|tag| props.tags.get(&tag).cloned());
+    let tag = props
+        .tag
+        .and_then(|tag| props.tags.get(tag))
+        .map(|tag| tag.name.clone());
 
     html! {
-        <div class="tag-select" hidden={props.tags.is_empty()}>
+        <div class="tag-select" hidden={props.tags.tags.is_empty()}>
             <div class="header">{"tag your question"}</div>
             <Tags tags={SharableTags::clone(&props.tags)} {tag} {tag_click} />
         </div>
@@ -12,17 +12,9 @@ pub struct TagsProps {
 
 #[function_component]
 pub fn Tags(props: &TagsProps) -> Html {
-    //TODO: use_effect
-    let mut tags = props
-        .tags
-        .iter()
-        .map(|(k, v)| (*k, v.clone()))
-        .collect::<Vec<_>>();
-    tags.sort_by(|a, b| a.1.cmp(&b.1));
-
     html! {
         <div class="tags">
-            {for tags.iter().map(|(id,tag)|{
+            {for props.tags.tags.iter().map(|shared::Tag { name: tag, id }|{
                 let is_current = props.tag.as_ref().is_some_and(|current| tag == current);
 
                 let onclick = if is_current {
@@ -1,10 +1,14 @@
 use crate::{
     components::{Spinner, UpgradeButton},
+    fetch,
     local_cache::LocalCache,
+    pages::BASE_API,
     tracking,
 };
 use shared::{EventTokens, ModRequestPremiumContext};
 use std::ops::Not;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, Eq, Properties)]
@@ -16,9 +20,15 @@ pub struct Props {
 pub struct Upgrade {
     data: Props,
     collapsed: bool,
+    voucher: String,
+    voucher_failed: bool,
 }
 pub enum Msg {
     ToggleExpansion,
+    VoucherInput(InputEvent),
+    RedeemVoucher,
+    /// the event update that follows a redemption hides the banner
+    VoucherRedeemed(bool),
 }
 impl Component for Upgrade {
     type Message = Msg;
@@ -31,6 +41,8 @@ impl Component for Upgrade {
         Self {
             data: ctx.props().clone(),
             collapsed,
+            voucher: String::new(),
+            voucher_failed: false,
         }
     }
 
@@ -44,6 +56,42 @@ impl Component for Upgrade {
                 }
                 true
             }
+            Msg::VoucherInput(e) => {
+                let target: HtmlInputElement = e.target_dyn_into().unwrap_throw();
+                self.voucher = target.value();
+                self.voucher_failed = false;
+                true
+            }
+            Msg::RedeemVoucher => {
+                let tokens = ctx.props().tokens.clone();
+                let code = self.voucher.trim().to_string();
+
+                ctx.link().send_future(async move {
+                    match fetch::mod_redeem_voucher(
+                        BASE_API,
+                        tokens.public_token,
+                        tokens.moderator_token.unwrap_throw(),
+                        code,
+                    )
+                    .await
+                    {
+                        Ok(()) => Msg::VoucherRedeemed(true),
+                        Err(e) => {
+                            log::error!("mod_redeem_voucher error: {e}");
+                            Msg::VoucherRedeemed(false)
+                        }
+                    }
+                });
+
+                false
+            }
+            Msg::VoucherRedeemed(redeemed) => {
+                self.voucher_failed = !redeemed;
+                if redeemed {
+                    self.voucher.clear();
+                }
+                true
+            }
         }
     }
 
@@ -107,6 +155,34 @@ impl Upgrade {
                 </div>
 
                 <UpgradeButton tokens={self.data.tokens.clone()} {pending} context={ModRequestPremiumContext::Regular} />
+
+                { self.view_voucher(ctx) }
+            </div>
+        }
+    }
+
+    fn view_voucher(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="voucher">
+                <input
+                    type="text"
+                    placeholder="voucher code"
+                    maxlength="32"
+                    value={self.voucher.clone()}
+                    oninput={ctx.link().callback(Msg::VoucherInput)}
+                />
+                <button
+                    class="button-white"
+                    disabled={self.voucher.trim().is_empty()}
+                    onclick={ctx.link().callback(|_| Msg::RedeemVoucher)}
+                >
+                    { "redeem" }
+                </button>
+                { if self.voucher_failed {
+                    html! { <div class="error">{ "This voucher is invalid, used up or expired." }</div> }
+                } else {
+                    html! {}
+                } }
             </div>
         }
     }
@@ -2,10 +2,13 @@
 
 use gloo_net::http::Request;
 use shared::{
-    AddEvent, AddQuestion, EditLike, EventData, EventInfo, EventPasswordRequest,
-    EventPasswordResponse, EventUpgradeResponse, GetEventResponse, GetUserInfo, ModEvent,
-    ModQuestion, ModRequestPremium, PaymentCapture, QuestionItem, SubscriptionCheckout,
-    SubscriptionResponse, SubscriptionUrlResponse, TagId, UserLogin, VersionInfo,
+    AddEvent, AddPoll, AddQuestion, CloneEvent, EditLike, EventData, EventInfo,
+    EventPasswordRequest, EventPasswordResponse, EventTemplate, EventUpgradeResponse,
+    GetEventResponse, GetUserInfo, ModEvent, ModLinkRecovery, ModPoll, ModQuestion,
+    ModRedeemVoucher, ModRequestPremium, OrganizerDashboard, OrganizerLogin, OrganizerLoginVerify,
+    PaymentCapture, Poll, PollVote, QuestionItem, SaveTemplate, SimilarQuestion,
+    SubscriptionCheckout, SubscriptionResponse, SubscriptionUrlResponse, TagId, UserLogin,
+    VersionInfo,
 };
 use std::{
     error::Error,
@@ -55,6 +58,11 @@ fn set_content_type_json(request: &Request) {
     request.headers().set("content-type", "application/json");
 }
 
+/// Moderator secret as bearer token, keeping it out of URLs (and thus access logs).
+fn mod_auth(secret: &str) -> String {
+    format!("Bearer {secret}")
+}
+
 pub async fn fetch_version(base_api: &str) -> Result<VersionInfo, FetchError> {
     let url = format!("{base_api}/api/version");
 
@@ -66,12 +74,13 @@ pub async fn fetch_event(
     id: String,
     secret: Option<String>,
 ) -> Result<GetEventResponse, FetchError> {
-    let url = secret.map_or_else(
-        || format!("{base_api}/api/event/{id}"),
-        |secret| format!("{base_api}/api/mod/event/{id}/{secret}"),
-    );
+    let request = match secret {
+        Some(secret) => Request::get(&format!("{base_api}/api/mod/event/{id}"))
+            .header("authorization", &mod_auth(&secret)),
+        None => Request::get(&format!("{base_api}/api/event/{id}")),
+    };
 
-    Ok(Request::get(&url)
+    Ok(request
         .credentials(RequestCredentials::Include)
         .send()
         .await?
@@ -85,15 +94,113 @@ pub async fn mod_edit_event(
     secret: String,
     change: ModEvent,
 ) -> Result<EventInfo, FetchError> {
-    let url = format!("{base_api}/api/mod/event/{id}/{secret}");
+    let url = format!("{base_api}/api/mod/event/{id}");
 
     let body = JsValue::from_str(&serde_json::to_string(&change)?);
 
-    let request = Request::post(&url).body(body)?;
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .body(body)?;
+    set_content_type_json(&request);
+    Ok(request.send().await?.json().await?)
+}
+
+pub async fn mod_clone_event(
+    base_api: &str,
+    id: String,
+    secret: String,
+    questions: bool,
+) -> Result<EventInfo, FetchError> {
+    let url = format!("{base_api}/api/mod/event/{id}/clone");
+
+    let body = JsValue::from_str(&serde_json::to_string(&CloneEvent {
+        questions,
+        customer: None,
+    })?);
+
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .body(body)?;
+    set_content_type_json(&request);
+    Ok(request.send().await?.json().await?)
+}
+
+pub async fn mod_save_template(
+    base_api: &str,
+    id: String,
+    secret: String,
+    name: String,
+) -> Result<EventTemplate, FetchError> {
+    let url = format!("{base_api}/api/mod/event/{id}/template");
+
+    let body = JsValue::from_str(&serde_json::to_string(&SaveTemplate { name })?);
+
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .body(body)?;
     set_content_type_json(&request);
     Ok(request.send().await?.json().await?)
 }
 
+pub async fn mod_add_poll(
+    base_api: &str,
+    id: String,
+    secret: String,
+    poll: AddPoll,
+) -> Result<EventInfo, FetchError> {
+    let url = format!("{base_api}/api/mod/event/{id}/poll");
+
+    let body = JsValue::from_str(&serde_json::to_string(&poll)?);
+
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .body(body)?;
+    set_content_type_json(&request);
+    Ok(request.send().await?.json().await?)
+}
+
+pub async fn mod_edit_poll(
+    base_api: &str,
+    id: String,
+    secret: String,
+    poll_id: i64,
+    state: ModPoll,
+) -> Result<EventInfo, FetchError> {
+    let url = format!("{base_api}/api/mod/event/{id}/poll/{poll_id}");
+
+    let body = JsValue::from_str(&serde_json::to_string(&state)?);
+
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .body(body)?;
+    set_content_type_json(&request);
+    Ok(request.send().await?.json().await?)
+}
+
+/// `Ok(None)` if this browser already voted on the poll.
+pub async fn vote_poll(
+    base_api: &str,
+    id: String,
+    poll_id: i64,
+    choices: Vec<usize>,
+) -> Result<Option<Poll>, FetchError> {
+    let url = format!("{base_api}/api/event/{id}/poll/{poll_id}/vote");
+
+    let body = JsValue::from_str(&serde_json::to_string(&PollVote { choices })?);
+
+    let request = Request::post(&url)
+        .credentials(RequestCredentials::Include)
+        .body(body)?;
+    set_content_type_json(&request);
+    let resp = request.send().await?;
+
+    if resp.status() == 409 {
+        return Ok(None);
+    }
+
+    Ok(Some(resp.json().await?))
+}
+
 pub async fn event_set_password(
     base_api: &str,
     id: String,
@@ -117,11 +224,15 @@ pub async fn mod_upgrade(
     secret: String,
     context: shared::ModRequestPremiumContext,
 ) -> Result<EventUpgradeResponse, FetchError> {
-    let url = format!("{base_api}/api/mod/event/upgrade/{id}/{secret}");
+    let url = format!("{base_api}/api/mod/event/{id}/upgrade");
 
-    let body = JsValue::from_str(&serde_json::to_string(&ModRequestPremium { context })?);
+    let body = JsValue::from_str(&serde_json::to_string(&ModRequestPremium {
+        context,
+        tier: shared::PremiumTier::SingleEvent,
+    })?);
 
     let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
         .credentials(RequestCredentials::Include)
         .body(body)?;
     set_content_type_json(&request);
@@ -131,6 +242,30 @@ pub async fn mod_upgrade(
     Ok(response)
 }
 
+pub async fn mod_redeem_voucher(
+    base_api: &str,
+    id: String,
+    secret: String,
+    code: String,
+) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/mod/event/{id}/voucher");
+
+    let body = JsValue::from_str(&serde_json::to_string(&ModRedeemVoucher { code })?);
+
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&secret))
+        .credentials(RequestCredentials::Include)
+        .body(body)?;
+    set_content_type_json(&request);
+    let resp = request.send().await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
 pub async fn mod_premium_capture(
     base_api: &str,
     id: String,
@@ -163,12 +298,13 @@ pub async fn mod_question(
     question_id: i64,
     modify: ModQuestion,
 ) -> Result<(), FetchError> {
-    let url =
-        format!("{base_api}/api/mod/event/questionmod/{event_id}/{event_secret}/{question_id}");
+    let url = format!("{base_api}/api/mod/event/{event_id}/question/{question_id}");
 
     let body = JsValue::from_str(&serde_json::to_string(&modify)?);
 
-    let request = Request::post(&url).body(body)?;
+    let request = Request::post(&url)
+        .header("authorization", &mod_auth(&event_secret))
+        .body(body)?;
     set_content_type_json(&request);
     let resp = request.send().await?;
 
@@ -179,19 +315,39 @@ pub async fn mod_question(
     }
 }
 
+/// Outcome of asking a question: posted, or held back because a similar one already exists.
+pub enum AddQuestionResult {
+    Added(QuestionItem),
+    Similar(QuestionItem),
+}
+
 pub async fn add_question(
     base_api: &str,
     event_id: String,
     text: String,
     tag: Option<TagId>,
-) -> Result<QuestionItem, FetchError> {
+    name: Option<String>,
+    allow_similar: bool,
+) -> Result<AddQuestionResult, FetchError> {
     let url = format!("{base_api}/api/event/addquestion/{event_id}");
 
-    let body = JsValue::from_str(&serde_json::to_string(&AddQuestion { text, tag })?);
+    let body = JsValue::from_str(&serde_json::to_string(&AddQuestion {
+        text,
+        tag,
+        allow_similar,
+        name,
+    })?);
 
     let request = Request::post(&url).body(body)?;
     set_content_type_json(&request);
-    Ok(request.send().await?.json().await?)
+    let resp = request.send().await?;
+
+    if resp.status() == 409 {
+        let similar: SimilarQuestion = resp.json().await?;
+        return Ok(AddQuestionResult::Similar(similar.question));
+    }
+
+    Ok(AddQuestionResult::Added(resp.json().await?))
 }
 
 pub async fn create_event(
@@ -200,6 +356,8 @@ pub async fn create_event(
     desc: String,
     email: Option<String>,
     customer: Option<String>,
+    template: Option<String>,
+    no_third_parties: bool,
 ) -> Result<EventInfo, FetchError> {
     let url = format!("{base_api}/api/event/add");
 
@@ -214,9 +372,14 @@ pub async fn create_event(
         test: false,
         customer,
         moderator_email: email,
+        template,
+        no_third_parties,
     })?);
 
-    let request = Request::post(&url).body(body)?;
+    // an organizer session cookie puts the new event on the organizer's account
+    let request = Request::post(&url)
+        .credentials(RequestCredentials::Include)
+        .body(body)?;
     set_content_type_json(&request);
     Ok(request.send().await?.json().await?)
 }
@@ -299,9 +462,127 @@ pub async fn delete_event(
     event_id: String,
     secret: String,
 ) -> Result<(), FetchError> {
-    let url = format!("{base_api}/api/mod/event/delete/{event_id}/{secret}");
+    let url = format!("{base_api}/api/mod/event/{event_id}/delete");
+
+    let resp = Request::get(&url)
+        .header("authorization", &mod_auth(&secret))
+        .send()
+        .await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+pub async fn organizer_login(base_api: &str, email: String) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/organizer/login");
+    let body = JsValue::from_str(&serde_json::to_string(&OrganizerLogin { email })?);
+
+    let request = Request::post(&url).body(body)?;
+    set_content_type_json(&request);
+    let resp = request.send().await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
 
-    let resp = Request::get(&url).send().await?;
+pub async fn organizer_verify(base_api: &str, token: String) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/organizer/verify");
+    let body = JsValue::from_str(&serde_json::to_string(&OrganizerLoginVerify { token })?);
+
+    let request = Request::post(&url)
+        .credentials(RequestCredentials::Include)
+        .body(body)?;
+    set_content_type_json(&request);
+    let resp = request.send().await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+/// `None` if not logged in as organizer
+pub async fn organizer_events(base_api: &str) -> Result<Option<OrganizerDashboard>, FetchError> {
+    let url = format!("{base_api}/api/organizer/events");
+
+    let resp = Request::get(&url)
+        .credentials(RequestCredentials::Include)
+        .send()
+        .await?;
+
+    if resp.status() == 403 {
+        Ok(None)
+    } else if resp.ok() {
+        Ok(Some(resp.json().await?))
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+pub async fn organizer_add_event(
+    base_api: &str,
+    event_id: String,
+    secret: String,
+) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/organizer/events/{event_id}");
+
+    let resp = Request::post(&url)
+        .credentials(RequestCredentials::Include)
+        .header("authorization", &mod_auth(&secret))
+        .send()
+        .await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+pub async fn organizer_remove_event(base_api: &str, event_id: String) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/organizer/events/{event_id}/remove");
+
+    let resp = Request::post(&url)
+        .credentials(RequestCredentials::Include)
+        .send()
+        .await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+pub async fn organizer_logout(base_api: &str) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/organizer/logout");
+
+    let resp = Request::get(&url)
+        .credentials(RequestCredentials::Include)
+        .send()
+        .await?;
+
+    if resp.ok() {
+        Ok(())
+    } else {
+        Err(FetchError::Generic("request failed".into()))
+    }
+}
+
+pub async fn recover_mod_link(base_api: &str, id: String, email: String) -> Result<(), FetchError> {
+    let url = format!("{base_api}/api/event/{id}/recover");
+    let body = JsValue::from_str(&serde_json::to_string(&ModLinkRecovery { email })?);
+
+    let request = Request::post(&url).body(body)?;
+    set_content_type_json(&request);
+    let resp = request.send().await?;
 
     if resp.ok() {
         Ok(())
@@ -22,7 +22,10 @@ use yewdux::{prelude::Dispatch, store::Store};
 
 use crate::{
     components::IconBar,
-    pages::{Event, Home, NewEvent, Print, Privacy, Subscribe},
+    pages::{
+        Event, Home, NewEvent, Organizer, OrganizerVerify, Print, Privacy, RecoverModLink,
+        Subscribe,
+    },
 };
 
 pub const VERSION_STR: &str = env!("CARGO_PKG_VERSION");
@@ -131,6 +134,9 @@ fn switch(switch: Route) -> Html {
         Route::Event { id } => {
             html! { <Event {id} /> }
         }
+        Route::RecoverModLink { id } => {
+            html! { <RecoverModLink {id} /> }
+        }
         Route::Print { id } => {
             html! { <Print {id} /> }
         }
@@ -152,5 +158,11 @@ fn switch(switch: Route) -> Html {
         Route::Login => {
             html! { <AdminLogin /> }
         }
+        Route::Organizer => {
+            html! { <Organizer /> }
+        }
+        Route::OrganizerVerify { token } => {
+            html! { <OrganizerVerify {token} /> }
+        }
     }
 }
@@ -11,6 +11,8 @@ struct EventStore {
     premium_banner_collapsed: bool,
     #[serde(default)]
     mod_color: bool,
+    #[serde(default)]
+    polls: HashSet<i64>,
 }
 
 pub struct LocalCache;
@@ -20,6 +22,16 @@ impl LocalCache {
         Self::get_state(event).likes.contains(&id)
     }
 
+    pub fn is_poll_voted(event: &str, id: i64) -> bool {
+        Self::get_state(event).polls.contains(&id)
+    }
+
+    pub fn set_poll_voted(event: &str, id: i64) {
+        let mut store = Self::get_state(event);
+        store.polls.insert(id);
+        Self::set_state(event, store);
+    }
+
     pub fn is_premium_banner_collapsed(event: &str) -> bool {
         Self::get_state(event).premium_banner_collapsed
     }
@@ -3,25 +3,28 @@ use const_format::formatcp;
 use events::{EventBridge, event_context};
 use serde::Deserialize;
 use shared::{
-    EventFlags, EventInfo, GetEventResponse, ModEvent, ModQuestion, QuestionItem, States,
+    EventFlags, EventInfo, EventState, GetEventResponse, ModEvent, ModQuestion, ParticipantNames,
+    QuestionItem, SessionId, States, TagId,
 };
-use std::{collections::HashMap, rc::Rc, str::FromStr};
+use std::{rc::Rc, str::FromStr};
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::HtmlAnchorElement;
 use yew::prelude::*;
-use yew_router::scope_ext::RouterScopeExt;
+use yew_router::{prelude::Link, scope_ext::RouterScopeExt};
 use yewdux::prelude::*;
 
 use crate::{
     GlobalEvent, State,
     components::{
-        DeletePopup, EventMeta, EventSocket, Footer, ModPassword, ModTags, PasswordPopup, Question,
-        QuestionClickType, QuestionFlags, QuestionPopup, SharableTags, SharePopup, SocketResponse,
+        DeletePopup, EventMeta, EventSocket, Footer, ModOrganizer, ModPassword, ModSessions,
+        ModTags, ModTemplates, PasswordPopup, Polls, Question, QuestionClickType, QuestionFlags,
+        QuestionPopup, Sessions, SharableSessions, SharableTags, SharePopup, SocketResponse, Tags,
         Upgrade,
     },
     environment::{LiveAskEnv, la_env},
     fetch,
     local_cache::LocalCache,
+    routes::Route,
     tracking,
 };
 
@@ -74,6 +77,11 @@ pub struct Event {
     query_params: QueryParams,
     mode: Mode,
     tags: SharableTags,
+    /// only show questions with this tag
+    filter_tag: Option<TagId>,
+    sessions: SharableSessions,
+    /// only show questions of this session
+    filter_session: Option<SessionId>,
     unanswered: Vec<Rc<QuestionItem>>,
     answered: Vec<Rc<QuestionItem>>,
     hidden: Vec<Rc<QuestionItem>>,
@@ -97,10 +105,13 @@ pub enum Msg {
     ModDelete,
     ModExport,
     ModStateChange(yew::Event),
+    ModNamesChange(yew::Event),
     StateChanged,
     PasswordSet,
     CopyLink,
     ModEditScreening,
+    FilterTag(Option<TagId>),
+    FilterSession(Option<SessionId>),
     GlobalEvent(GlobalEvent),
 }
 impl Component for Event {
@@ -141,7 +152,10 @@ impl Component for Event {
             },
             loading_state: LoadingState::Loading,
             state: dispatch.get(),
-            tags: Rc::new(HashMap::new()),
+            tags: Rc::default(),
+            filter_tag: None,
+            sessions: Rc::default(),
+            filter_session: None,
             unanswered: Vec::new(),
             answered: Vec::new(),
             hidden: Vec::new(),
@@ -195,6 +209,32 @@ impl Component for Event {
                 false
             }
 
+            Msg::FilterTag(tag) => {
+                self.filter_tag = tag;
+                true
+            }
+            Msg::FilterSession(session) => {
+                self.filter_session = session;
+                true
+            }
+            Msg::ModNamesChange(ev) => {
+                let e: web_sys::HtmlSelectElement =
+                    ev.target().unwrap_throw().dyn_into().unwrap_throw();
+                let names = ParticipantNames::from_str(e.value().as_str()).unwrap_throw();
+
+                request_event_change(
+                    self.current_event_id.clone(),
+                    ctx.props().secret.clone(),
+                    ModEvent {
+                        participant_names: Some(names),
+                        ..Default::default()
+                    },
+                    ctx.link(),
+                );
+
+                false
+            }
+
             Msg::ModEditScreening => {
                 request_event_change(
                     self.current_event_id.clone(),
@@ -438,7 +478,7 @@ impl Event {
             .map(|e| e.info.questions.clone())
             .unwrap_or_default();
         let mut wtr = WriterBuilder::new().from_writer(vec![]);
-        wtr.write_record(["date (utc)", "text", "state", "likes"])
+        wtr.write_record(["date (utc)", "text", "name", "state", "likes"])
             .unwrap_throw();
         for q in questions {
             let create_time = DateTime::<Utc>::from_naive_utc_and_offset(
@@ -452,6 +492,7 @@ impl Event {
             wtr.write_record(&[
                 create_time.format("%Y-%m-%d %H:%M").to_string(),
                 q.text,
+                q.name.unwrap_or_default(),
                 state,
                 q.likes.to_string(),
             ])?;
@@ -509,6 +550,10 @@ impl Event {
             let tags = SharableTags::clone(&self.tags);
             let current_tag = e.info.tags.current_tag;
             let screening_enabled = e.info.flags.contains(EventFlags::SCREENING);
+            let session_state = e
+                .info
+                .sessions
+                .effective_state(e.info.state, Utc::now().timestamp());
 
             let color = self
                 .state
@@ -528,7 +573,7 @@ impl Event {
                         show={e.is_wrong_pwd()}
                         onconfirmed={ctx.link().callback(|()|Msg::PasswordSet)}
                     />
-                    <QuestionPopup event_id={e.info.tokens.public_token.clone()} {current_tag} tags={SharableTags::clone(&tags)} />
+                    <QuestionPopup event_id={e.info.tokens.public_token.clone()} {current_tag} tags={SharableTags::clone(&tags)} limits={e.info.question_limits} names={e.info.participant_names} />
                     <SharePopup url={share_url} event_id={e.info.tokens.public_token.clone()} />
                     <div class="event-block">
                         <EventMeta
@@ -549,6 +594,9 @@ impl Event {
                         <div class="not-open" hidden={!e.info.state.is_vote_only()}>
                             { "This event is set to vote-only by the moderator. You cannot add new questions. You can still vote though." }
                         </div>
+                        <div class="not-open" hidden={!e.info.state.is_open() || session_state.is_open()}>
+                            { "The current session does not take new questions anymore." }
+                        </div>
                         <div class="not-open" hidden={!e.is_timed_out()}>
                             { "This free event timed out. Only the moderator can upgrade it to be accessible again." }
                         </div>
@@ -556,11 +604,22 @@ impl Event {
                     { self.mod_urls(ctx,admin) }
                     <div class="event-area" style={format!("background-color: {color}")}>
                         { self.view_stats() }
+                        <Polls
+                            tokens={e.info.tokens.clone()}
+                            polls={e.info.polls.clone()}
+                            is_mod={mod_view}
+                            can_vote={!e.is_closed()}
+                        />
                         <div class="review-note" hidden={!screening_enabled || mod_view}>
                         { "Moderator enabled question reviewing. New questions have to be approved first." }
                         </div>
+                        <Sessions
+                            sessions={SharableSessions::clone(&self.sessions)}
+                            filter={self.filter_session}
+                            on_filter={ctx.link().callback(Msg::FilterSession)}
+                        />
                         { self.view_questions(ctx,e) }
-                        { Self::view_ask_question(mod_view,ctx,e) }
+                        { Self::view_ask_question(mod_view,ctx,session_state) }
                     </div>
                 </div>
             }
@@ -568,12 +627,12 @@ impl Event {
     }
 
     #[allow(clippy::if_not_else)]
-    fn view_ask_question(mod_view: bool, ctx: &Context<Self>, e: &GetEventResponse) -> Html {
+    fn view_ask_question(mod_view: bool, ctx: &Context<Self>, state: EventState) -> Html {
         if mod_view {
             html! {}
         } else {
             html! {
-                <div class="addquestion" hidden={!e.info.state.is_open()}>
+                <div class="addquestion" hidden={!state.is_open()}>
                     <button
                         class="button-red"
                         data-testid="ask-button"
@@ -596,8 +655,16 @@ impl Event {
         } else {
             let can_vote = !e.is_closed();
             let is_mod = self.is_mod();
+            let filter = self
+                .filter_tag
+                .and_then(|tag| self.tags.get(tag))
+                .map(|tag| tag.name.clone());
             html! {
                 <>
+                    <div class="tag-filter" hidden={self.tags.tags.is_empty()}>
+                        <div class="header">{"filter by tag"}</div>
+                        <Tags tags={SharableTags::clone(&self.tags)} tag={filter} tag_click={ctx.link().callback(Msg::FilterTag)} />
+                    </div>
                     { self.view_items(ctx,&self.unscreened,if is_mod {"For review"} else {"Your Questions in review by host"},can_vote) }
                     { self.view_items(ctx,&self.unanswered,"Hot Questions",can_vote) }
                     { self.view_items(ctx,&self.answered,"Answered",can_vote) }
@@ -614,6 +681,12 @@ impl Event {
         title: &str,
         can_vote: bool,
     ) -> Html {
+        let items = items
+            .iter()
+            .filter(|item| self.filter_tag.is_none() || item.tag == self.filter_tag)
+            .filter(|item| self.filter_session.is_none() || item.session == self.filter_session)
+            .collect::<Vec<_>>();
+
         if !items.is_empty() {
             let masked = self.state.event.as_ref().is_some_and(|e| e.masked);
 
@@ -647,14 +720,21 @@ impl Event {
         flags.set(QuestionFlags::NEW_QUESTION, is_new);
         flags.set(QuestionFlags::MOD_VIEW, mod_view);
         flags.set(QuestionFlags::LOCAL_LIKE, local_like);
-        flags.set(QuestionFlags::CAN_VOTE, can_vote);
+        flags.set(
+            QuestionFlags::CAN_VOTE,
+            can_vote
+                && !self
+                    .sessions
+                    .question_state(EventState::default(), item.session)
+                    .is_closed(),
+        );
         flags.set(QuestionFlags::BLURR, blurr);
 
         let tag = item
             .tag
             .as_ref()
-            .and_then(|tag| self.tags.get(tag))
-            .cloned();
+            .and_then(|tag| self.tags.get(*tag))
+            .map(|tag| tag.name.clone());
 
         html! {
             <Question
@@ -693,6 +773,7 @@ impl Event {
                 <div class="mod-panel">
                     <DeletePopup tokens={e.info.tokens.clone()} />
                     { if timed_out {html!{}}else {html!{
+                        <>
                         <div class="state">
                             <select onchange={ctx.link().callback(Msg::ModStateChange)} data-testid="mod-state-select" >
                                 <option value="0" selected={e.info.state.is_open()}>{"Event open"}</option>
@@ -700,11 +781,21 @@ impl Event {
                                 <option value="2" selected={e.info.state.is_closed()}>{"Event closed"}</option>
                             </select>
                         </div>
+                        <div class="state">
+                            <select onchange={ctx.link().callback(Msg::ModNamesChange)} data-testid="mod-names-select" >
+                                <option value="0" selected={e.info.participant_names == ParticipantNames::Anonymous}>{"Anonymous questions"}</option>
+                                <option value="1" selected={e.info.participant_names == ParticipantNames::Optional}>{"Names optional"}</option>
+                                <option value="2" selected={e.info.participant_names == ParticipantNames::Required}>{"Names required"}</option>
+                            </select>
+                        </div>
+                        </>
                         }} }
                     <button class="button-white" onclick={ctx.link().callback(|_|Msg::ModDelete)} data-testid="mod-delete">
                         { "Delete Event" }
                     </button>
                     <ModPassword tokens={e.info.tokens.clone()} {pwd} />
+                    <ModTemplates tokens={e.info.tokens.clone()} name={e.info.data.name.clone()} />
+                    <ModOrganizer tokens={e.info.tokens.clone()} />
                     { if e.info.is_premium() {
                             Self::mod_view_premium(ctx,e,tags)
                         } else { html!{} } }
@@ -743,6 +834,7 @@ impl Event {
                     </button>
                 </div>
                 <ModTags tokens={e.info.tokens.clone()} {tag} {tags} />
+                <ModSessions tokens={e.info.tokens.clone()} sessions={SharableSessions::new(e.info.sessions.clone())} />
             </div>
         }
     }
@@ -845,7 +937,18 @@ impl Event {
                 </div>
             }
         } else {
-            html! {}
+            self.state.event.as_ref().map_or_else(
+                || html! {},
+                |e| {
+                    html! {
+                        <div class="recover-mod-link">
+                            <Link<Route> to={Route::RecoverModLink { id: e.info.tokens.public_token.clone() }}>
+                                { "Lost your moderator link?" }
+                            </Link<Route>>
+                        </div>
+                    }
+                },
+            )
         }
     }
 
@@ -883,6 +986,7 @@ impl Event {
 
         self.unanswered.clear();
         self.tags = Rc::default();
+        self.sessions = Rc::default();
 
         if let Some(e) = &self.state.event {
             let mut questions = e.info.questions.clone();
@@ -902,14 +1006,23 @@ impl Event {
             self.unanswered = unanswered.collect();
             self.hidden = hidden.collect();
 
-            self.tags = e
-                .info
-                .tags
-                .tags
-                .iter()
-                .map(|t| (t.id, t.name.clone()))
-                .collect::<HashMap<_, _>>()
-                .into();
+            self.tags = Rc::new(e.info.tags.clone());
+            self.sessions = Rc::new(e.info.sessions.clone());
+
+            if self
+                .filter_session
+                .is_some_and(|session| self.sessions.get(session).is_none())
+            {
+                self.filter_session = None;
+            }
+
+            // the tag filtered by might have been deleted meanwhile
+            if self
+                .filter_tag
+                .is_some_and(|tag| self.tags.get(tag).is_none())
+            {
+                self.filter_tag = None;
+            }
         }
     }
 
@@ -1049,6 +1162,9 @@ impl Event {
                         self.state = self.dispatch.get();
                     }
 
+                    true
+                } else if let Some(id) = msg.strip_prefix("p:") {
+                    log::info!("received poll update: {}", id);
                     true
                 } else if msg.starts_with("v:") {
                     log::debug!("received viewer update: {}", msg);
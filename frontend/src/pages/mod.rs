@@ -2,14 +2,18 @@ mod admin;
 mod event;
 mod home;
 mod newevent;
+mod organizer;
 mod print;
 mod privacy;
+mod recover;
 mod subscribe;
 
 pub use admin::AdminLogin;
 pub use event::{BASE_API, Event, LoadingState};
 pub use home::Home;
 pub use newevent::NewEvent;
+pub use organizer::{Organizer, OrganizerVerify};
 pub use print::Print;
 pub use privacy::Privacy;
+pub use recover::RecoverModLink;
 pub use subscribe::Subscribe;
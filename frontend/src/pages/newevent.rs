@@ -16,12 +16,14 @@ use super::event::BASE_API;
 #[derive(Debug, Default, Deserialize)]
 struct QueryParams {
     pub customer: Option<String>,
+    pub template: Option<String>,
 }
 
 pub struct NewEvent {
     name: String,
     desc: String,
     email: String,
+    no_third_parties: bool,
     name_ref: NodeRef,
     errors: CreateEventValidation,
     loading: bool,
@@ -42,6 +44,7 @@ pub enum Msg {
     Create,
     CreatedResult(Option<EventInfo>),
     InputChange(Input, InputEvent),
+    ToggleNoThirdParties,
 }
 impl Component for NewEvent {
     type Message = Msg;
@@ -52,6 +55,7 @@ impl Component for NewEvent {
             name: String::new(),
             desc: String::new(),
             email: String::new(),
+            no_third_parties: false,
             name_ref: NodeRef::default(),
             errors: CreateEventValidation::default(),
             loading: false,
@@ -78,11 +82,22 @@ impl Component for NewEvent {
                     .unwrap_or_default();
 
                 let customer = query_params.customer;
+                let template = query_params.template;
+                let no_third_parties = self.no_third_parties;
 
                 self.loading = true;
 
                 ctx.link().send_future(async move {
-                    let res = fetch::create_event(BASE_API, name, desc, email, customer).await;
+                    let res = fetch::create_event(
+                        BASE_API,
+                        name,
+                        desc,
+                        email,
+                        customer,
+                        template,
+                        no_third_parties,
+                    )
+                    .await;
 
                     match res {
                         Ok(e) => Msg::CreatedResult(Some(e)),
@@ -136,6 +151,11 @@ impl Component for NewEvent {
 
                 true
             }
+
+            Msg::ToggleNoThirdParties => {
+                self.no_third_parties = !self.no_third_parties;
+                true
+            }
         }
     }
 
@@ -192,6 +212,15 @@ impl Component for NewEvent {
                         <div hidden={self.errors.desc.is_none()} class="invalid">
                             { Self::desc_error(self.errors.desc.as_ref()).unwrap_or_default() }
                         </div>
+                        <label class="no-third-parties">
+                            <input
+                                data-testid="newevent-no-third-parties"
+                                type="checkbox"
+                                checked={self.no_third_parties}
+                                onchange={ctx.link().callback(|_| Msg::ToggleNoThirdParties)}
+                            />
+                            { "no third parties (no short link, no event details in analytics)" }
+                        </label>
                     </div>
                     if !self.loading {
                         <button
@@ -0,0 +1,299 @@
+use chrono::{DateTime, Local, Utc};
+use shared::{CreateEventValidation, OrganizerDashboard, OrganizerEvent};
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, suspense::use_future_with};
+use yew_router::prelude::*;
+
+use crate::{fetch, routes::Route};
+
+use super::BASE_API;
+
+fn format_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn premium_label(e: &OrganizerEvent) -> String {
+    e.premium_until_unix.map_or_else(
+        || String::from(" premium"),
+        |until| format!(" premium until {}", format_date(until)),
+    )
+}
+
+enum OrganizerState {
+    RequestingInfo,
+    LoggedIn(OrganizerDashboard),
+    NotLoggedIn,
+    MailSent,
+}
+
+/// Organizer account: log in by mail and list all owned events with their mod links.
+pub struct Organizer {
+    email: String,
+    state: OrganizerState,
+}
+
+pub enum Msg {
+    Login,
+    LogOut,
+    Remove(String),
+    EmailChange(InputEvent),
+    MailSent(bool),
+    Dashboard(Option<OrganizerDashboard>),
+}
+
+impl Component for Organizer {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        request_dashboard(ctx.link());
+
+        Self {
+            email: String::new(),
+            state: OrganizerState::RequestingInfo,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::EmailChange(c) => {
+                let target: HtmlInputElement = c.target_dyn_into().unwrap_throw();
+                self.email = target.value();
+                true
+            }
+            Msg::Login => {
+                let email = self.email.clone();
+
+                ctx.link().send_future(async move {
+                    match fetch::organizer_login(BASE_API, email).await {
+                        Ok(()) => Msg::MailSent(true),
+                        Err(e) => {
+                            log::error!("organizer_login error: {e}");
+                            Msg::MailSent(false)
+                        }
+                    }
+                });
+
+                false
+            }
+            Msg::MailSent(sent) => {
+                if sent {
+                    self.state = OrganizerState::MailSent;
+                }
+                true
+            }
+            Msg::LogOut => {
+                self.state = OrganizerState::RequestingInfo;
+
+                ctx.link().send_future(async move {
+                    if let Err(e) = fetch::organizer_logout(BASE_API).await {
+                        log::error!("organizer_logout error: {e}");
+                    }
+
+                    Msg::Dashboard(None)
+                });
+
+                true
+            }
+            Msg::Remove(id) => {
+                ctx.link().send_future(async move {
+                    if let Err(e) = fetch::organizer_remove_event(BASE_API, id).await {
+                        log::error!("organizer_remove_event error: {e}");
+                    }
+
+                    match fetch::organizer_events(BASE_API).await {
+                        Ok(dashboard) => Msg::Dashboard(dashboard),
+                        Err(e) => {
+                            log::error!("organizer_events error: {e}");
+                            Msg::Dashboard(None)
+                        }
+                    }
+                });
+
+                false
+            }
+            Msg::Dashboard(dashboard) => {
+                self.state = dashboard.map_or(OrganizerState::NotLoggedIn, |dashboard| {
+                    OrganizerState::LoggedIn(dashboard)
+                });
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let content = match &self.state {
+            OrganizerState::RequestingInfo => html! { { "Waiting..." } },
+            OrganizerState::NotLoggedIn => self.view_login(ctx),
+            OrganizerState::MailSent => html! {
+                <p>{ "Check your inbox, we sent you a link to log in. It is valid for 15 minutes." }</p>
+            },
+            OrganizerState::LoggedIn(dashboard) => Self::view_dashboard(ctx, dashboard),
+        };
+
+        html! {
+            <div class="newevent-bg">
+                <div class="title">{ "My Events" }</div>
+                <div class="form">{ content }</div>
+            </div>
+        }
+    }
+}
+
+impl Organizer {
+    fn view_login(&self, ctx: &Context<Self>) -> Html {
+        let valid = !self.email.trim().is_empty()
+            && CreateEventValidation::check_email(self.email.trim()).is_none();
+
+        html! {
+            <>
+                <p>{ "Log in with your email to see all events you created." }</p>
+                <div class="newevent">
+                    <div class="input-box">
+                        <input
+                            type="email"
+                            placeholder="email"
+                            value={self.email.clone()}
+                            maxlength="100"
+                            required=true
+                            oninput={ctx.link().callback(Msg::EmailChange)}
+                        />
+                    </div>
+                </div>
+                <button
+                    class="button-finish"
+                    disabled={!valid}
+                    onclick={ctx.link().callback(|_| Msg::Login)}
+                >
+                    { "send login link" }
+                </button>
+            </>
+        }
+    }
+
+    fn view_dashboard(ctx: &Context<Self>, dashboard: &OrganizerDashboard) -> Html {
+        let events = if dashboard.events.is_empty() {
+            html! {
+                <p>
+                    { "No events yet. Events you create while logged in show up here, existing ones can be added from their moderator view." }
+                </p>
+            }
+        } else {
+            dashboard
+                .events
+                .iter()
+                .map(|e| Self::view_event(ctx, e))
+                .collect::<Html>()
+        };
+
+        html! {
+            <>
+                <div class="organizer-events">{ events }</div>
+                <button class="button-finish" onclick={ctx.link().callback(|_| Msg::LogOut)}>
+                    { "logout" }
+                </button>
+            </>
+        }
+    }
+
+    fn view_event(ctx: &Context<Self>, e: &OrganizerEvent) -> Html {
+        let id = e.tokens.public_token.clone();
+
+        let mod_link = e.tokens.moderator_token.as_ref().map_or_else(
+            || html! {},
+            |secret| {
+                html! {
+                    <Link<Route> to={Route::EventMod { id: id.clone(), secret: secret.clone() }}>
+                        { "moderate" }
+                    </Link<Route>>
+                }
+            },
+        );
+
+        let remove = ctx.link().callback({
+            let id = id.clone();
+            move |_| Msg::Remove(id.clone())
+        });
+
+        html! {
+            <div class={classes!("organizer-event", e.deleted.then_some("deleted"))} key={id.clone()}>
+                <div class="name">
+                    { e.name.clone() }
+                    { if e.premium { html! { <span class="premium">{ premium_label(e) }</span> } } else { html! {} } }
+                </div>
+                <div class="stats">
+                    { format!("{} | {} questions | {} likes", format_date(e.create_time_unix), e.questions, e.likes) }
+                    { if e.deleted { " | deleted" } else { "" } }
+                </div>
+                <div class="links">
+                    <Link<Route> to={Route::Event { id }}>{ "public" }</Link<Route>>
+                    { mod_link }
+                    <span class="remove" onclick={remove}>{ "remove" }</span>
+                </div>
+            </div>
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Properties)]
+pub struct OrganizerVerifyProps {
+    pub token: String,
+}
+
+/// Landing page of the mailed login link: trade the token for a session and go to the
+/// dashboard.
+#[function_component]
+pub fn OrganizerVerify(props: &OrganizerVerifyProps) -> Html {
+    let navigator = use_navigator();
+    let failed = use_state(|| false);
+
+    let _ = use_future_with(props.token.clone(), {
+        let failed = failed.clone();
+        |token| async move {
+            match fetch::organizer_verify(BASE_API, (*token).clone()).await {
+                Ok(()) => {
+                    if let Some(navigator) = navigator {
+                        navigator.replace(&Route::Organizer);
+                    }
+                }
+                Err(e) => {
+                    log::error!("organizer_verify error: {e}");
+                    failed.set(true);
+                }
+            }
+        }
+    });
+
+    html! {
+        <div class="newevent-bg">
+            <div class="title">{ "My Events" }</div>
+            <div class="form">
+                { if *failed {
+                    html! {
+                        <p>
+                            { "This login link is invalid or expired. " }
+                            <Link<Route> to={Route::Organizer}>{ "Request a new one." }</Link<Route>>
+                        </p>
+                    }
+                } else {
+                    html! { { "Logging in..." } }
+                } }
+            </div>
+        </div>
+    }
+}
+
+fn request_dashboard(link: &html::Scope<Organizer>) {
+    link.send_future(async move {
+        match fetch::organizer_events(BASE_API).await {
+            Err(e) => {
+                log::error!("organizer_events error: {e}");
+                Msg::Dashboard(None)
+            }
+            Ok(dashboard) => Msg::Dashboard(dashboard),
+        }
+    });
+}
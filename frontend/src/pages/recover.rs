@@ -0,0 +1,106 @@
+use shared::CreateEventValidation;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, suspense::use_future_with};
+
+use crate::fetch;
+
+use super::BASE_API;
+
+#[derive(Clone, Debug, Eq, PartialEq, Properties)]
+pub struct RecoverModLinkProps {
+    pub id: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RecoverState {
+    Input,
+    Requested,
+    Sent,
+    Failed,
+}
+
+/// Ask for the mod link of an event to be mailed again to the address given at creation.
+#[function_component]
+pub fn RecoverModLink(props: &RecoverModLinkProps) -> Html {
+    let email = use_state(String::new);
+    let state = use_state(|| RecoverState::Input);
+
+    let _ = use_future_with(state.clone(), {
+        let id = props.id.clone();
+        let email = (*email).clone();
+
+        |state| async move {
+            if **state != RecoverState::Requested {
+                return;
+            }
+
+            match fetch::recover_mod_link(BASE_API, id, email).await {
+                Ok(()) => state.set(RecoverState::Sent),
+                Err(e) => {
+                    log::error!("recover_mod_link error: {e}");
+                    state.set(RecoverState::Failed);
+                }
+            }
+        }
+    });
+
+    let oninput = Callback::from({
+        let email = email.clone();
+        move |e: InputEvent| {
+            let target: HtmlInputElement = e.target_dyn_into().unwrap_throw();
+            email.set(target.value());
+        }
+    });
+
+    let onclick = Callback::from({
+        let state = state.clone();
+        move |_| state.set(RecoverState::Requested)
+    });
+
+    let valid =
+        !email.trim().is_empty() && CreateEventValidation::check_email(email.trim()).is_none();
+
+    let content = if *state == RecoverState::Sent {
+        html! {
+            <p>
+                { "If this is the email the event was created with, the moderator link is on its way." }
+            </p>
+        }
+    } else {
+        html! {
+            <>
+                <p>{ "Enter the email you gave when creating the event to get its moderator link again." }</p>
+                <div class="newevent">
+                    <div class="input-box">
+                        <input
+                            type="email"
+                            placeholder="email"
+                            value={(*email).clone()}
+                            maxlength="100"
+                            required=true
+                            {oninput}
+                        />
+                    </div>
+                </div>
+                <div class="error" hidden={*state != RecoverState::Failed}>
+                    { "Too many requests, please try again later." }
+                </div>
+                <button
+                    class="button-finish"
+                    disabled={!valid || *state == RecoverState::Requested}
+                    {onclick}
+                >
+                    { "send moderator link" }
+                </button>
+            </>
+        }
+    };
+
+    html! {
+        <div class="newevent-bg">
+            <div class="title">{ "Lost moderator link" }</div>
+            <div class="form">{ content }</div>
+        </div>
+    }
+}
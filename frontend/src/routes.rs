@@ -10,10 +10,16 @@ pub enum Route {
     Privacy,
     #[at("/event/:id")]
     Event { id: String },
+    #[at("/event/:id/recover")]
+    RecoverModLink { id: String },
     #[at("/event/print/:id")]
     Print { id: String },
     #[at("/eventmod/:id/:secret")]
     EventMod { id: String, secret: String },
+    #[at("/organizer")]
+    Organizer,
+    #[at("/organizer/login/:token")]
+    OrganizerVerify { token: String },
     #[at("/login")]
     Login,
     #[at("/")]
@@ -1,4 +1,7 @@
 mod flags;
+mod poll;
+mod sessions;
+mod tags;
 mod validation;
 
 use std::{str::FromStr, time::Duration};
@@ -8,11 +11,19 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 pub use flags::{EventFlags, EventResponseFlags};
+pub use poll::{AddPoll, ModPoll, Poll, PollKind, PollOption, PollVote, MAX_POLLS};
+pub use sessions::{EventSessions, Session, SessionData, SessionId, MAX_SESSIONS};
+pub use tags::{EventTags, Tag, TagId};
 pub use validation::{
     add_question::{AddQuestionError, AddQuestionValidation},
+    content_filter::{ContentFilterError, ContentFilterValidation},
     context_validation::{ContextLabelError, ContextUrlError, ContextValidation},
     create_event::{CreateEventError, CreateEventValidation},
+    participant_name::{ParticipantNameError, ParticipantNameValidation},
+    poll_validation::{PollError, PollValidation},
     pwd_validation::{PasswordError, PasswordValidation},
+    question_limits::{QuestionLimitsError, QuestionLimitsValidation},
+    session_validation::{SessionError, SessionValidation},
     tag_validation::{TagError, TagValidation},
     ValidationState,
 };
@@ -23,6 +34,7 @@ pub const TEST_EVENT_DESC: &str = "minimum desc length possible!!";
 pub const TEST_EVENT_NAME: &str = "min name";
 
 pub const MAX_TAGS: usize = 15;
+pub const MAX_CONTEXT_LINKS: usize = 5;
 
 /// A semver triplet (no pre-release/build metadata). Fields are declared major → minor →
 /// patch, so the derived `Ord` yields correct version precedence.
@@ -72,9 +84,6 @@ pub struct EventData {
     pub color: Option<Color>,
 }
 
-#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct TagId(pub usize);
-
 #[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
 pub struct QuestionItem {
     pub id: i64,
@@ -88,6 +97,11 @@ pub struct QuestionItem {
     pub create_time_unix: i64,
     #[serde(default)]
     pub tag: Option<TagId>,
+    /// display name the asker signed the question with
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub session: Option<SessionId>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -102,51 +116,51 @@ pub struct ContextItem {
     pub url: String,
 }
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
-pub struct PaymentCapture {
-    pub order_captured: bool,
+/// What happens to a question tripping the content filter.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ContentFilterAction {
+    #[default]
+    Off,
+    /// held back for moderator approval, as if screening was enabled
+    Screen,
+    /// refused right away
+    Reject,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
-pub struct Tag {
-    pub name: String,
-    pub id: TagId,
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct ContentFilter {
+    pub action: ContentFilterAction,
+    /// event specific words/phrases, checked on top of the built-in list
+    #[serde(default)]
+    pub blocklist: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
-pub struct EventTags {
-    pub current_tag: Option<TagId>,
-    pub tags: Vec<Tag>,
+/// Per event rules for new questions, bounded globally by `QuestionLimitsValidation`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(default)]
+pub struct QuestionLimits {
+    pub min_len: usize,
+    pub max_len: usize,
+    pub min_words: usize,
+    pub max_questions: usize,
 }
-impl EventTags {
-    #[must_use]
-    pub fn get_current_tag_label(&self) -> Option<String> {
-        self.current_tag
-            .as_ref()
-            .and_then(|current| self.tags.iter().find(|tag| tag.id == *current))
-            .map(|tag| tag.name.clone())
-    }
-
-    /// returns `false` if max tags is reached and a new tag would have to be added
-    pub fn set_or_add_tag(&mut self, tag: &str) -> bool {
-        let tag = tag.to_lowercase();
 
-        if let Some(i) = self.tags.iter().find(|e| *e.name == tag) {
-            self.current_tag = Some(i.id);
-        } else {
-            if self.tags.len() >= MAX_TAGS {
-                return false;
-            }
-
-            let id = TagId(self.tags.len());
-            self.tags.push(Tag { name: tag, id });
-            self.current_tag = Some(id);
+impl Default for QuestionLimits {
+    fn default() -> Self {
+        Self {
+            min_len: 10,
+            max_len: 200,
+            min_words: 3,
+            max_questions: 500,
         }
-
-        true
     }
 }
 
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct PaymentCapture {
+    pub order_captured: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
 pub struct EventInfo {
     pub tokens: EventTokens,
@@ -165,6 +179,17 @@ pub struct EventInfo {
     pub context: Vec<ContextItem>,
     #[serde(default)]
     pub tags: EventTags,
+    #[serde(default)]
+    pub question_limits: QuestionLimits,
+    #[serde(default)]
+    pub participant_names: ParticipantNames,
+    #[serde(default)]
+    pub polls: Vec<Poll>,
+    #[serde(default)]
+    pub sessions: EventSessions,
+    /// tier of an active premium, `None` for free and expired events
+    #[serde(default)]
+    pub premium_tier: Option<PremiumTier>,
 }
 
 impl EventInfo {
@@ -180,6 +205,7 @@ impl EventInfo {
         }
     }
 
+    /// Entitled to the premium features, an event only kept for longer is not.
     #[must_use]
     pub const fn is_premium(&self) -> bool {
         self.flags.contains(EventFlags::PREMIUM)
@@ -220,6 +246,8 @@ impl EventInfo {
 pub struct ModInfo {
     pub pwd: EventPassword,
     pub private_token: String,
+    #[serde(default)]
+    pub content_filter: ContentFilter,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
@@ -290,6 +318,35 @@ pub struct AddEvent {
     pub moderator_email: Option<String>,
     pub test: bool,
     pub customer: Option<String>,
+    /// id of a saved [`EventTemplate`] to copy the settings from
+    #[serde(default)]
+    pub template: Option<String>,
+    /// keep the event away from third parties: no link shortener, analytics without its
+    /// content
+    #[serde(default, rename = "noThirdParties")]
+    pub no_third_parties: bool,
+}
+
+/// Create a new event with the settings of an existing one.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Default)]
+pub struct CloneEvent {
+    /// carry over questions that are neither answered nor hidden
+    #[serde(default)]
+    pub questions: bool,
+    #[serde(default)]
+    pub customer: Option<String>,
+}
+
+/// Save the settings of an event as a reusable template.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct SaveTemplate {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct EventTemplate {
+    pub id: String,
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -304,6 +361,25 @@ pub struct AddQuestion {
     pub text: String,
     #[serde(default)]
     pub tag: Option<TagId>,
+    /// post even if a similar question exists (see [`SimilarQuestion`])
+    #[serde(default)]
+    pub allow_similar: bool,
+    /// display name, see [`ParticipantNames`] for whether it is accepted
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Body of the `409` answer to an [`AddQuestion`] closely matching a visible question: clients
+/// suggest liking that one instead, or resend with `allow_similar`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct SimilarQuestion {
+    pub question: QuestionItem,
+}
+
+/// Merge a question into the one with id `into`, which keeps its text and gains the likes.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MergeQuestion {
+    pub into: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -322,6 +398,9 @@ pub enum ModRequestPremiumContext {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ModRequestPremium {
     pub context: ModRequestPremiumContext,
+    /// tier of an admin upgrade, a checkout only sells [`PremiumTier::SingleEvent`]
+    #[serde(default)]
+    pub tier: PremiumTier,
 }
 
 #[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, Eq, PartialEq, Default)]
@@ -333,6 +412,29 @@ pub enum States {
     Closed = 2,
 }
 
+/// Whether askers sign their questions with a display name.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[repr(u8)]
+pub enum ParticipantNames {
+    #[default]
+    Anonymous = 0,
+    Optional = 1,
+    Required = 2,
+}
+
+impl FromStr for ParticipantNames {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self::Anonymous),
+            "1" => Ok(Self::Optional),
+            "2" => Ok(Self::Required),
+            _ => Err(()),
+        }
+    }
+}
+
 impl FromStr for States {
     type Err = ();
 
@@ -359,10 +461,48 @@ pub enum CurrentTag {
     Enabled(String),
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum EditTag {
+    Rename(TagId, String),
+    /// delete a tag, its questions get `reassign` or are left untagged
+    Delete {
+        tag: TagId,
+        reassign: Option<TagId>,
+    },
+    /// move a tag to index `to` of [`EventTags::tags`]
+    Move {
+        tag: TagId,
+        to: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum EditSession {
+    /// append a session, up to [`MAX_SESSIONS`]
+    Add(SessionData),
+    Edit(SessionId, SessionData),
+    /// open, set vote-only or close a single session
+    State(SessionId, EventState),
+    /// pin the active session, `None` falls back to the sessions time windows
+    Current(Option<SessionId>),
+    /// delete a session, its questions are kept without session
+    Delete(SessionId),
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub enum EditContextLink {
+    /// remove all links
     Disabled,
+    /// replace all links with this single one
     Enabled(ContextItem),
+    /// append a link, up to [`MAX_CONTEXT_LINKS`]
+    Add(ContextItem),
+    /// replace the link at an index
+    Edit(usize, ContextItem),
+    /// remove the link at an index
+    Remove(usize),
+    /// move the link at `from` to index `to`
+    Move { from: usize, to: usize },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -412,12 +552,17 @@ pub struct EditMetaData {
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Default)]
 pub struct ModEvent {
     pub current_tag: Option<CurrentTag>,
+    pub edit_tag: Option<EditTag>,
     pub password: Option<EventPassword>,
     pub state: Option<EventState>,
     pub meta: Option<EditMetaData>,
     pub screening: Option<bool>,
     pub context: Option<EditContextLink>,
     pub color: Option<EditColor>,
+    pub content_filter: Option<ContentFilter>,
+    pub question_limits: Option<QuestionLimits>,
+    pub participant_names: Option<ParticipantNames>,
+    pub session: Option<EditSession>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -461,6 +606,15 @@ impl EventState {
         matches!(self.state, States::Closed)
     }
 
+    #[must_use]
+    pub const fn most_restrictive(self, other: Self) -> Self {
+        if other.to_value() > self.to_value() {
+            other
+        } else {
+            self
+        }
+    }
+
     #[must_use]
     pub const fn to_value(&self) -> u8 {
         match self.state {
@@ -504,6 +658,148 @@ pub struct GetUserInfo {
     pub user: Option<UserInfo>,
 }
 
+/// Ask for the mod link of an event to be mailed again, to the email given at its creation.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct ModLinkRecovery {
+    pub email: String,
+}
+
+/// Request a magic login link to `email`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct OrganizerLogin {
+    pub email: String,
+}
+
+/// Redeem the token of a magic login link.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct OrganizerLoginVerify {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct OrganizerEvent {
+    /// includes the moderator token unless the event is deleted
+    pub tokens: EventTokens,
+    pub name: String,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+    pub questions: usize,
+    pub likes: i32,
+    pub premium: bool,
+    /// end of a time-limited premium
+    #[serde(default, rename = "premiumUntilUnix")]
+    pub premium_until_unix: Option<i64>,
+    pub deleted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct OrganizerDashboard {
+    /// newest first
+    pub events: Vec<OrganizerEvent>,
+}
+
+/// Which premium features an upgraded event gets.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum PremiumTier {
+    /// every premium feature for a single event
+    #[default]
+    SingleEvent,
+    /// no premium features, only keeps the event readable past the free retention period
+    ExtendedRetention,
+    /// every premium feature, for the events of a subscribed organization
+    Organization,
+}
+
+impl PremiumTier {
+    /// Entitled to the premium features, not only to the extended retention.
+    #[must_use]
+    pub const fn has_features(self) -> bool {
+        !matches!(self, Self::ExtendedRetention)
+    }
+
+    /// `other` is entitled to more than this tier, so upgrading to it is worth it.
+    #[must_use]
+    pub const fn is_below(self, other: Self) -> bool {
+        !self.has_features() && other.has_features()
+    }
+}
+
+/// An admin confirms the invoice numbered `invoice` was paid, upgrading the event.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct AdminInvoicePaid {
+    pub invoice: String,
+    #[serde(default)]
+    pub tier: PremiumTier,
+    /// premium ends at this time, `None` keeps it for good
+    #[serde(default, rename = "untilUnix")]
+    pub until_unix: Option<i64>,
+}
+
+/// An admin issues a voucher code that upgrades up to `max_redemptions` events.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct AdminCreateVoucher {
+    pub code: String,
+    /// what it is issued for, e.g. the meetup it is handed out at
+    #[serde(default)]
+    pub note: String,
+    #[serde(rename = "maxRedemptions")]
+    pub max_redemptions: usize,
+    #[serde(default)]
+    pub tier: PremiumTier,
+    /// cannot be redeemed after this time
+    #[serde(default, rename = "validUntilUnix")]
+    pub valid_until_unix: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct VoucherInfo {
+    pub code: String,
+    pub note: String,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+    #[serde(rename = "maxRedemptions")]
+    pub max_redemptions: usize,
+    /// public ids of the events it upgraded, oldest first
+    pub events: Vec<String>,
+    pub tier: PremiumTier,
+    #[serde(rename = "validUntilUnix")]
+    pub valid_until_unix: Option<i64>,
+}
+
+/// A moderator upgrades the event with a voucher code.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct ModRedeemVoucher {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum WebhookOutcome {
+    /// claimed by the request processing it
+    Processing,
+    Processed,
+    /// nothing to do for this kind of event
+    Ignored,
+    /// processing failed with this error, the provider retries the delivery
+    Failed(String),
+}
+
+/// A payment provider webhook delivery, identified by the provider's event id.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: String,
+    /// event type, e.g. `checkout.session.completed`
+    pub kind: String,
+    #[serde(rename = "timeUnix")]
+    pub time_unix: i64,
+    pub outcome: WebhookOutcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct WebhookDeliveries {
+    /// newest first
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
 pub struct EventPasswordRequest {
     pub pwd: String,
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_POLLS: usize = 20;
+
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PollKind {
+    #[default]
+    SingleChoice,
+    MultipleChoice,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct PollOption {
+    pub text: String,
+    pub votes: u32,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct Poll {
+    pub id: i64,
+    pub question: String,
+    pub kind: PollKind,
+    pub options: Vec<PollOption>,
+    /// number of participants that voted, an option count on multiple choice polls can be lower
+    pub voters: u32,
+    /// closed polls keep showing their results but take no more votes
+    pub closed: bool,
+    pub hidden: bool,
+    #[serde(rename = "createTimeUnix")]
+    pub create_time_unix: i64,
+}
+
+impl Poll {
+    #[must_use]
+    pub const fn is_open(&self) -> bool {
+        !self.closed && !self.hidden
+    }
+
+    /// Whether `choices` (option indices) are a valid ballot for this poll.
+    #[must_use]
+    pub fn valid_choices(&self, choices: &[usize]) -> bool {
+        let in_range = choices.iter().all(|choice| *choice < self.options.len());
+        let unique = choices
+            .iter()
+            .enumerate()
+            .all(|(i, choice)| !choices.iter().skip(i + 1).any(|other| other == choice));
+
+        let count_ok = match self.kind {
+            PollKind::SingleChoice => choices.len() == 1,
+            PollKind::MultipleChoice => !choices.is_empty(),
+        };
+
+        in_range && unique && count_ok
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AddPoll {
+    pub question: String,
+    pub kind: PollKind,
+    pub options: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ModPoll {
+    pub closed: bool,
+    pub hidden: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PollVote {
+    /// indices into [`Poll::options`]
+    pub choices: Vec<usize>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn poll(kind: PollKind) -> Poll {
+        Poll {
+            kind,
+            options: vec![PollOption::default(); 3],
+            ..Poll::default()
+        }
+    }
+
+    #[test]
+    fn test_valid_choices() {
+        let single = poll(PollKind::SingleChoice);
+        assert!(single.valid_choices(&[2]));
+        assert!(!single.valid_choices(&[]));
+        assert!(!single.valid_choices(&[0, 1]));
+        assert!(!single.valid_choices(&[3]));
+
+        let multi = poll(PollKind::MultipleChoice);
+        assert!(multi.valid_choices(&[0, 2]));
+        assert!(!multi.valid_choices(&[]));
+        assert!(!multi.valid_choices(&[1, 1]));
+    }
+}
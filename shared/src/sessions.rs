@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+
+use crate::EventState;
+
+pub const MAX_SESSIONS: usize = 20;
+
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SessionId(pub usize);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct SessionData {
+    pub name: String,
+    /// planned start, unix seconds
+    #[serde(default)]
+    pub start_unix: Option<i64>,
+    /// planned end, unix seconds
+    #[serde(default)]
+    pub end_unix: Option<i64>,
+}
+
+/// A track/agenda slot of an event with its own state and question list.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct Session {
+    pub id: SessionId,
+    pub data: SessionData,
+    pub state: EventState,
+}
+
+impl Session {
+    #[must_use]
+    pub fn is_running(&self, now: i64) -> bool {
+        self.data.start_unix.is_some_and(|start| start <= now)
+            && self.data.end_unix.map_or(true, |end| now < end)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct EventSessions {
+    /// picked by the moderator, takes precedence over the time windows
+    pub current: Option<SessionId>,
+    /// in agenda order
+    pub sessions: Vec<Session>,
+    /// lower bound for the next [`SessionId`], ids of deleted sessions are never handed out again
+    pub next_id: usize,
+}
+
+impl EventSessions {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    #[must_use]
+    pub fn get(&self, id: SessionId) -> Option<&Session> {
+        self.sessions.iter().find(|s| s.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: SessionId) -> Option<&mut Session> {
+        self.sessions.iter_mut().find(|s| s.id == id)
+    }
+
+    /// the session new questions go to: the `current` one or else the first one running at `now`
+    #[must_use]
+    pub fn active(&self, now: i64) -> Option<&Session> {
+        self.current.map_or_else(
+            || self.sessions.iter().find(|s| s.is_running(now)),
+            |id| self.get(id),
+        )
+    }
+
+    /// returns `None` if [`MAX_SESSIONS`] is reached
+    pub fn add(&mut self, data: SessionData) -> Option<SessionId> {
+        if self.sessions.len() >= MAX_SESSIONS {
+            return None;
+        }
+
+        let id = SessionId(self.next_id);
+        self.next_id += 1;
+
+        self.sessions.push(Session {
+            id,
+            data,
+            state: EventState::default(),
+        });
+
+        Some(id)
+    }
+
+    /// returns `false` if the session does not exist
+    pub fn delete(&mut self, id: SessionId) -> bool {
+        let count = self.sessions.len();
+
+        self.sessions.retain(|s| s.id != id);
+
+        if self.current == Some(id) {
+            self.current = None;
+        }
+
+        self.sessions.len() != count
+    }
+
+    /// state for adding questions at `now`: the stricter of the event and the active session
+    #[must_use]
+    pub fn effective_state(&self, event: EventState, now: i64) -> EventState {
+        self.active(now)
+            .map_or(event, |s| event.most_restrictive(s.state))
+    }
+
+    /// state for voting on a question of `session`
+    #[must_use]
+    pub fn question_state(&self, event: EventState, session: Option<SessionId>) -> EventState {
+        session
+            .and_then(|id| self.get(id))
+            .map_or(event, |s| event.most_restrictive(s.state))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::States;
+
+    fn data(name: &str, start_unix: Option<i64>, end_unix: Option<i64>) -> SessionData {
+        SessionData {
+            name: name.to_string(),
+            start_unix,
+            end_unix,
+        }
+    }
+
+    #[test]
+    fn test_active_session() {
+        let mut sessions = EventSessions::default();
+
+        let (a, b) = (SessionId(0), SessionId(1));
+        assert_eq!(sessions.add(data("keynote", Some(100), Some(200))), Some(a));
+        assert_eq!(sessions.add(data("workshop", Some(200), None)), Some(b));
+
+        assert_eq!(sessions.active(50), None);
+        assert_eq!(sessions.active(150).map(|s| s.id), Some(a));
+        assert_eq!(sessions.active(250).map(|s| s.id), Some(b));
+
+        sessions.current = Some(a);
+        assert_eq!(sessions.active(250).map(|s| s.id), Some(a));
+
+        assert!(sessions.delete(a));
+        assert_eq!(sessions.current, None);
+        assert_eq!(sessions.add(data("panel", None, None)), Some(SessionId(2)));
+    }
+
+    #[test]
+    fn test_effective_state() {
+        let mut sessions = EventSessions::default();
+        let open = EventState::default();
+        let closed = EventState {
+            state: States::Closed,
+        };
+
+        let a = SessionId(0);
+        assert_eq!(sessions.add(data("keynote", None, None)), Some(a));
+        sessions.current = Some(a);
+        assert!(sessions.effective_state(open, 0).is_open());
+
+        sessions.sessions[0].state = closed;
+        assert!(sessions.effective_state(open, 0).is_closed());
+        assert!(sessions.question_state(open, Some(a)).is_closed());
+        assert!(sessions.question_state(open, None).is_open());
+
+        sessions.sessions[0].state = EventState {
+            state: States::VotingOnly,
+        };
+        assert!(sessions.effective_state(closed, 0).is_closed());
+    }
+
+    #[test]
+    fn test_max_sessions() {
+        let mut sessions = EventSessions::default();
+
+        for _ in 0..MAX_SESSIONS {
+            assert!(sessions.add(SessionData::default()).is_some());
+        }
+
+        assert!(sessions.add(SessionData::default()).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::MAX_TAGS;
+
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TagId(pub usize);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct Tag {
+    pub name: String,
+    pub id: TagId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct EventTags {
+    pub current_tag: Option<TagId>,
+    /// in display order
+    pub tags: Vec<Tag>,
+    /// lower bound for the next [`TagId`], ids of deleted tags are never handed out again
+    #[serde(default)]
+    pub next_id: usize,
+}
+
+impl EventTags {
+    #[must_use]
+    pub fn get_current_tag_label(&self) -> Option<String> {
+        self.current_tag
+            .and_then(|current| self.get(current))
+            .map(|tag| tag.name.clone())
+    }
+
+    #[must_use]
+    pub fn get(&self, id: TagId) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.id == id)
+    }
+
+    /// returns `false` if max tags is reached and a new tag would have to be added
+    pub fn set_or_add_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.to_lowercase();
+
+        if let Some(i) = self.tags.iter().find(|e| *e.name == tag) {
+            self.current_tag = Some(i.id);
+        } else {
+            if self.tags.len() >= MAX_TAGS {
+                return false;
+            }
+
+            let id = self.new_id();
+            self.tags.push(Tag { name: tag, id });
+            self.current_tag = Some(id);
+        }
+
+        true
+    }
+
+    /// returns `false` if the tag does not exist or another tag already has this name
+    pub fn rename(&mut self, id: TagId, name: &str) -> bool {
+        let name = name.to_lowercase();
+
+        if self.tags.iter().any(|tag| tag.name == name && tag.id != id) {
+            return false;
+        }
+
+        self.tags
+            .iter_mut()
+            .find(|tag| tag.id == id)
+            .map(|tag| tag.name = name)
+            .is_some()
+    }
+
+    /// returns `false` if the tag does not exist
+    pub fn delete(&mut self, id: TagId) -> bool {
+        let count = self.tags.len();
+
+        self.tags.retain(|tag| tag.id != id);
+
+        if self.current_tag == Some(id) {
+            self.current_tag = None;
+        }
+
+        self.tags.len() != count
+    }
+
+    /// returns `false` if the tag does not exist or `to` is out of bounds
+    pub fn move_tag(&mut self, id: TagId, to: usize) -> bool {
+        let Some(from) = self.tags.iter().position(|tag| tag.id == id) else {
+            return false;
+        };
+
+        if to >= self.tags.len() {
+            return false;
+        }
+
+        let tag = self.tags.remove(from);
+        self.tags.insert(to, tag);
+
+        true
+    }
+
+    fn new_id(&mut self) -> TagId {
+        // events from before `next_id` existed used the index as id
+        let id = self
+            .tags
+            .iter()
+            .map(|tag| tag.id.0 + 1)
+            .max()
+            .unwrap_or_default()
+            .max(self.next_id);
+
+        self.next_id = id + 1;
+
+        TagId(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(tags: &EventTags) -> Vec<&str> {
+        tags.tags.iter().map(|tag| tag.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ids_survive_deletion() {
+        let mut tags = EventTags::default();
+
+        assert!(tags.set_or_add_tag("day1"));
+        assert!(tags.set_or_add_tag("day2"));
+        assert_eq!(tags.current_tag, Some(TagId(1)));
+
+        assert!(tags.delete(TagId(1)));
+        assert_eq!(tags.current_tag, None);
+        assert!(!tags.delete(TagId(1)));
+
+        assert!(tags.set_or_add_tag("day3"));
+        assert_eq!(tags.current_tag, Some(TagId(2)));
+    }
+
+    #[test]
+    fn test_legacy_ids() {
+        let mut tags = EventTags {
+            current_tag: None,
+            tags: vec![Tag {
+                name: String::from("old"),
+                id: TagId(0),
+            }],
+            next_id: 0,
+        };
+
+        assert!(tags.set_or_add_tag("new"));
+        assert_eq!(tags.current_tag, Some(TagId(1)));
+    }
+
+    #[test]
+    fn test_rename_and_move() {
+        let mut tags = EventTags::default();
+        tags.set_or_add_tag("a");
+        tags.set_or_add_tag("b");
+        tags.set_or_add_tag("c");
+
+        assert!(!tags.rename(TagId(0), "b"));
+        assert!(tags.rename(TagId(0), "A2"));
+        assert!(!tags.rename(TagId(9), "x"));
+        assert_eq!(names(&tags), ["a2", "b", "c"]);
+
+        assert!(tags.move_tag(TagId(2), 0));
+        assert_eq!(names(&tags), ["c", "a2", "b"]);
+        assert!(!tags.move_tag(TagId(2), 3));
+        assert_eq!(tags.get_current_tag_label(), Some(String::from("c")));
+    }
+}
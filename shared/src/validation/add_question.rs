@@ -1,4 +1,5 @@
 use super::ValidationState;
+use crate::QuestionLimits;
 
 #[derive(Debug)]
 pub enum AddQuestionError {
@@ -8,9 +9,6 @@ pub enum AddQuestionError {
     WordLengthMax(usize),
 }
 
-const TRIMMED_MIN_LEN: usize = 10;
-const TRIMMED_MAX_LEN: usize = 200;
-const WORD_MIN: usize = 3;
 const WORD_LEN_MAX: usize = 30;
 
 #[derive(Default, Debug)]
@@ -19,8 +17,8 @@ pub struct AddQuestionValidation {
 }
 
 impl AddQuestionValidation {
-    pub fn check(&mut self, content: &str) {
-        self.content = Self::check_content(content);
+    pub fn check(&mut self, content: &str, limits: &QuestionLimits) {
+        self.content = Self::check_content(content, limits);
     }
 
     #[must_use]
@@ -28,16 +26,16 @@ impl AddQuestionValidation {
         !self.content.is_valid()
     }
 
-    fn check_content(v: &str) -> ValidationState<AddQuestionError> {
+    fn check_content(v: &str, limits: &QuestionLimits) -> ValidationState<AddQuestionError> {
         let trimmed_len = v.trim().len();
         let words = v.split_whitespace().count();
 
-        if trimmed_len < TRIMMED_MIN_LEN {
-            ValidationState::Invalid(AddQuestionError::MinLength(trimmed_len, TRIMMED_MIN_LEN))
-        } else if trimmed_len > TRIMMED_MAX_LEN {
-            ValidationState::Invalid(AddQuestionError::MaxLength(trimmed_len, TRIMMED_MAX_LEN))
-        } else if words < WORD_MIN {
-            ValidationState::Invalid(AddQuestionError::MinWordCount(words, WORD_MIN))
+        if trimmed_len < limits.min_len {
+            ValidationState::Invalid(AddQuestionError::MinLength(trimmed_len, limits.min_len))
+        } else if trimmed_len > limits.max_len {
+            ValidationState::Invalid(AddQuestionError::MaxLength(trimmed_len, limits.max_len))
+        } else if words < limits.min_words {
+            ValidationState::Invalid(AddQuestionError::MinWordCount(words, limits.min_words))
         } else if v
             .split_ascii_whitespace()
             .any(|word| word.len() > WORD_LEN_MAX)
@@ -48,3 +46,37 @@ impl AddQuestionValidation {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event_limits_apply() {
+        let mut v = AddQuestionValidation::default();
+        let text = "why so short?";
+
+        v.check(text, &QuestionLimits::default());
+        assert!(!v.has_any());
+
+        let strict = QuestionLimits {
+            min_len: 20,
+            ..QuestionLimits::default()
+        };
+        v.check(text, &strict);
+        assert!(matches!(
+            v.content,
+            ValidationState::Invalid(AddQuestionError::MinLength(13, 20))
+        ));
+
+        let workshop = QuestionLimits {
+            max_len: 50,
+            min_words: 1,
+            ..QuestionLimits::default()
+        };
+        v.check("x".repeat(25).as_str(), &workshop);
+        assert!(!v.has_any());
+        v.check(&format!("{} ?", "word ".repeat(11)), &workshop);
+        assert!(v.has_any());
+    }
+}
@@ -0,0 +1,80 @@
+use super::ValidationState;
+
+#[derive(Debug)]
+pub enum ContentFilterError {
+    MaxEntries(usize, usize),
+    EntryMinLength(usize, usize),
+    EntryMaxLength(usize, usize),
+}
+
+const MAX_ENTRIES: usize = 100;
+const ENTRY_TRIMMED_MIN_LEN: usize = 2;
+const ENTRY_MAX_LEN: usize = 30;
+
+#[derive(Default, Debug)]
+pub struct ContentFilterValidation {
+    pub blocklist: ValidationState<ContentFilterError>,
+}
+
+impl ContentFilterValidation {
+    pub fn check(&mut self, blocklist: &[String]) {
+        self.blocklist = Self::check_blocklist(blocklist);
+    }
+
+    #[must_use]
+    pub const fn has_any(&self) -> bool {
+        self.blocklist.is_invalid()
+    }
+
+    fn check_blocklist(list: &[String]) -> ValidationState<ContentFilterError> {
+        if list.len() > MAX_ENTRIES {
+            return ValidationState::Invalid(ContentFilterError::MaxEntries(
+                list.len(),
+                MAX_ENTRIES,
+            ));
+        }
+
+        for entry in list {
+            let trimmed_len = entry.trim().len();
+
+            if trimmed_len < ENTRY_TRIMMED_MIN_LEN {
+                return ValidationState::Invalid(ContentFilterError::EntryMinLength(
+                    trimmed_len,
+                    ENTRY_TRIMMED_MIN_LEN,
+                ));
+            } else if entry.len() > ENTRY_MAX_LEN {
+                return ValidationState::Invalid(ContentFilterError::EntryMaxLength(
+                    entry.len(),
+                    ENTRY_MAX_LEN,
+                ));
+            }
+        }
+
+        ValidationState::Valid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blocklist_bounds() {
+        let mut v = ContentFilterValidation::default();
+
+        v.check(&[]);
+        assert!(!v.has_any());
+
+        v.check(&[String::from("spam"), String::from("buy now")]);
+        assert!(!v.has_any());
+
+        v.check(&[String::from(" x ")]);
+        assert!(v.has_any());
+
+        v.check(&[String::from("x").repeat(31)]);
+        assert!(v.has_any());
+
+        v.check(&vec![String::from("word"); 101]);
+        assert!(v.has_any());
+    }
+}
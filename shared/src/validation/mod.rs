@@ -1,7 +1,12 @@
 pub mod add_question;
+pub mod content_filter;
 pub mod context_validation;
 pub mod create_event;
+pub mod participant_name;
+pub mod poll_validation;
 pub mod pwd_validation;
+pub mod question_limits;
+pub mod session_validation;
 pub mod tag_validation;
 
 #[derive(Debug, Default)]
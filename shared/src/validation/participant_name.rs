@@ -0,0 +1,83 @@
+use super::ValidationState;
+use crate::ParticipantNames;
+
+#[derive(Debug)]
+pub enum ParticipantNameError {
+    NotAllowed,
+    Required,
+    MinLength(usize, usize),
+    MaxLength(usize, usize),
+}
+
+const TRIMMED_MIN_LEN: usize = 2;
+const MAX_LEN: usize = 30;
+
+#[derive(Default, Debug)]
+pub struct ParticipantNameValidation {
+    pub name: ValidationState<ParticipantNameError>,
+}
+
+impl ParticipantNameValidation {
+    /// an empty or blank `name` counts as none given
+    pub fn check(&mut self, name: Option<&str>, mode: ParticipantNames) {
+        self.name = Self::check_name(name.map(str::trim).filter(|name| !name.is_empty()), mode);
+    }
+
+    #[must_use]
+    pub const fn has_any(&self) -> bool {
+        self.name.is_invalid()
+    }
+
+    fn check_name(
+        name: Option<&str>,
+        mode: ParticipantNames,
+    ) -> ValidationState<ParticipantNameError> {
+        match (name, mode) {
+            (None, ParticipantNames::Required) => {
+                ValidationState::Invalid(ParticipantNameError::Required)
+            }
+            (None, _) => ValidationState::Valid,
+            (Some(_), ParticipantNames::Anonymous) => {
+                ValidationState::Invalid(ParticipantNameError::NotAllowed)
+            }
+            (Some(name), _) => {
+                let len = name.chars().count();
+
+                if len < TRIMMED_MIN_LEN {
+                    ValidationState::Invalid(ParticipantNameError::MinLength(len, TRIMMED_MIN_LEN))
+                } else if len > MAX_LEN {
+                    ValidationState::Invalid(ParticipantNameError::MaxLength(len, MAX_LEN))
+                } else {
+                    ValidationState::Valid
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_name_modes() {
+        let mut v = ParticipantNameValidation::default();
+
+        v.check(None, ParticipantNames::Anonymous);
+        assert!(!v.has_any());
+        v.check(Some("Jo"), ParticipantNames::Anonymous);
+        assert!(v.has_any());
+
+        v.check(Some("  "), ParticipantNames::Optional);
+        assert!(!v.has_any());
+        v.check(Some("Jo"), ParticipantNames::Optional);
+        assert!(!v.has_any());
+
+        v.check(Some(" "), ParticipantNames::Required);
+        assert!(v.has_any());
+        v.check(Some("J"), ParticipantNames::Required);
+        assert!(v.has_any());
+        v.check(Some(&"x".repeat(31)), ParticipantNames::Required);
+        assert!(v.has_any());
+    }
+}
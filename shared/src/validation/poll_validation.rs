@@ -0,0 +1,100 @@
+use super::ValidationState;
+
+#[derive(Debug)]
+pub enum PollError {
+    MinLength(usize, usize),
+    MaxLength(usize, usize),
+    MinOptions(usize, usize),
+    MaxOptions(usize, usize),
+    OptionMinLength(usize, usize),
+    OptionMaxLength(usize, usize),
+}
+
+const QUESTION_TRIMMED_MIN_LEN: usize = 3;
+const QUESTION_MAX_LEN: usize = 100;
+const MIN_OPTIONS: usize = 2;
+const MAX_OPTIONS: usize = 10;
+const OPTION_TRIMMED_MIN_LEN: usize = 1;
+const OPTION_MAX_LEN: usize = 50;
+
+#[derive(Default, Debug)]
+pub struct PollValidation {
+    pub question: ValidationState<PollError>,
+    pub options: ValidationState<PollError>,
+}
+
+impl PollValidation {
+    pub fn check(&mut self, question: &str, options: &[String]) {
+        self.question = Self::check_question(question);
+        self.options = Self::check_options(options);
+    }
+
+    #[must_use]
+    pub const fn has_any(&self) -> bool {
+        self.question.is_invalid() || self.options.is_invalid()
+    }
+
+    fn check_question(v: &str) -> ValidationState<PollError> {
+        let trimmed_len = v.trim().chars().count();
+
+        if trimmed_len < QUESTION_TRIMMED_MIN_LEN {
+            ValidationState::Invalid(PollError::MinLength(trimmed_len, QUESTION_TRIMMED_MIN_LEN))
+        } else if trimmed_len > QUESTION_MAX_LEN {
+            ValidationState::Invalid(PollError::MaxLength(trimmed_len, QUESTION_MAX_LEN))
+        } else {
+            ValidationState::Valid
+        }
+    }
+
+    fn check_options(options: &[String]) -> ValidationState<PollError> {
+        if options.len() < MIN_OPTIONS {
+            return ValidationState::Invalid(PollError::MinOptions(options.len(), MIN_OPTIONS));
+        } else if options.len() > MAX_OPTIONS {
+            return ValidationState::Invalid(PollError::MaxOptions(options.len(), MAX_OPTIONS));
+        }
+
+        for option in options {
+            let trimmed_len = option.trim().chars().count();
+
+            if trimmed_len < OPTION_TRIMMED_MIN_LEN {
+                return ValidationState::Invalid(PollError::OptionMinLength(
+                    trimmed_len,
+                    OPTION_TRIMMED_MIN_LEN,
+                ));
+            } else if trimmed_len > OPTION_MAX_LEN {
+                return ValidationState::Invalid(PollError::OptionMaxLength(
+                    trimmed_len,
+                    OPTION_MAX_LEN,
+                ));
+            }
+        }
+
+        ValidationState::Valid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_poll_bounds() {
+        let mut v = PollValidation::default();
+        let options = |list: &[&str]| list.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        v.check("Lunch?", &options(&["pizza", "salad"]));
+        assert!(!v.has_any());
+
+        v.check("  ", &options(&["pizza", "salad"]));
+        assert!(v.has_any());
+
+        v.check("Lunch?", &options(&["pizza"]));
+        assert!(v.has_any());
+
+        v.check("Lunch?", &options(&["pizza", " "]));
+        assert!(v.has_any());
+
+        v.check("Lunch?", &vec![String::from("x"); 11]);
+        assert!(v.has_any());
+    }
+}
@@ -0,0 +1,177 @@
+use super::ValidationState;
+use crate::QuestionLimits;
+
+#[derive(Debug)]
+pub enum QuestionLimitsError {
+    MinLength(usize),
+    MaxLength(usize),
+    MinWordCount(usize),
+    MaxQuestions(usize),
+    /// `min_len` above `max_len`, no question could pass
+    MinAboveMaxLength(usize),
+}
+
+/// Global bounds any event specific limit has to stay within. The questions are stored in the
+/// event item, the upper bounds keep a full event below dynamodb's 400KB item limit.
+pub const MIN_LEN_BOUNDS: (usize, usize) = (3, 50);
+pub const MAX_LEN_BOUNDS: (usize, usize) = (50, 400);
+pub const MIN_WORDS_BOUNDS: (usize, usize) = (1, 10);
+pub const MAX_QUESTIONS_BOUNDS: (usize, usize) = (10, 500);
+
+#[derive(Default, Debug)]
+pub struct QuestionLimitsValidation {
+    pub limits: ValidationState<QuestionLimitsError>,
+}
+
+impl QuestionLimitsValidation {
+    pub fn check(&mut self, limits: &QuestionLimits) {
+        self.limits = Self::check_limits(limits);
+    }
+
+    #[must_use]
+    pub const fn has_any(&self) -> bool {
+        self.limits.is_invalid()
+    }
+
+    const fn check_limits(limits: &QuestionLimits) -> ValidationState<QuestionLimitsError> {
+        if limits.min_len > limits.max_len {
+            ValidationState::Invalid(QuestionLimitsError::MinAboveMaxLength(limits.min_len))
+        } else if !within(limits.min_len, MIN_LEN_BOUNDS) {
+            ValidationState::Invalid(QuestionLimitsError::MinLength(limits.min_len))
+        } else if !within(limits.max_len, MAX_LEN_BOUNDS) {
+            ValidationState::Invalid(QuestionLimitsError::MaxLength(limits.max_len))
+        } else if !within(limits.min_words, MIN_WORDS_BOUNDS) {
+            ValidationState::Invalid(QuestionLimitsError::MinWordCount(limits.min_words))
+        } else if !within(limits.max_questions, MAX_QUESTIONS_BOUNDS) {
+            ValidationState::Invalid(QuestionLimitsError::MaxQuestions(limits.max_questions))
+        } else {
+            ValidationState::Valid
+        }
+    }
+}
+
+const fn within(value: usize, (min, max): (usize, usize)) -> bool {
+    value >= min && value <= max
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// what the questions may take of the event item, the rest is description, polls, sessions..
+    const QUESTIONS_BUDGET_BYTES: usize = 300 * 1024;
+    /// a stored question besides its text: attribute names, id, likes, time, flags, tag, session
+    /// and a participant name of up to 30 bytes
+    const QUESTION_OVERHEAD_BYTES: usize = 200;
+
+    #[test]
+    fn test_full_event_fits_item() {
+        let full = MAX_QUESTIONS_BOUNDS.1 * (MAX_LEN_BOUNDS.1 + QUESTION_OVERHEAD_BYTES);
+
+        assert!(full <= QUESTIONS_BUDGET_BYTES, "{full} bytes");
+    }
+
+    fn valid(limits: QuestionLimits) -> bool {
+        let mut v = QuestionLimitsValidation::default();
+        v.check(&limits);
+        !v.has_any()
+    }
+
+    #[test]
+    fn test_limit_bounds() {
+        let limits = QuestionLimits::default();
+        assert!(valid(limits));
+
+        // min_len within 3..=50
+        assert!(!valid(QuestionLimits {
+            min_len: 2,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            min_len: 3,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            min_len: 50,
+            ..limits
+        }));
+        assert!(!valid(QuestionLimits {
+            min_len: 51,
+            ..limits
+        }));
+
+        // max_len within 50..=400
+        assert!(!valid(QuestionLimits {
+            max_len: 49,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            max_len: 50,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            max_len: 400,
+            ..limits
+        }));
+        assert!(!valid(QuestionLimits {
+            max_len: 401,
+            ..limits
+        }));
+
+        // min_words within 1..=10
+        assert!(!valid(QuestionLimits {
+            min_words: 0,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            min_words: 1,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            min_words: 10,
+            ..limits
+        }));
+        assert!(!valid(QuestionLimits {
+            min_words: 11,
+            ..limits
+        }));
+
+        // max_questions within 10..=500
+        assert!(!valid(QuestionLimits {
+            max_questions: 9,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            max_questions: 10,
+            ..limits
+        }));
+        assert!(valid(QuestionLimits {
+            max_questions: 500,
+            ..limits
+        }));
+        assert!(!valid(QuestionLimits {
+            max_questions: 501,
+            ..limits
+        }));
+    }
+
+    #[test]
+    fn test_min_len_above_max_len() {
+        assert!(valid(QuestionLimits {
+            min_len: 50,
+            max_len: 50,
+            ..QuestionLimits::default()
+        }));
+
+        let mut v = QuestionLimitsValidation::default();
+        v.check(&QuestionLimits {
+            min_len: 51,
+            max_len: 50,
+            ..QuestionLimits::default()
+        });
+        assert!(matches!(
+            v.limits,
+            ValidationState::Invalid(QuestionLimitsError::MinAboveMaxLength(51))
+        ));
+    }
+}
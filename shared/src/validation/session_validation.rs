@@ -0,0 +1,81 @@
+use super::ValidationState;
+use crate::SessionData;
+
+#[derive(Debug)]
+pub enum SessionError {
+    MaxLength(usize, usize),
+    MinLength(usize, usize),
+    EndBeforeStart,
+}
+
+const TRIMMED_MIN_LEN: usize = 3;
+const MAX_LEN: usize = 40;
+
+#[derive(Default, Debug)]
+pub struct SessionValidation {
+    pub name: ValidationState<SessionError>,
+    pub time: ValidationState<SessionError>,
+}
+
+impl SessionValidation {
+    pub fn check(&mut self, data: &SessionData) {
+        self.name = Self::check_name(&data.name);
+        self.time = Self::check_time(data.start_unix, data.end_unix);
+    }
+
+    #[must_use]
+    pub const fn has_any(&self) -> bool {
+        self.name.is_invalid() || self.time.is_invalid()
+    }
+
+    fn check_name(v: &str) -> ValidationState<SessionError> {
+        let trimmed_len = v.trim().chars().count();
+
+        if trimmed_len < TRIMMED_MIN_LEN {
+            ValidationState::Invalid(SessionError::MinLength(trimmed_len, TRIMMED_MIN_LEN))
+        } else if trimmed_len > MAX_LEN {
+            ValidationState::Invalid(SessionError::MaxLength(trimmed_len, MAX_LEN))
+        } else {
+            ValidationState::Valid
+        }
+    }
+
+    const fn check_time(start: Option<i64>, end: Option<i64>) -> ValidationState<SessionError> {
+        match (start, end) {
+            (Some(start), Some(end)) if end <= start => {
+                ValidationState::Invalid(SessionError::EndBeforeStart)
+            }
+            _ => ValidationState::Valid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_session_validation() {
+        let mut v = SessionValidation::default();
+
+        v.check(&SessionData {
+            name: String::from("keynote"),
+            start_unix: Some(100),
+            end_unix: Some(200),
+        });
+        assert!(!v.has_any());
+
+        v.check(&SessionData {
+            name: String::from(" k "),
+            ..SessionData::default()
+        });
+        assert!(v.has_any());
+
+        v.check(&SessionData {
+            name: String::from("keynote"),
+            start_unix: Some(200),
+            end_unix: Some(200),
+        });
+        assert!(v.has_any());
+    }
+}